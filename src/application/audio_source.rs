@@ -1,35 +1,86 @@
-use std::pin::Pin;
+use std::sync::Arc;
 
 use anywho::Error;
+use futures::future::BoxFuture;
+use tokio::sync::mpsc::Sender;
 
 use crate::{
-    domain::{entities::audio_source_layer::AudioSourceLayer, ports::audio_source::AudioSource},
+    domain::{
+        entities::audio_source_layer::AudioSourceLayer,
+        ports::audio_source::{AudioSource, OutboundFrame, SessionEvent},
+    },
     infrastructure::audio_source::{
         local_source_adapter::LocalAdapter, twilio_source_adapter::TwilioAdapter,
     },
 };
 
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub enum AudioSourceList {
     Twilio(TwilioAdapter),
     Local(LocalAdapter),
+    /// An adapter registered by the embedding application.
+    Custom(Arc<dyn AudioSource>),
 }
 
 impl AudioSource for AudioSourceList {
-    async fn handle(&self, buffers: &mut AudioSourceLayer<'_>) -> Result<(), Error> {
+    fn connect(&self, outbound: Sender<OutboundFrame>) -> Arc<dyn AudioSource> {
+        match self {
+            AudioSourceList::Twilio(adapter) => adapter.connect(outbound),
+            AudioSourceList::Local(adapter) => adapter.connect(outbound),
+            AudioSourceList::Custom(adapter) => adapter.connect(outbound),
+        }
+    }
+
+    fn handle<'a>(
+        &'a self,
+        buffers: &'a mut AudioSourceLayer<'_>,
+    ) -> BoxFuture<'a, Result<(), Error>> {
         match self {
-            AudioSourceList::Twilio(adapter) => adapter.handle(buffers).await,
-            AudioSourceList::Local(adapter) => adapter.handle(buffers).await,
+            AudioSourceList::Twilio(adapter) => adapter.handle(buffers),
+            AudioSourceList::Local(adapter) => adapter.handle(buffers),
+            AudioSourceList::Custom(adapter) => adapter.handle(buffers),
         }
     }
 
-    fn send_audio(
-        &self,
-        bytes: &Vec<i16>,
-    ) -> Pin<Box<dyn Future<Output = Result<(), Error>> + Send>> {
+    fn send_audio(&self, bytes: &[i16]) -> BoxFuture<'static, Result<(), Error>> {
         match self {
             AudioSourceList::Twilio(adapter) => adapter.send_audio(bytes),
             AudioSourceList::Local(adapter) => adapter.send_audio(bytes),
+            AudioSourceList::Custom(adapter) => adapter.send_audio(bytes),
+        }
+    }
+
+    fn clear_audio(&self) -> BoxFuture<'static, Result<(), Error>> {
+        match self {
+            AudioSourceList::Twilio(adapter) => adapter.clear_audio(),
+            AudioSourceList::Local(adapter) => adapter.clear_audio(),
+            AudioSourceList::Custom(adapter) => adapter.clear_audio(),
+        }
+    }
+
+    fn send_event(&self, event: SessionEvent) -> BoxFuture<'static, Result<(), Error>> {
+        match self {
+            AudioSourceList::Twilio(adapter) => adapter.send_event(event),
+            AudioSourceList::Local(adapter) => adapter.send_event(event),
+            AudioSourceList::Custom(adapter) => adapter.send_event(event),
         }
     }
 }
+
+impl From<TwilioAdapter> for AudioSourceList {
+    fn from(adapter: TwilioAdapter) -> Self {
+        AudioSourceList::Twilio(adapter)
+    }
+}
+
+impl From<LocalAdapter> for AudioSourceList {
+    fn from(adapter: LocalAdapter) -> Self {
+        AudioSourceList::Local(adapter)
+    }
+}
+
+impl From<Arc<dyn AudioSource>> for AudioSourceList {
+    fn from(adapter: Arc<dyn AudioSource>) -> Self {
+        AudioSourceList::Custom(adapter)
+    }
+}
@@ -0,0 +1,307 @@
+use std::{
+    path::Path,
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+    time::Duration,
+};
+
+use anywho::Error;
+use tokio::{
+    spawn,
+    sync::mpsc::channel,
+    time::{Instant, sleep_until},
+};
+use tracing::{info, warn};
+
+use crate::{
+    application::{http::app_state::AppState, registry::ProviderRegistry},
+    domain::{
+        entities::{
+            agent_profile::VadOverrides,
+            audio_buffer::AudioBuffer,
+            audio_source_layer::{AudioSourceLayer, FRAME, SessionCallbacks},
+            history::{history::History, history_member::HistoryMember},
+        },
+        ports::{
+            audio_source::{AudioSource, OutboundFrame},
+            stt::Stt,
+            vad::{Vad, VadEvent},
+        },
+        utils::{
+            Utils,
+            units::{Millis, SampleRate, Samples},
+        },
+    },
+    infrastructure::{
+        audio_source::file_source_adapter::FileAudioSource,
+        capture::capture_file::{CaptureFile, Direction},
+        llm::mock_llm::MockLlm,
+        stt::mock_stt::MockStt,
+        tts::mock_tts::MockTts,
+        vad::local_vad::LocalVadAdapter,
+    },
+};
+
+/// Splits the file into turns with the VAD and prints the transcript of each.
+pub async fn transcribe(state: &AppState, path: &Path) -> Result<(), Error> {
+    let agent = state.agents.default_profile();
+    let mut vad = state
+        .providers
+        .vad(agent.providers.vad.as_deref())
+        .ok_or_else(|| Error::msg("No VAD registered"))?;
+    let stt = state
+        .providers
+        .stt(agent.providers.stt.as_deref())
+        .ok_or_else(|| Error::msg("No STT registered"))?;
+    vad.configure(&state.agents.vad_settings(&agent, &VadOverrides::default()));
+
+    let pcm = FileAudioSource::read_pcm(path)?;
+    let mut audio_buffer = AudioBuffer::new();
+    let mut segments = Vec::<(Samples, Samples)>::new();
+    let mut pending = None;
+
+    // trailing silence lets the VAD close the last turn
+    let silence = vec![0; SampleRate::PIPELINE.samples(Millis(5000)).as_usize()];
+    let frame = SampleRate::PIPELINE.samples(FRAME).as_usize();
+    for frame in pcm.chunks(frame).chain(silence.chunks(frame)) {
+        audio_buffer.push_user(frame);
+
+        loop {
+            match vad.process_audio(&mut audio_buffer) {
+                VadEvent::SpeechPaused(start, end) => pending = Some((start, end)),
+                VadEvent::SpeechFullStop => segments.extend(pending.take()),
+                VadEvent::WaitingMoreChunks => break,
+                _ => {}
+            }
+        }
+    }
+    segments.extend(pending);
+
+    for (start, end) in segments {
+        let end = end.min(Samples::of(&pcm));
+        if start >= end {
+            continue;
+        }
+
+        let text = stt
+            .execute(&pcm[start.as_usize()..end.as_usize()], &agent.stt_language)
+            .await?
+            .text
+            .unwrap_or_default();
+
+        println!(
+            "[{} - {}] {}",
+            timestamp(start),
+            timestamp(end),
+            text.trim()
+        );
+    }
+
+    Ok(())
+}
+
+/// Plays the file as a caller through the full pipeline and prints the history.
+pub async fn simulate(
+    state: &AppState,
+    path: &Path,
+    speed: f32,
+    agent: Option<&str>,
+) -> Result<(), Error> {
+    let file_source = FileAudioSource::new(path.to_path_buf(), speed);
+    let audio_source: Arc<dyn AudioSource> = Arc::new(file_source.clone());
+
+    let mut vad = state
+        .providers
+        .vad(None)
+        .ok_or_else(|| Error::msg("No VAD registered"))?;
+    let mut history = History::new();
+    let mut audio_buffer = AudioBuffer::new();
+    let mut audio_source_layer = AudioSourceLayer::new(
+        Utils::generate_uuid(),
+        state,
+        &state.providers,
+        SessionCallbacks::from_source(audio_source.clone()),
+        &mut vad,
+        &mut history,
+        &mut audio_buffer,
+    )?;
+
+    audio_source_layer.apply_agent(state.agents.get(agent));
+    info!(
+        "Simulating {} as session {}",
+        path.display(),
+        audio_source_layer.id
+    );
+
+    audio_source.handle(&mut audio_source_layer).await?;
+
+    audio_source_layer.drain_pipeline().await;
+    print_history(audio_source_layer.history);
+
+    let agent_audio = file_source.agent_audio().await;
+    println!(
+        "agent audio: {} ms",
+        SampleRate::PIPELINE.millis(Samples::of(&agent_audio)).0
+    );
+
+    Ok(())
+}
+
+/// What a replayed session produced, next to what was recorded.
+pub struct ReplayOutcome {
+    pub history: History,
+    /// Agent frames of the capture.
+    pub recorded: usize,
+    /// Agent frames sent during the replay.
+    pub replayed: usize,
+}
+
+/// Replays the capture at `path` and prints the history.
+pub async fn replay(state: &AppState, path: &Path, speed: f32, mock: bool) -> Result<(), Error> {
+    let outcome = replay_session(state, path, speed, mock).await?;
+
+    print_history(&outcome.history);
+    println!(
+        "agent frames: {} recorded, {} replayed",
+        outcome.recorded, outcome.replayed
+    );
+
+    Ok(())
+}
+
+/// Feeds a recorded session back through the audio source it was served by,
+/// with the original timing scaled by `speed`. With `mock`, scripted providers
+/// answer instead of the live ones.
+pub async fn replay_session(
+    state: &AppState,
+    path: &Path,
+    speed: f32,
+    mock: bool,
+) -> Result<ReplayOutcome, Error> {
+    let capture = CaptureFile::read(path)?;
+    let protocol = capture.header.protocol.as_str();
+    let providers = match mock {
+        true => mock_providers(),
+        false => state.providers.clone(),
+    };
+
+    // agent frames are only counted, to be compared with the recorded ones
+    let (outbound_tx, mut outbound_rx) = channel::<OutboundFrame>(256);
+    let replayed = Arc::new(AtomicUsize::new(0));
+    spawn({
+        let replayed = replayed.clone();
+        async move {
+            while outbound_rx.recv().await.is_some() {
+                replayed.fetch_add(1, Ordering::SeqCst);
+            }
+        }
+    });
+
+    let audio_source = state
+        .providers
+        .audio_source(protocol)
+        .map(|source| source.connect(outbound_tx))
+        .ok_or_else(|| Error::msg(format!("No {} audio source registered", protocol)))?;
+
+    let mut vad = providers
+        .vad(None)
+        .ok_or_else(|| Error::msg("No VAD registered"))?;
+    let mut history = History::new();
+    let mut audio_buffer = AudioBuffer::new();
+    let mut audio_source_layer = AudioSourceLayer::new(
+        Utils::generate_uuid(),
+        state,
+        &providers,
+        SessionCallbacks::from_source(audio_source.clone()),
+        &mut vad,
+        &mut history,
+        &mut audio_buffer,
+    )?;
+
+    audio_source_layer.vad_overrides = capture.header.vad;
+    audio_source_layer.apply_agent(state.agents.get(capture.header.agent.as_deref()));
+    info!(
+        "Replaying {} session {} recorded {} as session {}",
+        protocol, capture.header.session, capture.header.started_at, audio_source_layer.id
+    );
+
+    let started = Instant::now();
+    for frame in capture.inbound() {
+        if speed > 0.0 {
+            let offset = Duration::from_micros(frame.at_us).div_f32(speed);
+            sleep_until(started + offset).await;
+        }
+
+        audio_source_layer
+            .audio_buffer
+            .override_streamed_buffer(frame.payload.inbound_frame()?);
+
+        if let Err(err) = audio_source.handle(&mut audio_source_layer).await {
+            warn!("Replayed frame failed: {:?}", err);
+        }
+
+        if audio_source_layer.ended {
+            break;
+        }
+    }
+
+    audio_source_layer.drain_pipeline().await;
+    drop(audio_source_layer);
+
+    let recorded = capture
+        .frames
+        .iter()
+        .filter(|frame| frame.direction == Direction::Outbound)
+        .count();
+
+    Ok(ReplayOutcome {
+        history,
+        recorded,
+        replayed: replayed.load(Ordering::SeqCst),
+    })
+}
+
+/// Providers answering every turn with the same canned text.
+fn mock_providers() -> ProviderRegistry {
+    let mut providers = ProviderRegistry::new();
+    providers
+        .register_stt(
+            "mock",
+            MockStt::new(Vec::new()).with_fallback("(transcription simulée)"),
+        )
+        .register_llm(
+            "mock",
+            MockLlm::new(Vec::new()).with_fallback(MockLlm::text("Réponse simulée.")),
+        )
+        .register_tts("mock", MockTts::new())
+        .register_vad("local", LocalVadAdapter::new);
+
+    providers
+}
+
+fn print_history(history: &History) {
+    for event in history.events.iter() {
+        match (&event.member, &event.tool_call) {
+            (HistoryMember::ToolCall, Some(call)) => {
+                println!(
+                    "{}: {}({}) -> {}",
+                    event.member, call.name, call.arguments, call.result
+                )
+            }
+            // a tool round where the agent said nothing
+            (HistoryMember::Agent, None) if event.content.is_none() => {}
+            _ => println!(
+                "{}: {}",
+                event.member,
+                event.content.as_deref().unwrap_or_default()
+            ),
+        }
+    }
+}
+
+fn timestamp(sample: Samples) -> String {
+    let Millis(ms) = SampleRate::PIPELINE.millis(sample);
+    format!("{:02}:{:02}.{:03}", ms / 60_000, ms / 1000 % 60, ms % 1000)
+}
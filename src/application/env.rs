@@ -1,15 +1,29 @@
 use clap::Parser;
 
 use crate::application::env::{
-    aistudio::AiStudioEnv, elevenlabs::ElevenLabsEnv, logger::LoggerEnv,
+    agent::AgentEnv, aistudio::AiStudioEnv, audio::AudioEnv, capture::CaptureEnv, command::Command,
+    elevenlabs::ElevenLabsEnv, gmm_vad::GmmVadEnv, logger::LoggerEnv, silero::SileroEnv,
+    tools::ToolsEnv, turn_detector::TurnDetectorEnv, vad::VadEnv,
 };
 
+pub mod agent;
 pub mod aistudio;
+pub mod audio;
+pub mod capture;
+pub mod command;
 pub mod elevenlabs;
+pub mod gmm_vad;
 pub mod logger;
+pub mod silero;
+pub mod tools;
+pub mod turn_detector;
+pub mod vad;
 
 #[derive(Debug, Clone, Parser)]
 pub struct Args {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
     #[command(flatten)]
     pub elevenlabs: ElevenLabsEnv,
 
@@ -18,4 +32,28 @@ pub struct Args {
 
     #[command(flatten)]
     pub llm: AiStudioEnv,
+
+    #[command(flatten)]
+    pub agent: AgentEnv,
+
+    #[command(flatten)]
+    pub tools: ToolsEnv,
+
+    #[command(flatten)]
+    pub capture: CaptureEnv,
+
+    #[command(flatten)]
+    pub audio: AudioEnv,
+
+    #[command(flatten)]
+    pub silero: SileroEnv,
+
+    #[command(flatten)]
+    pub gmm_vad: GmmVadEnv,
+
+    #[command(flatten)]
+    pub vad: VadEnv,
+
+    #[command(flatten)]
+    pub turn_detector: TurnDetectorEnv,
 }
@@ -0,0 +1,85 @@
+use std::path::PathBuf;
+
+use anywho::Error;
+use clap::ValueEnum;
+
+use crate::domain::{
+    entities::{
+        agent_profile::{AgentProfile, VadSettings},
+        agent_registry::AgentRegistry,
+        barge_in::BargeInMode,
+    },
+    utils::units::Millis,
+};
+
+#[derive(clap::Args, Debug, Clone)]
+pub struct AgentEnv {
+    #[arg(
+        long,
+        env = "AGENT_BARGE_IN",
+        name = "AGENT_BARGE_IN",
+        help = "How the agent reacts when the caller talks over it, unless its profile sets barge_in",
+        default_value = "immediate"
+    )]
+    pub barge_in: BargeInKind,
+
+    #[arg(
+        long,
+        env = "AGENT_BARGE_IN_MIN_SPEECH_MS",
+        name = "AGENT_BARGE_IN_MIN_SPEECH_MS",
+        help = "Speech duration required before interrupting the agent in sustained mode",
+        default_value_t = 300
+    )]
+    pub barge_in_min_speech_ms: u64,
+
+    #[arg(
+        long,
+        env = "AGENT_PROFILES_FILE",
+        name = "AGENT_PROFILES_FILE",
+        help = "JSON file declaring the agent profiles served by this deployment"
+    )]
+    pub agent_profiles_file: Option<PathBuf>,
+
+    #[arg(
+        long,
+        env = "AGENT_DEFAULT",
+        name = "AGENT_DEFAULT",
+        help = "Profile used when a session does not ask for a specific agent"
+    )]
+    pub agent_default: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, ValueEnum, Default)]
+pub enum BargeInKind {
+    Off,
+    #[default]
+    Immediate,
+    Sustained,
+}
+
+impl AgentEnv {
+    pub fn barge_in_mode(&self) -> BargeInMode {
+        match self.barge_in {
+            BargeInKind::Off => BargeInMode::Off,
+            BargeInKind::Immediate => BargeInMode::Immediate,
+            BargeInKind::Sustained => BargeInMode::Sustained(Millis(self.barge_in_min_speech_ms)),
+        }
+    }
+
+    pub fn agents(&self, vad: VadSettings) -> Result<AgentRegistry, Error> {
+        let profiles = match &self.agent_profiles_file {
+            Some(path) => {
+                let content = std::fs::read_to_string(path)?;
+                serde_json::from_str::<Vec<AgentProfile>>(&content)?
+            }
+            None => Vec::new(),
+        };
+
+        AgentRegistry::new(
+            profiles,
+            self.agent_default.clone(),
+            vad,
+            self.barge_in_mode(),
+        )
+    }
+}
@@ -1,16 +1,32 @@
 #[derive(clap::Args, Debug, Clone)]
 pub struct AiStudioEnv {
     #[arg(
+        long,
         env = "LLM_AISTUDIO_GOOGLE_API_KEY",
         name = "LLM_AISTUDIO_GOOGLE_API_KEY",
-        help = "The AI Studio API key"
+        help = "The AI Studio API key, not needed to transcribe or to replay with --mock"
     )]
-    pub aistudio_api_key: String,
+    pub aistudio_api_key: Option<String>,
 
     #[arg(
+        long,
         env = "LLM_AISTUDIO_BASE_URL",
         name = "LLM_AISTUDIO_BASE_URL",
-        help = "The AI Studio base URL"
+        help = "The AI Studio base URL, not needed to transcribe or to replay with --mock"
     )]
-    pub aistudio_base_url: String,
+    pub aistudio_base_url: Option<String>,
+}
+
+impl AiStudioEnv {
+    /// Settings the live providers need but were not given.
+    pub fn missing(&self) -> Vec<&'static str> {
+        let mut missing = Vec::new();
+        if self.aistudio_api_key.is_none() {
+            missing.push("LLM_AISTUDIO_GOOGLE_API_KEY");
+        }
+        if self.aistudio_base_url.is_none() {
+            missing.push("LLM_AISTUDIO_BASE_URL");
+        }
+        missing
+    }
 }
@@ -0,0 +1,20 @@
+use crate::domain::utils::units::Millis;
+
+#[derive(clap::Args, Debug, Clone)]
+pub struct AudioEnv {
+    #[arg(
+        long,
+        env = "AUDIO_RETENTION_MS",
+        name = "AUDIO_RETENTION_MS",
+        help = "Caller audio kept in memory before the active turn, older audio is dropped (at least 1000, the VAD pre-roll must fit)",
+        value_parser = clap::value_parser!(u64).range(1000..),
+        default_value_t = 10_000
+    )]
+    pub audio_retention_ms: u64,
+}
+
+impl AudioEnv {
+    pub fn retention(&self) -> Millis {
+        Millis(self.audio_retention_ms)
+    }
+}
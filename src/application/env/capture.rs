@@ -0,0 +1,22 @@
+use std::path::PathBuf;
+
+#[derive(clap::Args, Debug, Clone)]
+pub struct CaptureEnv {
+    #[arg(
+        long,
+        env = "CAPTURE_DIR",
+        name = "CAPTURE_DIR",
+        help = "Directory where every WebSocket session is recorded for replay"
+    )]
+    pub capture_dir: Option<PathBuf>,
+
+    #[arg(
+        long,
+        env = "CAPTURE_AUDIO",
+        name = "CAPTURE_AUDIO",
+        help = "Also write the whole caller audio of each session as a WAV file in CAPTURE_DIR",
+        action = clap::ArgAction::Set,
+        default_value_t = false
+    )]
+    pub capture_audio: bool,
+}
@@ -0,0 +1,58 @@
+use std::path::PathBuf;
+
+use clap::Subcommand;
+
+use crate::application::env::{aistudio::AiStudioEnv, elevenlabs::ElevenLabsEnv};
+
+#[derive(Subcommand, Debug, Clone, Default)]
+pub enum Command {
+    /// Serve the Twilio and local WebSocket endpoints
+    #[default]
+    Serve,
+
+    /// Split a WAV file into turns with the VAD and transcribe each of them
+    Transcribe {
+        /// 16 bits PCM WAV file, resampled from 8, 16, 22.05, 24, 44.1 or 48 kHz
+        wav: PathBuf,
+    },
+
+    /// Run the whole agent pipeline on a WAV file and print the resulting history
+    Simulate {
+        /// 16 bits PCM WAV file, resampled from 8, 16, 22.05, 24, 44.1 or 48 kHz
+        wav: PathBuf,
+
+        /// How many times faster than real time the file is played, 0 for no pacing
+        #[arg(long, default_value_t = 1.0)]
+        speed: f32,
+
+        /// Agent profile to run
+        #[arg(long)]
+        agent: Option<String>,
+    },
+
+    /// Feed a session recorded in CAPTURE_DIR back through its audio source
+    Replay {
+        /// Capture file written by the recorder
+        capture: PathBuf,
+
+        /// How many times faster than the original call frames are fed, 0 for no pacing
+        #[arg(long, default_value_t = 1.0)]
+        speed: f32,
+
+        /// Answer with scripted providers instead of the configured ones
+        #[arg(long)]
+        mock: bool,
+    },
+}
+
+impl Command {
+    /// Settings of the live providers the command calls that were not given.
+    pub fn missing(&self, elevenlabs: &ElevenLabsEnv, llm: &AiStudioEnv) -> Vec<&'static str> {
+        match self {
+            Command::Replay { mock: true, .. } => Vec::new(),
+            // turns are only transcribed, nothing is replied
+            Command::Transcribe { .. } => elevenlabs.missing(),
+            _ => [elevenlabs.missing(), llm.missing()].concat(),
+        }
+    }
+}
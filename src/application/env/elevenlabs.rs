@@ -1,9 +1,65 @@
+use crate::domain::utils::{audio::resampler::SUPPORTED_RATES, units::SampleRate};
+
 #[derive(clap::Args, Debug, Clone)]
 pub struct ElevenLabsEnv {
     #[arg(
+        long,
         env = "ELEVENLABS_API_KEY",
         name = "ELEVENLABS_API_KEY",
-        help = "The ElevenLabs API key"
+        help = "The ElevenLabs API key, not needed to replay with --mock"
     )]
-    pub elevenlabs_api_key: String,
+    pub elevenlabs_api_key: Option<String>,
+
+    #[arg(
+        long,
+        env = "ELEVENLABS_VOICE_ID",
+        name = "ELEVENLABS_VOICE_ID",
+        help = "The ElevenLabs voice used to speak agent replies",
+        default_value = "21m00Tcm4TlvDq8ikWAM"
+    )]
+    pub elevenlabs_voice_id: String,
+
+    #[arg(
+        long,
+        env = "ELEVENLABS_TTS_MODEL",
+        name = "ELEVENLABS_TTS_MODEL",
+        help = "The ElevenLabs text-to-speech model",
+        default_value = "eleven_flash_v2_5"
+    )]
+    pub elevenlabs_tts_model: String,
+
+    #[arg(
+        long,
+        env = "ELEVENLABS_TTS_SAMPLE_RATE",
+        name = "ELEVENLABS_TTS_SAMPLE_RATE",
+        help = "The rate of the PCM ElevenLabs speaks at, one of 8000, 16000, 22050, 24000, 44100 and 48000",
+        value_parser = supported_rate,
+        default_value_t = 16000
+    )]
+    pub elevenlabs_tts_sample_rate: u32,
+}
+
+impl ElevenLabsEnv {
+    /// Settings the live providers need but were not given.
+    pub fn missing(&self) -> Vec<&'static str> {
+        match self.elevenlabs_api_key {
+            Some(_) => Vec::new(),
+            None => vec!["ELEVENLABS_API_KEY"],
+        }
+    }
+}
+
+/// Rejects at startup the rates the resampler could not convert, instead of
+/// failing every reply.
+fn supported_rate(value: &str) -> Result<u32, String> {
+    let rate: u32 = value.parse().map_err(|err| format!("{}", err))?;
+
+    match SUPPORTED_RATES.contains(&SampleRate(rate)) {
+        true => Ok(rate),
+        false => Err(format!(
+            "{} cannot be resampled, use one of {}",
+            SampleRate(rate),
+            SUPPORTED_RATES.map(|rate| rate.0.to_string()).join(", ")
+        )),
+    }
 }
@@ -0,0 +1,33 @@
+use anywho::Error;
+
+use crate::{domain::utils::units::Millis, infrastructure::vad::gmm_vad::GmmVadAdapter};
+
+#[derive(clap::Args, Debug, Clone)]
+pub struct GmmVadEnv {
+    #[arg(
+        long,
+        env = "GMM_VAD_MODE",
+        name = "GMM_VAD_MODE",
+        help = "Aggressiveness of the gmm VAD, from 0 (keeps the most audio as speech) to 3",
+        default_value_t = 2
+    )]
+    pub gmm_vad_mode: u8,
+
+    #[arg(
+        long,
+        env = "GMM_VAD_FRAME_MS",
+        name = "GMM_VAD_FRAME_MS",
+        help = "Frame length of the gmm VAD: 10, 20 or 30 ms",
+        default_value_t = 30
+    )]
+    pub gmm_vad_frame_ms: u64,
+}
+
+impl GmmVadEnv {
+    /// Every session gets a copy of the initial models, adapted to its own line.
+    pub fn vad(&self) -> Result<impl Fn() -> GmmVadAdapter + use<>, Error> {
+        let adapter = GmmVadAdapter::new(self.gmm_vad_mode, Millis(self.gmm_vad_frame_ms))?;
+
+        Ok(move || adapter.clone())
+    }
+}
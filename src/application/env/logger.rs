@@ -4,6 +4,7 @@ use std::fmt::Display;
 #[derive(clap::Args, Debug, Clone)]
 pub struct LoggerEnv {
     #[arg(
+        long,
         env = "LOG_LEVEL",
         name = "LOG_LEVEL",
         help = "The log level used in the application"
@@ -11,6 +12,7 @@ pub struct LoggerEnv {
     pub level: LogLevel,
 
     #[arg(
+        long,
         env = "LOG_PRETTIFY",
         name = "LOG_PRETTIFY",
         help = "Whether to prettify the log output",
@@ -0,0 +1,70 @@
+use std::path::PathBuf;
+
+use anywho::Error;
+
+use crate::infrastructure::vad::silero_vad::{SileroModel, SileroVadAdapter};
+
+#[derive(clap::Args, Debug, Clone)]
+pub struct SileroEnv {
+    #[arg(
+        long,
+        env = "SILERO_MODEL",
+        name = "SILERO_MODEL",
+        help = "Silero VAD ONNX model, registers the silero VAD when set (onnxruntime is loaded from ORT_DYLIB_PATH)"
+    )]
+    pub silero_model: Option<PathBuf>,
+
+    #[arg(
+        long,
+        env = "SILERO_THRESHOLD",
+        name = "SILERO_THRESHOLD",
+        help = "Speech probability a frame must reach to start speech",
+        default_value_t = 0.5
+    )]
+    pub silero_threshold: f32,
+
+    #[arg(
+        long,
+        env = "SILERO_HYSTERESIS",
+        name = "SILERO_HYSTERESIS",
+        help = "How far below the threshold the probability must drop to end speech",
+        default_value_t = 0.15
+    )]
+    pub silero_hysteresis: f32,
+
+    #[arg(
+        long,
+        env = "SILERO_SESSIONS",
+        name = "SILERO_SESSIONS",
+        help = "How many copies of the model run inferences side by side",
+        default_value_t = 4
+    )]
+    pub silero_sessions: usize,
+}
+
+impl SileroEnv {
+    /// Loads the model once, every session built by the factory shares it.
+    pub fn vad(&self) -> Result<Option<impl Fn() -> SileroVadAdapter + use<>>, Error> {
+        let Some(path) = &self.silero_model else {
+            return Ok(None);
+        };
+
+        let (threshold, hysteresis) = (self.silero_threshold, self.silero_hysteresis);
+        if !(0.0..=1.0).contains(&threshold) || !(0.0..=threshold).contains(&hysteresis) {
+            return Err(Error::msg(format!(
+                "SILERO_THRESHOLD must be within [0, 1] and SILERO_HYSTERESIS within [0, {}]",
+                threshold
+            )));
+        }
+
+        if self.silero_sessions == 0 {
+            return Err(Error::msg("SILERO_SESSIONS must be at least 1"));
+        }
+
+        let model = SileroModel::load(path, self.silero_sessions)?;
+
+        Ok(Some(move || {
+            SileroVadAdapter::new(model.clone(), threshold, hysteresis)
+        }))
+    }
+}
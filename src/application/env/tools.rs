@@ -0,0 +1,44 @@
+use std::{path::PathBuf, sync::Arc, time::Duration};
+
+use anywho::Error;
+
+use crate::{
+    domain::entities::tool_registry::ToolRegistry, infrastructure::tool::webhook_tool::WebhookTool,
+};
+
+#[derive(clap::Args, Debug, Clone)]
+pub struct ToolsEnv {
+    #[arg(
+        long,
+        env = "TOOLS_FILE",
+        name = "TOOLS_FILE",
+        help = "JSON file declaring the webhook tools the agent may call"
+    )]
+    pub tools_file: Option<PathBuf>,
+
+    #[arg(
+        long,
+        env = "TOOLS_TIMEOUT_MS",
+        name = "TOOLS_TIMEOUT_MS",
+        help = "Maximum duration of a single tool call",
+        default_value_t = 10000
+    )]
+    pub tools_timeout_ms: u64,
+}
+
+impl ToolsEnv {
+    pub fn timeout(&self) -> Duration {
+        Duration::from_millis(self.tools_timeout_ms)
+    }
+
+    pub fn tools(&self) -> Result<ToolRegistry, Error> {
+        let mut tools = ToolRegistry::new(self.timeout());
+        if let Some(path) = &self.tools_file {
+            for tool in WebhookTool::load_file(path)? {
+                tools.register(Arc::new(tool));
+            }
+        }
+
+        Ok(tools)
+    }
+}
@@ -0,0 +1,69 @@
+use std::{path::PathBuf, sync::Arc};
+
+use anywho::Error;
+
+use crate::{
+    domain::{entities::agent_profile::VadSettings, utils::units::Millis},
+    infrastructure::turn_detector::{
+        semantic_turn_detector::SemanticTurnDetector, turn_classifier::TurnClassifier,
+    },
+};
+
+#[derive(clap::Args, Debug, Clone)]
+pub struct TurnDetectorEnv {
+    #[arg(
+        long,
+        env = "TURN_COMPLETE_MS",
+        name = "TURN_COMPLETE_MS",
+        help = "Pause ending the turn of a caller who clearly finished their sentence",
+        default_value_t = 500
+    )]
+    pub turn_complete_ms: u64,
+
+    #[arg(
+        long,
+        env = "TURN_INCOMPLETE_MS",
+        name = "TURN_INCOMPLETE_MS",
+        help = "Pause waited for a caller who left their sentence hanging, at most 10000",
+        default_value_t = 4000
+    )]
+    pub turn_incomplete_ms: u64,
+
+    #[arg(
+        long,
+        env = "TURN_CLASSIFIER_FILE",
+        name = "TURN_CLASSIFIER_FILE",
+        help = "JSON weights of the end of turn classifier, punctuation and trailing words decide without it"
+    )]
+    pub turn_classifier_file: Option<PathBuf>,
+}
+
+impl TurnDetectorEnv {
+    /// Loads the classifier once, every session built by the factory shares it.
+    pub fn detector(&self) -> Result<impl Fn() -> SemanticTurnDetector + use<>, Error> {
+        if self.turn_complete_ms >= self.turn_incomplete_ms {
+            return Err(Error::msg(format!(
+                "TURN_COMPLETE_MS ({}) must be shorter than TURN_INCOMPLETE_MS ({})",
+                self.turn_complete_ms, self.turn_incomplete_ms
+            )));
+        }
+        // the VAD full stop bounds the wait, and the answer is held until then
+        let longest = *VadSettings::FULL_STOP_RANGE.end();
+        if Millis(self.turn_incomplete_ms) > longest {
+            return Err(Error::msg(format!(
+                "TURN_INCOMPLETE_MS ({}) cannot exceed the longest full stop ({})",
+                self.turn_incomplete_ms, longest
+            )));
+        }
+
+        let mut detector = SemanticTurnDetector::new(
+            Millis(self.turn_complete_ms),
+            Millis(self.turn_incomplete_ms),
+        );
+        if let Some(path) = &self.turn_classifier_file {
+            detector = detector.with_classifier(Arc::new(TurnClassifier::load(path)?));
+        }
+
+        Ok(move || detector.clone())
+    }
+}
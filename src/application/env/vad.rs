@@ -0,0 +1,132 @@
+use anywho::Error;
+
+use crate::domain::{
+    entities::agent_profile::{AdaptiveThreshold, VadSettings},
+    utils::units::Millis,
+};
+
+/// Endpointing of the deployment, agent profiles and sessions may override it.
+#[derive(clap::Args, Debug, Clone)]
+pub struct VadEnv {
+    #[arg(
+        long,
+        env = "VAD_THRESHOLD",
+        name = "VAD_THRESHOLD",
+        help = "RMS energy a frame must reach to be speech, for the energy VAD",
+        default_value_t = 800.0
+    )]
+    pub vad_threshold: f32,
+
+    #[arg(
+        long,
+        env = "VAD_FRAME_MS",
+        name = "VAD_FRAME_MS",
+        help = "Frame length of the energy VAD, from 10 to 100 ms (the gmm VAD reads GMM_VAD_FRAME_MS, silero keeps its own)",
+        default_value_t = 32
+    )]
+    pub vad_frame_ms: u64,
+
+    #[arg(
+        long,
+        env = "VAD_FULL_STOP_MS",
+        name = "VAD_FULL_STOP_MS",
+        help = "Silence after which the caller's turn is over",
+        default_value_t = 2000
+    )]
+    pub vad_full_stop_ms: u64,
+
+    #[arg(
+        long,
+        env = "VAD_MIN_SPEECH_MS",
+        name = "VAD_MIN_SPEECH_MS",
+        help = "Speech shorter than this does not start a pipeline",
+        default_value_t = 200
+    )]
+    pub vad_min_speech_ms: u64,
+
+    #[arg(
+        long,
+        env = "VAD_PRE_ROLL_FRAMES",
+        name = "VAD_PRE_ROLL_FRAMES",
+        help = "Frames kept up to the first speech frame, so the onset is not clipped",
+        default_value_t = 3
+    )]
+    pub vad_pre_roll_frames: u64,
+
+    #[arg(
+        long,
+        env = "VAD_PAUSE_TRAILER_FRAMES",
+        name = "VAD_PAUSE_TRAILER_FRAMES",
+        help = "Silent frames waited, and kept, before a pause starts a pipeline",
+        default_value_t = 2
+    )]
+    pub vad_pause_trailer_frames: u64,
+
+    #[arg(
+        long,
+        env = "VAD_ADAPTIVE",
+        name = "VAD_ADAPTIVE",
+        help = "Derive the energy VAD threshold from the line noise instead of VAD_THRESHOLD",
+        action = clap::ArgAction::Set,
+        default_value_t = false
+    )]
+    pub vad_adaptive: bool,
+
+    #[arg(
+        long,
+        env = "VAD_ADAPTIVE_WINDOW_MS",
+        name = "VAD_ADAPTIVE_WINDOW_MS",
+        help = "Span of non-speech audio the noise floor is estimated over",
+        default_value_t = AdaptiveThreshold::default().window_ms.0
+    )]
+    pub vad_adaptive_window_ms: u64,
+
+    #[arg(
+        long,
+        env = "VAD_ADAPTIVE_ONSET_DB",
+        name = "VAD_ADAPTIVE_ONSET_DB",
+        help = "Margin above the noise floor a frame must reach to start speech",
+        default_value_t = AdaptiveThreshold::default().onset_db
+    )]
+    pub vad_adaptive_onset_db: f32,
+
+    #[arg(
+        long,
+        env = "VAD_ADAPTIVE_OFFSET_DB",
+        name = "VAD_ADAPTIVE_OFFSET_DB",
+        help = "Margin above the noise floor a frame must keep for speech to go on",
+        default_value_t = AdaptiveThreshold::default().offset_db
+    )]
+    pub vad_adaptive_offset_db: f32,
+
+    #[arg(
+        long,
+        env = "VAD_ADAPTIVE_MIN_FLOOR",
+        name = "VAD_ADAPTIVE_MIN_FLOOR",
+        help = "Lowest noise floor assumed, in RMS energy",
+        default_value_t = AdaptiveThreshold::default().min_floor
+    )]
+    pub vad_adaptive_min_floor: f32,
+}
+
+impl VadEnv {
+    pub fn settings(&self) -> Result<VadSettings, Error> {
+        let settings = VadSettings {
+            threshold: self.vad_threshold,
+            frame_ms: Millis(self.vad_frame_ms),
+            full_stop_ms: Millis(self.vad_full_stop_ms),
+            min_speech_ms: Millis(self.vad_min_speech_ms),
+            pre_roll_frames: self.vad_pre_roll_frames,
+            pause_trailer_frames: self.vad_pause_trailer_frames,
+            adaptive: self.vad_adaptive.then_some(AdaptiveThreshold {
+                window_ms: Millis(self.vad_adaptive_window_ms),
+                onset_db: self.vad_adaptive_onset_db,
+                offset_db: self.vad_adaptive_offset_db,
+                min_floor: self.vad_adaptive_min_floor,
+            }),
+        };
+        settings.validate()?;
+
+        Ok(settings)
+    }
+}
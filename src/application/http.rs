@@ -1,2 +1,3 @@
 pub mod app_state;
 pub mod handlers;
+pub mod outbound;
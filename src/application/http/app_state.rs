@@ -1,29 +1,101 @@
-use tokio::sync::Mutex;
+use std::path::PathBuf;
+
+use tracing::{info, warn};
+use uuid::Uuid;
 
 use crate::{
-    application::{audio_source::AudioSourceList, llm::LlmList, stt::SttList},
-    domain::entities::pipeline::pool_manager::PoolManager,
+    application::{env::capture::CaptureEnv, registry::ProviderRegistry},
+    domain::{
+        entities::{
+            agent_profile::VadOverrides, agent_registry::AgentRegistry, audio_buffer::AudioBuffer,
+            pipeline::pool_manager::PoolManager, tool_registry::ToolRegistry,
+        },
+        utils::units::Millis,
+    },
+    infrastructure::capture::{session_recorder::SessionRecorder, wav_sink::WavSink},
 };
 
+/// Shared by every session and never mutated once built, so handlers clone the
+/// adapters they need without taking any lock.
 pub struct AppState {
     pub pool_manager: PoolManager,
-    pub stt: Mutex<SttList>,
-    pub audio_sources: Mutex<Vec<AudioSourceList>>,
-    pub llms: Mutex<Vec<LlmList>>,
+    pub providers: ProviderRegistry,
+    pub tools: ToolRegistry,
+    pub agents: AgentRegistry,
+    /// Sessions are recorded there when set.
+    pub capture_dir: Option<PathBuf>,
+    /// Writes the caller audio of the sessions next to their capture.
+    pub capture_audio: bool,
+    pub audio_retention: Millis,
 }
 
 impl AppState {
     pub fn new(
         pool_manager: PoolManager,
-        stt: SttList,
-        audio_sources: Vec<AudioSourceList>,
-        llms: Vec<LlmList>,
+        providers: ProviderRegistry,
+        tools: ToolRegistry,
+        agents: AgentRegistry,
+        capture: CaptureEnv,
+        audio_retention: Millis,
     ) -> Self {
         Self {
             pool_manager,
-            stt: Mutex::new(stt),
-            audio_sources: Mutex::new(audio_sources),
-            llms: Mutex::new(llms),
+            providers,
+            tools,
+            agents,
+            capture_dir: capture.capture_dir,
+            capture_audio: capture.capture_audio,
+            audio_retention,
+        }
+    }
+
+    /// Starts recording the session when captures are enabled. A capture that
+    /// cannot be written is logged, the call goes on without it.
+    pub async fn recorder(
+        &self,
+        protocol: &str,
+        session: Uuid,
+        agent: Option<String>,
+        vad: VadOverrides,
+    ) -> Option<SessionRecorder> {
+        let dir = self.capture_dir.as_ref()?;
+
+        match SessionRecorder::create(dir, protocol, session, agent, vad).await {
+            Ok((recorder, path)) => {
+                info!("Session {} recorded in {}", session, path.display());
+                Some(recorder)
+            }
+            Err(err) => {
+                warn!("Session {} will not be recorded: {:?}", session, err);
+                None
+            }
+        }
+    }
+
+    /// Buffer of the caller audio of the session, also written to a WAV file
+    /// when enabled. A file that cannot be written is logged, the call goes on.
+    pub async fn audio_buffer(&self, protocol: &str, session: Uuid) -> AudioBuffer {
+        let audio_buffer = AudioBuffer::new().with_retention(self.audio_retention);
+        let Some(dir) = self.capture_dir.as_ref().filter(|_| self.capture_audio) else {
+            return audio_buffer;
+        };
+
+        match WavSink::create(dir, protocol, session).await {
+            Ok((sink, path)) => {
+                info!(
+                    "Session {} caller audio written to {}",
+                    session,
+                    path.display()
+                );
+                audio_buffer.with_sink(sink)
+            }
+            Err(err) => {
+                warn!(
+                    "Session {} caller audio will not be written: {:?}",
+                    session, err
+                );
+                audio_buffer
+            }
         }
     }
 }
@@ -1,2 +1,3 @@
+pub mod client_handler;
 pub mod incoming_local_handler;
 pub mod incoming_twilio_handler;
@@ -0,0 +1,8 @@
+use axum::response::{Html, IntoResponse};
+
+const CLIENT_PAGE: &str = include_str!("../static/client.html");
+
+/// Browser client talking to `/local`, to try an agent without writing any code.
+pub async fn client_handler() -> impl IntoResponse {
+    Html(CLIENT_PAGE)
+}
@@ -1,72 +1,100 @@
-use std::sync::Arc;
+use std::{collections::HashMap, sync::Arc};
 
-use axum::extract::{
-    State, WebSocketUpgrade,
-    ws::{Message, WebSocket},
+use axum::{
+    extract::{
+        Query, State, WebSocketUpgrade,
+        ws::{Message, WebSocket},
+    },
+    http::StatusCode,
+    response::{IntoResponse, Response},
 };
 use futures::StreamExt;
+use serde::Deserialize;
+use tokio::sync::mpsc::channel;
 use tracing::info;
 
 use crate::{
-    application::{
-        audio_source::AudioSourceList, http::app_state::AppState, llm::LlmList, vad::VadList,
-    },
+    application::http::{app_state::AppState, outbound::spawn_outbound_writer},
     domain::{
         entities::{
-            audio_buffer::AudioBuffer,
-            audio_source_layer::{AudioSourceLayer, SendAudioCallback},
+            agent_profile::VadOverrides,
+            audio_source_layer::{AudioSourceLayer, SessionCallbacks},
             history::history::History,
         },
-        ports::audio_source::AudioSource,
+        ports::audio_source::{AudioSource, InboundFrame},
         utils::Utils,
     },
-    infrastructure::vad::local_vad::LocalVadAdapter,
 };
 
+#[derive(Debug, Deserialize)]
+pub struct LocalQuery {
+    pub agent: Option<String>,
+    /// `vad_*` parameters tuning the endpointing of this session.
+    #[serde(flatten)]
+    pub params: HashMap<String, String>,
+}
+
 pub async fn ws_local_handler(
     ws: WebSocketUpgrade,
+    Query(query): Query<LocalQuery>,
     State(state): State<Arc<AppState>>,
-) -> impl axum::response::IntoResponse {
-    ws.on_upgrade(move |socket| handle_twilio_socket(socket, state))
+) -> Response {
+    let vad_overrides = match VadOverrides::from_params(&query.params) {
+        Ok(overrides) => overrides,
+        Err(err) => return (StatusCode::BAD_REQUEST, err.to_string()).into_response(),
+    };
+
+    let agent = state.agents.get(query.agent.as_deref());
+    if let Err(err) = state
+        .agents
+        .vad_defaults()
+        .merged(&agent.vad)
+        .merged(&vad_overrides)
+        .validate()
+    {
+        return (StatusCode::BAD_REQUEST, err.to_string()).into_response();
+    }
+
+    ws.on_upgrade(move |socket| handle_local_socket(socket, state, query, vad_overrides))
 }
 
-async fn handle_twilio_socket(mut socket: WebSocket, state: Arc<AppState>) {
-    let audio_source = {
-        let audio_sources = state.audio_sources.lock().await;
-        audio_sources
-            .iter()
-            .find(|s| matches!(s, AudioSourceList::Local(_)))
-            .cloned()
-            .expect("No local audio source found")
-    };
+async fn handle_local_socket(
+    socket: WebSocket,
+    state: Arc<AppState>,
+    query: LocalQuery,
+    vad_overrides: VadOverrides,
+) {
+    let id = Utils::generate_uuid();
+    let recorder = state
+        .recorder("local", id, query.agent.clone(), vad_overrides)
+        .await;
 
-    let stt = {
-        let audio_sources = state.stt.lock().await;
-        audio_sources.clone()
-    };
+    let (sink, mut stream) = socket.split();
+    let (outbound_tx, outbound_rx) = channel(256);
+    let writer = spawn_outbound_writer(sink, outbound_rx, recorder.clone());
 
-    let llm = {
-        let llms = state.llms.lock().await;
-        llms.iter()
-            .find(|s| matches!(s, LlmList::Gemini(_)))
-            .cloned()
-            .expect("No local audio source found")
-    };
+    let audio_source = state
+        .providers
+        .audio_source("local")
+        .map(|source| source.connect(outbound_tx))
+        .expect("No local audio source registered");
 
-    let _history = History::new();
-    let mut audio_source_layer = AudioSourceLayer {
-        id: Utils::generate_uuid(),
-        vad: &mut VadList::Local(LocalVadAdapter::new()),
-        stt: stt.clone(),
-        llm: llm.clone(),
-        pool_manager: state.pool_manager.clone(),
-        history: &mut History::new(),
-        audio_buffer: &mut AudioBuffer::new(),
-        send_audio: SendAudioCallback::new({
-            let audio_source = audio_source.clone();
-            move |bytes| audio_source.send_audio(bytes)
-        }),
-    };
+    let mut vad = state.providers.vad(None).expect("No VAD registered");
+    let mut history = History::new();
+    let mut audio_buffer = state.audio_buffer("local", id).await;
+    let mut audio_source_layer = AudioSourceLayer::new(
+        id,
+        &state,
+        &state.providers,
+        SessionCallbacks::from_source(audio_source.clone()),
+        &mut vad,
+        &mut history,
+        &mut audio_buffer,
+    )
+    .expect("No provider registered");
+    audio_source_layer.vad_overrides = vad_overrides;
+
+    audio_source_layer.apply_agent(state.agents.get(query.agent.as_deref()));
 
     // Make HTTP calls to initialize conversation
     // - History : compute prompt-system + prompt user
@@ -74,13 +102,26 @@ async fn handle_twilio_socket(mut socket: WebSocket, state: Arc<AppState>) {
 
     info!("Nouvelle connexion locale id={}", audio_source_layer.id);
 
-    while let Some(Ok(msg)) = socket.next().await {
-        if let Message::Text(message) = msg {
-            audio_source_layer
-                .audio_buffer
-                .override_streamed_buffer(message);
+    while let Some(Ok(msg)) = stream.next().await {
+        let frame = match msg {
+            Message::Text(message) => InboundFrame::Text(message),
+            Message::Binary(bytes) => InboundFrame::Binary(bytes),
+            Message::Close(_) => break,
+            _ => continue,
+        };
 
-            let _ = audio_source.handle(&mut audio_source_layer).await;
+        if let Some(recorder) = &recorder {
+            recorder.inbound(&frame);
+        }
+
+        audio_source_layer
+            .audio_buffer
+            .override_streamed_buffer(frame);
+
+        let _ = audio_source.handle(&mut audio_source_layer).await;
+
+        if audio_source_layer.ended {
+            break;
         }
     }
 
@@ -89,9 +130,17 @@ async fn handle_twilio_socket(mut socket: WebSocket, state: Arc<AppState>) {
         audio_source_layer.id
     );
 
+    audio_source_layer.history.sync();
+
     println!("History events {}", audio_source_layer.history.events.len());
 
     for entry in audio_source_layer.history.events.iter() {
         info!("-> {}: {:?}", entry.member, entry.content);
     }
+
+    state
+        .pool_manager
+        .stop_pipeline(&audio_source_layer.id)
+        .await;
+    writer.abort();
 }
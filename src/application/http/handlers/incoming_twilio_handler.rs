@@ -5,22 +5,20 @@ use axum::extract::{
     ws::{Message, WebSocket},
 };
 use futures::StreamExt;
+use tokio::sync::mpsc::channel;
 use tracing::info;
 
 use crate::{
-    application::{
-        audio_source::AudioSourceList, http::app_state::AppState, llm::LlmList, vad::VadList,
-    },
+    application::http::{app_state::AppState, outbound::spawn_outbound_writer},
     domain::{
         entities::{
-            audio_buffer::AudioBuffer,
-            audio_source_layer::{AudioSourceLayer, SendAudioCallback},
+            agent_profile::VadOverrides,
+            audio_source_layer::{AudioSourceLayer, SessionCallbacks},
             history::history::History,
         },
-        ports::audio_source::AudioSource,
+        ports::audio_source::{AudioSource, InboundFrame},
         utils::Utils,
     },
-    infrastructure::vad::local_vad::LocalVadAdapter,
 };
 
 pub async fn ws_twilio_handler(
@@ -30,56 +28,69 @@ pub async fn ws_twilio_handler(
     ws.on_upgrade(move |socket| handle_twilio_socket(socket, state))
 }
 
-async fn handle_twilio_socket(mut socket: WebSocket, state: Arc<AppState>) {
-    let audio_source = {
-        let audio_sources = state.audio_sources.lock().await;
-        audio_sources
-            .iter()
-            .find(|s| matches!(s, AudioSourceList::Twilio(_)))
-            .cloned()
-            .expect("No Twilio audio source found")
-    };
+async fn handle_twilio_socket(socket: WebSocket, state: Arc<AppState>) {
+    let id = Utils::generate_uuid();
+    let recorder = state
+        .recorder("twilio", id, None, VadOverrides::default())
+        .await;
+
+    let (sink, mut stream) = socket.split();
+    let (outbound_tx, outbound_rx) = channel(256);
+    let writer = spawn_outbound_writer(sink, outbound_rx, recorder.clone());
 
-    let llm = {
-        let llms = state.llms.lock().await;
-        llms.iter()
-            .find(|s| matches!(s, LlmList::Gemini(_)))
-            .cloned()
-            .expect("No local audio source found")
-    };
+    let audio_source = state
+        .providers
+        .audio_source("twilio")
+        .map(|source| source.connect(outbound_tx))
+        .expect("No Twilio audio source registered");
 
-    let stt = {
-        let stt = state.stt.lock().await;
-        stt.clone()
-    };
+    let mut vad = state.providers.vad(None).expect("No VAD registered");
+    let mut history = History::new();
+    let mut audio_buffer = state.audio_buffer("twilio", id).await;
+    let mut audio_source_layer = AudioSourceLayer::new(
+        id,
+        &state,
+        &state.providers,
+        SessionCallbacks::from_source(audio_source.clone()),
+        &mut vad,
+        &mut history,
+        &mut audio_buffer,
+    )
+    .expect("No provider registered");
 
-    let mut audio_source_layer = AudioSourceLayer {
-        id: Utils::generate_uuid(),
-        vad: &mut VadList::Local(LocalVadAdapter::new()),
-        stt: stt.clone(),
-        llm: llm.clone(),
-        pool_manager: state.pool_manager.clone(),
-        history: &mut History::new(),
-        audio_buffer: &mut AudioBuffer::new(),
-        send_audio: SendAudioCallback::new({
-            let audio_source = audio_source.clone();
-            move |bytes| audio_source.send_audio(bytes)
-        }),
-    };
+    // Twilio streams cannot carry a query string, the agent comes with the start event
+    audio_source_layer.apply_agent(state.agents.default_profile());
 
     info!("Nouvelle connexion Twilio id={}", audio_source_layer.id);
-    while let Some(msg) = socket.next().await {
+    while let Some(msg) = stream.next().await {
         if let Ok(Message::Text(message)) = msg {
+            let frame = InboundFrame::Text(message);
+            if let Some(recorder) = &recorder {
+                recorder.inbound(&frame);
+            }
+
             audio_source_layer
                 .audio_buffer
-                .override_streamed_buffer(message);
+                .override_streamed_buffer(frame);
 
             let _ = audio_source.handle(&mut audio_source_layer).await;
         }
+
+        if audio_source_layer.ended {
+            break;
+        }
     }
 
     info!(
         "Connexion id={} fermée. Nettoyage des ressources.",
         audio_source_layer.id
     );
+
+    audio_source_layer.history.sync();
+
+    state
+        .pool_manager
+        .stop_pipeline(&audio_source_layer.id)
+        .await;
+    writer.abort();
 }
@@ -0,0 +1,35 @@
+use axum::extract::ws::{Message, WebSocket};
+use futures::{SinkExt, stream::SplitSink};
+use tokio::{spawn, sync::mpsc::Receiver, task::JoinHandle};
+use tracing::debug;
+
+use crate::{
+    domain::ports::audio_source::OutboundFrame,
+    infrastructure::capture::session_recorder::SessionRecorder,
+};
+
+pub fn spawn_outbound_writer(
+    mut sink: SplitSink<WebSocket, Message>,
+    mut outbound: Receiver<OutboundFrame>,
+    recorder: Option<SessionRecorder>,
+) -> JoinHandle<()> {
+    spawn(async move {
+        while let Some(frame) = outbound.recv().await {
+            if let Some(recorder) = &recorder {
+                recorder.outbound(&frame);
+            }
+
+            let message = match frame {
+                OutboundFrame::Text(text) => Message::Text(text),
+                OutboundFrame::Binary(bytes) => Message::Binary(bytes),
+            };
+
+            if let Err(err) = sink.send(message).await {
+                debug!("Outbound writer stopped: {}", err);
+                break;
+            }
+        }
+
+        let _ = sink.close().await;
+    })
+}
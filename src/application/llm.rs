@@ -1,26 +1,54 @@
+use std::sync::Arc;
+
 use anywho::Error;
+use futures::future::BoxFuture;
 
 use crate::{
-    domain::{
-        entities::history::history_event::HistoryEvent,
-        ports::llm::{Llm, LlmProcessResponse},
-    },
-    infrastructure::llm::gemini_adapter::GeminiAdapter,
+    domain::ports::llm::{Llm, LlmProcessResponse, LlmRequest, LlmStream},
+    infrastructure::llm::{gemini_adapter::GeminiAdapter, mock_llm::MockLlm},
 };
 
 #[derive(Clone)]
 pub enum LlmList {
     Gemini(GeminiAdapter),
+    /// Scripted replies for tests and offline runs.
+    Mock(MockLlm),
+    /// An adapter registered by the embedding application.
+    Custom(Arc<dyn Llm>),
 }
 
 impl Llm for LlmList {
-    async fn process(
-        &mut self,
-        model: String,
-        history_events: Vec<HistoryEvent>,
-    ) -> Result<LlmProcessResponse, Error> {
+    fn process(&self, request: LlmRequest) -> BoxFuture<'_, Result<LlmProcessResponse, Error>> {
         match self {
-            LlmList::Gemini(adapter) => adapter.process(model, history_events).await,
+            LlmList::Gemini(adapter) => adapter.process(request),
+            LlmList::Mock(adapter) => adapter.process(request),
+            LlmList::Custom(adapter) => adapter.process(request),
         }
     }
+
+    fn process_stream(&self, request: LlmRequest) -> BoxFuture<'_, Result<LlmStream, Error>> {
+        match self {
+            LlmList::Gemini(adapter) => adapter.process_stream(request),
+            LlmList::Mock(adapter) => adapter.process_stream(request),
+            LlmList::Custom(adapter) => adapter.process_stream(request),
+        }
+    }
+}
+
+impl From<GeminiAdapter> for LlmList {
+    fn from(adapter: GeminiAdapter) -> Self {
+        LlmList::Gemini(adapter)
+    }
+}
+
+impl From<MockLlm> for LlmList {
+    fn from(adapter: MockLlm) -> Self {
+        LlmList::Mock(adapter)
+    }
+}
+
+impl From<Arc<dyn Llm>> for LlmList {
+    fn from(adapter: Arc<dyn Llm>) -> Self {
+        LlmList::Custom(adapter)
+    }
 }
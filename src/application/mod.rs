@@ -1,6 +1,11 @@
 pub mod audio_source;
+pub mod cli;
 pub mod env;
 pub mod http;
 pub mod llm;
+pub mod registry;
+pub mod simulator;
 pub mod stt;
+pub mod tts;
+pub mod turn_detector;
 pub mod vad;
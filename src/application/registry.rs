@@ -0,0 +1,199 @@
+use std::sync::Arc;
+
+use tracing::warn;
+
+use crate::{
+    application::{
+        audio_source::AudioSourceList,
+        env::{aistudio::AiStudioEnv, elevenlabs::ElevenLabsEnv},
+        llm::LlmList,
+        stt::SttList,
+        tts::TtsList,
+        turn_detector::TurnDetectorList,
+        vad::VadList,
+    },
+    domain::utils::units::SampleRate,
+    infrastructure::{
+        audio_source::{local_source_adapter::LocalAdapter, twilio_source_adapter::TwilioAdapter},
+        llm::gemini_adapter::GeminiAdapter,
+        stt::scribe_adapter::ScribeAdapter,
+        tts::elevenlabs_adapter::ElevenLabsTtsAdapter,
+        turn_detector::{
+            semantic_turn_detector::SemanticTurnDetector,
+            silence_turn_detector::SilenceTurnDetector,
+        },
+        vad::local_vad::LocalVadAdapter,
+    },
+};
+
+pub type VadFactory = Arc<dyn Fn() -> VadList + Send + Sync>;
+pub type TurnDetectorFactory = Arc<dyn Fn() -> TurnDetectorList + Send + Sync>;
+
+/// Adapters available to the sessions, registered by name at startup.
+///
+/// Built-in adapters and the ones of an embedding application (`Arc<dyn Stt>`,
+/// `Arc<dyn Llm>`, ...) are registered the same way. The first adapter of each
+/// kind is used when a session does not ask for a specific one.
+#[derive(Clone, Default)]
+pub struct ProviderRegistry {
+    stt: Providers<SttList>,
+    llm: Providers<LlmList>,
+    tts: Providers<TtsList>,
+    vad: Providers<VadFactory>,
+    turn_detectors: Providers<TurnDetectorFactory>,
+    audio_sources: Providers<AudioSourceList>,
+}
+
+impl ProviderRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The adapters shipped with the crate, configured from the environment.
+    pub fn builtin(elevenlabs: &ElevenLabsEnv, llm: &AiStudioEnv) -> Self {
+        let mut providers = Self::new();
+
+        // adapters without credentials are left out, for mocked runs
+        if let Some(api_key) = &elevenlabs.elevenlabs_api_key {
+            providers
+                .register_stt("scribe", ScribeAdapter::new(api_key.clone()))
+                .register_tts(
+                    "elevenlabs",
+                    ElevenLabsTtsAdapter::new(
+                        api_key.clone(),
+                        elevenlabs.elevenlabs_voice_id.clone(),
+                        elevenlabs.elevenlabs_tts_model.clone(),
+                    )
+                    .with_sample_rate(SampleRate(elevenlabs.elevenlabs_tts_sample_rate)),
+                );
+        }
+        if let (Some(api_key), Some(base_url)) = (&llm.aistudio_api_key, &llm.aistudio_base_url) {
+            providers.register_llm(
+                "gemini",
+                GeminiAdapter::new(api_key.clone(), base_url.clone()),
+            );
+        }
+
+        providers
+            .register_vad("local", LocalVadAdapter::new)
+            .register_turn_detector("silence", SilenceTurnDetector::new)
+            .register_turn_detector("semantic", SemanticTurnDetector::default)
+            .register_audio_source("twilio", TwilioAdapter::new())
+            .register_audio_source("local", LocalAdapter::new());
+
+        providers
+    }
+
+    pub fn register_stt(&mut self, name: &str, stt: impl Into<SttList>) -> &mut Self {
+        self.stt.insert(name, stt.into());
+        self
+    }
+
+    pub fn register_llm(&mut self, name: &str, llm: impl Into<LlmList>) -> &mut Self {
+        self.llm.insert(name, llm.into());
+        self
+    }
+
+    pub fn register_tts(&mut self, name: &str, tts: impl Into<TtsList>) -> &mut Self {
+        self.tts.insert(name, tts.into());
+        self
+    }
+
+    /// Sessions keep their own detector state, so VAD adapters are registered as factories.
+    pub fn register_vad<F, V>(&mut self, name: &str, factory: F) -> &mut Self
+    where
+        F: Fn() -> V + Send + Sync + 'static,
+        V: Into<VadList>,
+    {
+        self.vad.insert(name, Arc::new(move || factory().into()));
+        self
+    }
+
+    /// Turn detectors are configured with the endpointing of each session, so
+    /// they are registered as factories too.
+    pub fn register_turn_detector<F, T>(&mut self, name: &str, factory: F) -> &mut Self
+    where
+        F: Fn() -> T + Send + Sync + 'static,
+        T: Into<TurnDetectorList>,
+    {
+        self.turn_detectors
+            .insert(name, Arc::new(move || factory().into()));
+        self
+    }
+
+    pub fn register_audio_source(
+        &mut self,
+        name: &str,
+        audio_source: impl Into<AudioSourceList>,
+    ) -> &mut Self {
+        self.audio_sources.insert(name, audio_source.into());
+        self
+    }
+
+    pub fn stt(&self, name: Option<&str>) -> Option<SttList> {
+        self.stt.get("STT", name)
+    }
+
+    pub fn llm(&self, name: Option<&str>) -> Option<LlmList> {
+        self.llm.get("LLM", name)
+    }
+
+    pub fn tts(&self, name: Option<&str>) -> Option<TtsList> {
+        self.tts.get("TTS", name)
+    }
+
+    pub fn vad(&self, name: Option<&str>) -> Option<VadList> {
+        self.vad.get("VAD", name).map(|factory| factory())
+    }
+
+    pub fn turn_detector(&self, name: Option<&str>) -> Option<TurnDetectorList> {
+        self.turn_detectors
+            .get("turn detector", name)
+            .map(|factory| factory())
+    }
+
+    /// Audio sources speak a given wire protocol, there is no fallback.
+    pub fn audio_source(&self, name: &str) -> Option<AudioSourceList> {
+        self.audio_sources.find(name)
+    }
+}
+
+#[derive(Clone)]
+struct Providers<T> {
+    entries: Vec<(String, T)>,
+}
+
+impl<T: Clone> Providers<T> {
+    fn insert(&mut self, name: &str, provider: T) {
+        match self.entries.iter_mut().find(|(entry, _)| entry == name) {
+            Some(entry) => entry.1 = provider,
+            None => self.entries.push((name.to_string(), provider)),
+        }
+    }
+
+    fn find(&self, name: &str) -> Option<T> {
+        self.entries
+            .iter()
+            .find(|(entry, _)| entry == name)
+            .map(|(_, provider)| provider.clone())
+    }
+
+    fn get(&self, kind: &str, name: Option<&str>) -> Option<T> {
+        if let Some(name) = name {
+            match self.find(name) {
+                Some(provider) => return Some(provider),
+                None => warn!("Unknown {} provider {}, using the default one", kind, name),
+            }
+        }
+
+        self.entries.first().map(|(_, provider)| provider.clone())
+    }
+}
+
+impl<T> Default for Providers<T> {
+    fn default() -> Self {
+        Self {
+            entries: Vec::new(),
+        }
+    }
+}
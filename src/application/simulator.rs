@@ -0,0 +1,247 @@
+use std::{sync::Arc, time::Duration};
+
+use anywho::Error;
+use tokio::time::{Instant, sleep};
+use tracing::{info, warn};
+
+use crate::{
+    application::{
+        http::app_state::AppState,
+        llm::LlmList,
+        registry::ProviderRegistry,
+        simulator::{
+            probe::{Probe, ProbeKind, TimedLlm, TimedStt},
+            report::SimulationReport,
+            scenario::{CallerTurn, MockSetup, Scenario},
+        },
+        stt::SttList,
+    },
+    domain::{
+        entities::{
+            audio_buffer::AudioBuffer,
+            audio_source_layer::{
+                AudioSourceLayer, ClearAudioCallback, FRAME, SendAudioCallback, SessionCallbacks,
+                SessionEventCallback,
+            },
+            history::{history::History, history_member::HistoryMember},
+        },
+        ports::{audio_source::SessionEvent, llm::Llm, stt::Stt, tts::Tts},
+        utils::{
+            Utils,
+            units::{Millis, SampleRate},
+        },
+    },
+    infrastructure::{
+        audio_source::file_source_adapter::FileAudioSource,
+        llm::mock_llm::MockLlm,
+        stt::mock_stt::MockStt,
+        tts::mock_tts::MockTts,
+        turn_detector::{
+            semantic_turn_detector::SemanticTurnDetector,
+            silence_turn_detector::SilenceTurnDetector,
+        },
+        vad::local_vad::LocalVadAdapter,
+    },
+};
+
+pub mod probe;
+pub mod report;
+pub mod scenario;
+
+/// How long an interrupting turn waits for the answer it is meant to cut.
+const REPLY_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Plays the scenario as a caller, in real time, and reports the latencies of each turn.
+pub async fn run(state: &AppState, scenario: &Scenario) -> Result<SimulationReport, Error> {
+    let providers = match &scenario.mock {
+        Some(mock) => mock_providers(mock, &scenario.turns),
+        None => state.providers.clone(),
+    };
+
+    let mut caller_audio = Vec::with_capacity(scenario.turns.len());
+    for turn in scenario.turns.iter() {
+        caller_audio.push(render_turn(&providers, scenario, turn).await?);
+    }
+
+    let mut probe = Probe::new();
+    let recorder = probe.recorder();
+    let agent = state.agents.get(scenario.agent.as_deref());
+
+    let callbacks = SessionCallbacks {
+        send_audio: SendAudioCallback::new({
+            let recorder = recorder.clone();
+            move |_| {
+                recorder.record(ProbeKind::AudioSent);
+                async { Ok(()) }
+            }
+        }),
+        clear_audio: ClearAudioCallback::new({
+            let recorder = recorder.clone();
+            move || {
+                recorder.record(ProbeKind::AudioCleared);
+                async { Ok(()) }
+            }
+        }),
+        send_event: SessionEventCallback::new({
+            let recorder = recorder.clone();
+            move |event| {
+                match event {
+                    SessionEvent::Vad {
+                        kind: "speech_paused",
+                        ..
+                    } => recorder.record(ProbeKind::VadPaused),
+                    SessionEvent::Transcript {
+                        member: HistoryMember::User,
+                        text,
+                    } => recorder.record(ProbeKind::UserTranscript(text)),
+                    SessionEvent::Transcript {
+                        member: HistoryMember::Agent,
+                        text,
+                    } => recorder.record(ProbeKind::AgentTranscript(text)),
+                    _ => {}
+                }
+                async { Ok(()) }
+            }
+        }),
+    };
+    let mut vad = providers
+        .vad(None)
+        .ok_or_else(|| Error::msg("No VAD registered"))?;
+    let mut history = History::new();
+    let mut audio_buffer = AudioBuffer::new();
+    let mut layer = AudioSourceLayer::new(
+        Utils::generate_uuid(),
+        state,
+        &providers,
+        callbacks,
+        &mut vad,
+        &mut history,
+        &mut audio_buffer,
+    )?;
+
+    layer.apply_agent(agent);
+    layer.stt =
+        SttList::from(Arc::new(TimedStt::new(layer.stt.clone(), probe.recorder())) as Arc<dyn Stt>);
+    layer.llm =
+        LlmList::from(Arc::new(TimedLlm::new(layer.llm.clone(), probe.recorder())) as Arc<dyn Llm>);
+
+    info!(
+        "Simulating {} turns as session {}",
+        scenario.turns.len(),
+        layer.id
+    );
+
+    for (index, (turn, audio)) in scenario.turns.iter().zip(caller_audio).enumerate() {
+        recorder.record(ProbeKind::TurnStarted(index));
+        feed(&mut layer, &audio, &mut probe).await;
+
+        let speech_end = Instant::now();
+        recorder.record(ProbeKind::SpeechEnded(index));
+
+        let silence = vec![0; SampleRate::PIPELINE.samples(FRAME).as_usize()];
+        match scenario
+            .turns
+            .get(index + 1)
+            .and_then(|next| next.interrupt_after_ms)
+        {
+            Some(delay) => {
+                // silence until the answer has been playing for `delay`
+                let delay = Duration::from_millis(delay);
+                let mut waited = Duration::ZERO;
+                loop {
+                    probe.sync();
+                    if let Some(first_audio) = probe.first_audio_after(speech_end)
+                        && first_audio.elapsed() >= delay
+                    {
+                        break;
+                    }
+                    if waited >= REPLY_TIMEOUT {
+                        warn!("Turn {} was never answered, not interrupting", index + 1);
+                        break;
+                    }
+
+                    feed(&mut layer, &silence, &mut probe).await;
+                    waited += FRAME.as_duration();
+                }
+            }
+            None => {
+                let pause = vec![
+                    0;
+                    SampleRate::PIPELINE
+                        .samples(Millis(turn.pause_ms))
+                        .as_usize()
+                ];
+                feed(&mut layer, &pause, &mut probe).await;
+            }
+        }
+    }
+
+    layer.drain_pipeline().await;
+    probe.sync();
+
+    let labels: Vec<String> = scenario.turns.iter().map(CallerTurn::label).collect();
+    Ok(SimulationReport::new(
+        scenario.name.clone(),
+        &labels,
+        &probe.events,
+    ))
+}
+
+/// Feeds `audio` frame by frame at real time pace.
+async fn feed(layer: &mut AudioSourceLayer<'_>, audio: &[i16], probe: &mut Probe) {
+    for frame in audio.chunks(SampleRate::PIPELINE.samples(FRAME).as_usize()) {
+        layer.process(frame).await;
+        probe.sync();
+        sleep(FRAME.as_duration()).await;
+    }
+}
+
+/// Scripted providers answering the scenario: one transcript per turn and the
+/// scripted replies in order.
+fn mock_providers(mock: &MockSetup, turns: &[CallerTurn]) -> ProviderRegistry {
+    let transcripts = turns.iter().map(|turn| Ok(turn.label())).collect();
+    let replies = mock
+        .replies
+        .iter()
+        .map(|reply| Ok(MockLlm::text(reply)))
+        .collect();
+
+    let mut providers = ProviderRegistry::new();
+    providers
+        .register_stt(
+            "mock",
+            MockStt::new(transcripts).with_latency(Duration::from_millis(mock.stt_latency_ms)),
+        )
+        .register_llm(
+            "mock",
+            MockLlm::new(replies).with_latency(Duration::from_millis(mock.llm_latency_ms)),
+        )
+        .register_tts(
+            "mock",
+            MockTts::new().with_latency(Duration::from_millis(mock.tts_latency_ms)),
+        )
+        .register_vad("local", LocalVadAdapter::new)
+        .register_turn_detector("silence", SilenceTurnDetector::new)
+        .register_turn_detector("semantic", SemanticTurnDetector::default);
+
+    providers
+}
+
+/// The caller audio of a turn, at 16 kHz.
+async fn render_turn(
+    providers: &ProviderRegistry,
+    scenario: &Scenario,
+    turn: &CallerTurn,
+) -> Result<Vec<i16>, Error> {
+    match (&turn.wav, &turn.text) {
+        (Some(wav), _) => FileAudioSource::read_pcm(wav),
+        (None, Some(text)) if scenario.mock.is_some() => Ok(MockTts::render(text)),
+        (None, Some(text)) => {
+            let tts = providers
+                .tts(None)
+                .ok_or_else(|| Error::msg("No TTS registered to render the caller"))?;
+            tts.synthesize(text, scenario.caller_voice.as_deref()).await
+        }
+        (None, None) => Err(Error::msg("A turn needs a wav or a text")),
+    }
+}
@@ -0,0 +1,187 @@
+use std::sync::{
+    Arc,
+    atomic::{AtomicU64, Ordering},
+};
+
+use anywho::Error;
+use futures::{StreamExt, future::BoxFuture};
+use tokio::{
+    sync::mpsc::{UnboundedReceiver, UnboundedSender, unbounded_channel},
+    time::Instant,
+};
+
+use crate::{
+    application::{llm::LlmList, stt::SttList},
+    domain::ports::{
+        llm::{Llm, LlmProcessResponse, LlmRequest, LlmStream},
+        stt::{Stt, SttPayload},
+    },
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ProbeKind {
+    TurnStarted(usize),
+    SpeechEnded(usize),
+    VadPaused,
+    SttStarted(u64),
+    SttFinished(u64),
+    LlmStarted(u64),
+    LlmFirstToken(u64),
+    UserTranscript(String),
+    AgentTranscript(String),
+    AudioSent,
+    AudioCleared,
+}
+
+#[derive(Debug, Clone)]
+pub struct ProbeEvent {
+    pub at: Instant,
+    pub kind: ProbeKind,
+}
+
+/// Timestamps what happens during a simulated call. Recorders are handed to
+/// the providers and callbacks, events are collected with [`Probe::sync`].
+pub struct Probe {
+    pub events: Vec<ProbeEvent>,
+    sender: UnboundedSender<ProbeEvent>,
+    receiver: UnboundedReceiver<ProbeEvent>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ProbeRecorder {
+    sender: UnboundedSender<ProbeEvent>,
+    calls: Arc<AtomicU64>,
+}
+
+impl Probe {
+    pub fn new() -> Self {
+        let (sender, receiver) = unbounded_channel();
+
+        Self {
+            events: Vec::new(),
+            sender,
+            receiver,
+        }
+    }
+
+    pub fn recorder(&self) -> ProbeRecorder {
+        ProbeRecorder {
+            sender: self.sender.clone(),
+            calls: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    pub fn sync(&mut self) {
+        while let Ok(event) = self.receiver.try_recv() {
+            self.events.push(event);
+        }
+    }
+
+    /// When the agent first sent audio after `since`.
+    pub fn first_audio_after(&self, since: Instant) -> Option<Instant> {
+        self.events
+            .iter()
+            .find(|event| event.kind == ProbeKind::AudioSent && event.at >= since)
+            .map(|event| event.at)
+    }
+}
+
+impl Default for Probe {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ProbeRecorder {
+    pub fn record(&self, kind: ProbeKind) {
+        let _ = self.sender.send(ProbeEvent {
+            at: Instant::now(),
+            kind,
+        });
+    }
+
+    fn next_call(&self) -> u64 {
+        self.calls.fetch_add(1, Ordering::SeqCst)
+    }
+}
+
+/// Times every transcription of the wrapped adapter.
+pub struct TimedStt {
+    inner: SttList,
+    recorder: ProbeRecorder,
+}
+
+impl TimedStt {
+    pub fn new(inner: SttList, recorder: ProbeRecorder) -> Self {
+        Self { inner, recorder }
+    }
+}
+
+impl Stt for TimedStt {
+    fn execute<'a>(
+        &'a self,
+        audio: &'a [i16],
+        language_code: &'a str,
+    ) -> BoxFuture<'a, Result<SttPayload, Error>> {
+        Box::pin(async move {
+            let call = self.recorder.next_call();
+            self.recorder.record(ProbeKind::SttStarted(call));
+            let result = self.inner.execute(audio, language_code).await;
+            self.recorder.record(ProbeKind::SttFinished(call));
+
+            result
+        })
+    }
+
+    fn write_audio_file<'a>(
+        &'a self,
+        filename: String,
+        bytes: &'a [i16],
+    ) -> BoxFuture<'a, Result<(), Error>> {
+        self.inner.write_audio_file(filename, bytes)
+    }
+}
+
+/// Times the first token of every request to the wrapped adapter.
+pub struct TimedLlm {
+    inner: LlmList,
+    recorder: ProbeRecorder,
+}
+
+impl TimedLlm {
+    pub fn new(inner: LlmList, recorder: ProbeRecorder) -> Self {
+        Self { inner, recorder }
+    }
+}
+
+impl Llm for TimedLlm {
+    fn process(&self, request: LlmRequest) -> BoxFuture<'_, Result<LlmProcessResponse, Error>> {
+        Box::pin(async move {
+            let call = self.recorder.next_call();
+            self.recorder.record(ProbeKind::LlmStarted(call));
+            let result = self.inner.process(request).await;
+            self.recorder.record(ProbeKind::LlmFirstToken(call));
+
+            result
+        })
+    }
+
+    fn process_stream(&self, request: LlmRequest) -> BoxFuture<'_, Result<LlmStream, Error>> {
+        Box::pin(async move {
+            let call = self.recorder.next_call();
+            self.recorder.record(ProbeKind::LlmStarted(call));
+            let stream = self.inner.process_stream(request).await?;
+
+            let recorder = self.recorder.clone();
+            let mut first = true;
+            let stream = stream.inspect(move |_| {
+                if first {
+                    first = false;
+                    recorder.record(ProbeKind::LlmFirstToken(call));
+                }
+            });
+
+            Ok(Box::pin(stream) as LlmStream)
+        })
+    }
+}
@@ -0,0 +1,183 @@
+use std::{collections::HashMap, fmt::Display};
+
+use serde::Serialize;
+use tokio::time::Instant;
+
+use crate::application::simulator::probe::{ProbeEvent, ProbeKind};
+
+/// Latencies of one caller turn, in ms from the end of the caller speech
+/// unless stated otherwise.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct TurnReport {
+    pub turn: usize,
+    pub caller: String,
+    /// Until the VAD reported the pause that started the pipeline.
+    pub vad_endpoint_ms: Option<u64>,
+    /// Duration of the transcription that was answered.
+    pub stt_ms: Option<u64>,
+    /// Time to first token of the last request of the turn.
+    pub llm_ms: Option<u64>,
+    pub first_audio_ms: Option<u64>,
+    /// Whether the caller cut the agent answer short.
+    pub interrupted: bool,
+    pub transcript: Option<String>,
+    pub answer: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SimulationReport {
+    pub scenario: Option<String>,
+    pub turns: Vec<TurnReport>,
+}
+
+impl SimulationReport {
+    pub fn new(scenario: Option<String>, labels: &[String], events: &[ProbeEvent]) -> Self {
+        let mut events = events.to_vec();
+        events.sort_by_key(|event| event.at);
+
+        let mut turns: Vec<TurnReport> = labels
+            .iter()
+            .enumerate()
+            .map(|(index, label)| TurnReport {
+                turn: index + 1,
+                caller: label.clone(),
+                ..Default::default()
+            })
+            .collect();
+
+        let mut current: Option<usize> = None;
+        let mut speech_ends = vec![None::<Instant>; turns.len()];
+        let mut stt_started = HashMap::<u64, Instant>::new();
+        let mut llm_started = HashMap::<u64, Instant>::new();
+        // answers may land after the next turn started, they belong to the
+        // turn whose request produced them
+        let mut answering: Option<usize> = None;
+
+        for event in events.iter() {
+            if let ProbeKind::TurnStarted(index) = event.kind {
+                current = Some(index);
+                continue;
+            }
+
+            let Some(index) = current else {
+                continue;
+            };
+
+            match &event.kind {
+                ProbeKind::SpeechEnded(_) => speech_ends[index] = Some(event.at),
+                ProbeKind::VadPaused => {
+                    if let Some(end) = speech_ends[index]
+                        && turns[index].vad_endpoint_ms.is_none()
+                    {
+                        turns[index].vad_endpoint_ms = Some(elapsed_ms(end, event.at));
+                    }
+                }
+                ProbeKind::SttStarted(call) => {
+                    stt_started.insert(*call, event.at);
+                }
+                ProbeKind::SttFinished(call) => {
+                    if let Some(start) = stt_started.remove(call) {
+                        turns[index].stt_ms = Some(elapsed_ms(start, event.at));
+                    }
+                }
+                ProbeKind::UserTranscript(text) => turns[index].transcript = Some(text.clone()),
+                ProbeKind::LlmStarted(call) => {
+                    llm_started.insert(*call, event.at);
+                    answering = Some(index);
+                }
+                ProbeKind::LlmFirstToken(call) => {
+                    if let Some(start) = llm_started.remove(call)
+                        && let Some(turn) = answering
+                    {
+                        turns[turn].llm_ms = Some(elapsed_ms(start, event.at));
+                    }
+                }
+                ProbeKind::AgentTranscript(text) => {
+                    if let Some(turn) = answering {
+                        match &mut turns[turn].answer {
+                            Some(answer) => {
+                                answer.push(' ');
+                                answer.push_str(text);
+                            }
+                            None => turns[turn].answer = Some(text.clone()),
+                        }
+                    }
+                }
+                ProbeKind::AudioSent => {
+                    if let Some(turn) = answering
+                        && let Some(end) = speech_ends[turn]
+                        && turns[turn].first_audio_ms.is_none()
+                    {
+                        turns[turn].first_audio_ms = Some(elapsed_ms(end, event.at));
+                    }
+                }
+                // the caller barged in on the answer being played
+                ProbeKind::AudioCleared => {
+                    if let Some(turn) = answering {
+                        turns[turn].interrupted = true;
+                    }
+                }
+                ProbeKind::TurnStarted(_) => {}
+            }
+        }
+
+        Self { scenario, turns }
+    }
+
+    /// Turns answered later than `max_ms`, or never answered.
+    pub fn slow_turns(&self, max_ms: u64) -> Vec<usize> {
+        self.turns
+            .iter()
+            .filter(|turn| turn.first_audio_ms.is_none_or(|ms| ms > max_ms))
+            .map(|turn| turn.turn)
+            .collect()
+    }
+}
+
+impl Display for SimulationReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(name) = &self.scenario {
+            writeln!(f, "Scenario: {}", name)?;
+        }
+
+        writeln!(
+            f,
+            "{:>4} {:>8} {:>8} {:>8} {:>8} {:>11}  caller",
+            "turn", "vad", "stt", "llm", "audio", "interrupted"
+        )?;
+
+        for turn in self.turns.iter() {
+            writeln!(
+                f,
+                "{:>4} {:>8} {:>8} {:>8} {:>8} {:>11}  {}",
+                turn.turn,
+                format_ms(turn.vad_endpoint_ms),
+                format_ms(turn.stt_ms),
+                format_ms(turn.llm_ms),
+                format_ms(turn.first_audio_ms),
+                match turn.interrupted {
+                    true => "yes",
+                    false => "no",
+                },
+                turn.caller
+            )?;
+
+            if let Some(answer) = &turn.answer {
+                writeln!(f, "{:>52}  -> {}", "", answer)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn elapsed_ms(from: Instant, to: Instant) -> u64 {
+    to.saturating_duration_since(from).as_millis() as u64
+}
+
+fn format_ms(ms: Option<u64>) -> String {
+    match ms {
+        Some(ms) => format!("{}ms", ms),
+        None => "-".to_string(),
+    }
+}
@@ -0,0 +1,114 @@
+use std::path::{Path, PathBuf};
+
+use anywho::Error;
+use serde::Deserialize;
+
+/// A scripted call, read from a YAML file.
+///
+/// ```yaml
+/// name: prise de rendez-vous
+/// agent: support
+/// max_first_audio_ms: 6000
+/// mock:
+///   replies: ["Pour quand ?", "C'est noté."]
+///   llm_latency_ms: 400
+/// turns:
+///   - text: Je voudrais un rendez-vous
+///   - wav: demain.wav
+///     text: Demain matin
+///   - text: Non attendez
+///     interrupt_after_ms: 800
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+pub struct Scenario {
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Agent profile to run, the default one when not set.
+    #[serde(default)]
+    pub agent: Option<String>,
+    /// Scripted providers instead of the live ones.
+    #[serde(default)]
+    pub mock: Option<MockSetup>,
+    /// Voice rendering the text turns with a live TTS.
+    #[serde(default)]
+    pub caller_voice: Option<String>,
+    /// The run fails when a turn is answered later than this, or not at all.
+    #[serde(default)]
+    pub max_first_audio_ms: Option<u64>,
+    pub turns: Vec<CallerTurn>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct MockSetup {
+    pub replies: Vec<String>,
+    pub stt_latency_ms: u64,
+    pub llm_latency_ms: u64,
+    pub tts_latency_ms: u64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CallerTurn {
+    /// 16 bits PCM WAV file, relative to the scenario file.
+    #[serde(default)]
+    pub wav: Option<PathBuf>,
+    /// What the caller says. Rendered through a TTS when there is no `wav`,
+    /// used as the transcript with mock providers.
+    #[serde(default)]
+    pub text: Option<String>,
+    /// Silence played after the turn.
+    #[serde(default = "CallerTurn::default_pause_ms")]
+    pub pause_ms: u64,
+    /// Speak over the agent this long after its answer to the previous turn started,
+    /// instead of waiting for the end of the previous pause.
+    #[serde(default)]
+    pub interrupt_after_ms: Option<u64>,
+}
+
+impl Scenario {
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        let content = std::fs::read_to_string(path)?;
+        let mut scenario = serde_yaml::from_str::<Scenario>(&content)?;
+
+        let base = path.parent().unwrap_or(Path::new("."));
+        for (index, turn) in scenario.turns.iter_mut().enumerate() {
+            if turn.wav.is_none() && turn.text.is_none() {
+                return Err(Error::msg(format!(
+                    "Turn {} needs a wav or a text",
+                    index + 1
+                )));
+            }
+
+            if let Some(wav) = &turn.wav
+                && wav.is_relative()
+            {
+                turn.wav = Some(base.join(wav));
+            }
+        }
+
+        if scenario.turns.is_empty() {
+            return Err(Error::msg("The scenario has no turn"));
+        }
+
+        Ok(scenario)
+    }
+}
+
+impl CallerTurn {
+    /// Long enough for the VAD to close the turn.
+    fn default_pause_ms() -> u64 {
+        5000
+    }
+
+    /// A short label for the report.
+    pub fn label(&self) -> String {
+        match (&self.text, &self.wav) {
+            (Some(text), _) => text.clone(),
+            (None, Some(wav)) => wav
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default(),
+            (None, None) => String::new(),
+        }
+    }
+}
@@ -1,25 +1,62 @@
+use std::sync::Arc;
+
 use anywho::Error;
+use futures::future::BoxFuture;
 
 use crate::{
     domain::ports::stt::{Stt, SttPayload},
-    infrastructure::stt::scribe_adapter::ScribeAdapter,
+    infrastructure::stt::{mock_stt::MockStt, scribe_adapter::ScribeAdapter},
 };
 
 #[derive(Clone)]
 pub enum SttList {
     Scribe(ScribeAdapter),
+    /// Scripted transcripts for tests and offline runs.
+    Mock(MockStt),
+    /// An adapter registered by the embedding application.
+    Custom(Arc<dyn Stt>),
 }
 
 impl Stt for SttList {
-    async fn execute(&self, bytes: &Vec<i16>) -> Result<SttPayload, Error> {
+    fn execute<'a>(
+        &'a self,
+        bytes: &'a [i16],
+        language_code: &'a str,
+    ) -> BoxFuture<'a, Result<SttPayload, Error>> {
         match self {
-            SttList::Scribe(adapter) => adapter.execute(bytes).await,
+            SttList::Scribe(adapter) => adapter.execute(bytes, language_code),
+            SttList::Mock(adapter) => adapter.execute(bytes, language_code),
+            SttList::Custom(adapter) => adapter.execute(bytes, language_code),
         }
     }
 
-    async fn write_audio_file(&self, filename: String, bytes: &Vec<i16>) -> Result<(), Error> {
+    fn write_audio_file<'a>(
+        &'a self,
+        filename: String,
+        bytes: &'a [i16],
+    ) -> BoxFuture<'a, Result<(), Error>> {
         match self {
-            SttList::Scribe(adapter) => adapter.write_audio_file(filename, bytes).await,
+            SttList::Scribe(adapter) => adapter.write_audio_file(filename, bytes),
+            SttList::Mock(adapter) => adapter.write_audio_file(filename, bytes),
+            SttList::Custom(adapter) => adapter.write_audio_file(filename, bytes),
         }
     }
 }
+
+impl From<ScribeAdapter> for SttList {
+    fn from(adapter: ScribeAdapter) -> Self {
+        SttList::Scribe(adapter)
+    }
+}
+
+impl From<MockStt> for SttList {
+    fn from(adapter: MockStt) -> Self {
+        SttList::Mock(adapter)
+    }
+}
+
+impl From<Arc<dyn Stt>> for SttList {
+    fn from(adapter: Arc<dyn Stt>) -> Self {
+        SttList::Custom(adapter)
+    }
+}
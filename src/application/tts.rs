@@ -0,0 +1,50 @@
+use std::sync::Arc;
+
+use anywho::Error;
+use futures::future::BoxFuture;
+
+use crate::{
+    domain::ports::tts::Tts,
+    infrastructure::tts::{elevenlabs_adapter::ElevenLabsTtsAdapter, mock_tts::MockTts},
+};
+
+#[derive(Clone)]
+pub enum TtsList {
+    ElevenLabs(ElevenLabsTtsAdapter),
+    /// Deterministic audio for tests and offline runs.
+    Mock(MockTts),
+    /// An adapter registered by the embedding application.
+    Custom(Arc<dyn Tts>),
+}
+
+impl Tts for TtsList {
+    fn synthesize<'a>(
+        &'a self,
+        text: &'a str,
+        voice_id: Option<&'a str>,
+    ) -> BoxFuture<'a, Result<Vec<i16>, Error>> {
+        match self {
+            TtsList::ElevenLabs(adapter) => adapter.synthesize(text, voice_id),
+            TtsList::Mock(adapter) => adapter.synthesize(text, voice_id),
+            TtsList::Custom(adapter) => adapter.synthesize(text, voice_id),
+        }
+    }
+}
+
+impl From<ElevenLabsTtsAdapter> for TtsList {
+    fn from(adapter: ElevenLabsTtsAdapter) -> Self {
+        TtsList::ElevenLabs(adapter)
+    }
+}
+
+impl From<MockTts> for TtsList {
+    fn from(adapter: MockTts) -> Self {
+        TtsList::Mock(adapter)
+    }
+}
+
+impl From<Arc<dyn Tts>> for TtsList {
+    fn from(adapter: Arc<dyn Tts>) -> Self {
+        TtsList::Custom(adapter)
+    }
+}
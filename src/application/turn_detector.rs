@@ -0,0 +1,67 @@
+use crate::{
+    domain::{
+        entities::agent_profile::VadSettings, ports::turn_detector::TurnDetector,
+        utils::units::Millis,
+    },
+    infrastructure::turn_detector::{
+        semantic_turn_detector::SemanticTurnDetector, silence_turn_detector::SilenceTurnDetector,
+    },
+};
+
+/// Each session configures its own detector, registries hold factories building them.
+pub enum TurnDetectorList {
+    Silence(SilenceTurnDetector),
+    Semantic(SemanticTurnDetector),
+    /// A detector registered by the embedding application.
+    Custom(Box<dyn TurnDetector>),
+}
+
+impl Default for TurnDetectorList {
+    fn default() -> Self {
+        TurnDetectorList::Silence(SilenceTurnDetector::new())
+    }
+}
+
+impl TurnDetector for TurnDetectorList {
+    fn configure(&mut self, settings: &VadSettings) {
+        match self {
+            TurnDetectorList::Silence(detector) => detector.configure(settings),
+            TurnDetectorList::Semantic(detector) => detector.configure(settings),
+            TurnDetectorList::Custom(detector) => detector.configure(settings),
+        }
+    }
+
+    fn max_silence_ms(&self) -> Millis {
+        match self {
+            TurnDetectorList::Silence(detector) => detector.max_silence_ms(),
+            TurnDetectorList::Semantic(detector) => detector.max_silence_ms(),
+            TurnDetectorList::Custom(detector) => detector.max_silence_ms(),
+        }
+    }
+
+    fn silence_ms(&self, transcript: Option<&str>) -> Millis {
+        match self {
+            TurnDetectorList::Silence(detector) => detector.silence_ms(transcript),
+            TurnDetectorList::Semantic(detector) => detector.silence_ms(transcript),
+            TurnDetectorList::Custom(detector) => detector.silence_ms(transcript),
+        }
+    }
+}
+
+impl From<SilenceTurnDetector> for TurnDetectorList {
+    fn from(detector: SilenceTurnDetector) -> Self {
+        TurnDetectorList::Silence(detector)
+    }
+}
+
+impl From<SemanticTurnDetector> for TurnDetectorList {
+    fn from(detector: SemanticTurnDetector) -> Self {
+        TurnDetectorList::Semantic(detector)
+    }
+}
+
+impl From<Box<dyn TurnDetector>> for TurnDetectorList {
+    fn from(detector: Box<dyn TurnDetector>) -> Self {
+        TurnDetectorList::Custom(detector)
+    }
+}
@@ -1,26 +1,81 @@
 use crate::{
     domain::{
-        entities::audio_buffer::AudioBuffer,
+        entities::{agent_profile::VadSettings, audio_buffer::AudioBuffer},
         ports::vad::{Vad, VadEvent},
     },
-    infrastructure::vad::local_vad::LocalVadAdapter,
+    infrastructure::vad::{
+        gmm_vad::GmmVadAdapter, local_vad::LocalVadAdapter, silero_vad::SileroVadAdapter,
+    },
 };
 
-#[derive(Debug, Clone)]
+/// Each session owns its detector, registries hold factories building them.
 pub enum VadList {
     Local(LocalVadAdapter),
+    Silero(SileroVadAdapter),
+    /// Boxed, its models make it much larger than the other detectors.
+    Gmm(Box<GmmVadAdapter>),
+    /// An adapter registered by the embedding application.
+    Custom(Box<dyn Vad>),
 }
 
 impl Vad for VadList {
+    fn configure(&mut self, settings: &VadSettings) {
+        match self {
+            VadList::Local(adapter) => adapter.configure(settings),
+            VadList::Silero(adapter) => adapter.configure(settings),
+            VadList::Gmm(adapter) => adapter.configure(settings),
+            VadList::Custom(adapter) => adapter.configure(settings),
+        }
+    }
+
     fn process_audio(&mut self, audio_buffer: &mut AudioBuffer) -> VadEvent {
         match self {
             VadList::Local(adapter) => adapter.process_audio(audio_buffer),
+            VadList::Silero(adapter) => adapter.process_audio(audio_buffer),
+            VadList::Gmm(adapter) => adapter.process_audio(audio_buffer),
+            VadList::Custom(adapter) => adapter.process_audio(audio_buffer),
         }
     }
 
     fn is_speech(&self, bytes: &[i16]) -> bool {
         match self {
             VadList::Local(adapter) => adapter.is_speech(bytes),
+            VadList::Silero(adapter) => adapter.is_speech(bytes),
+            VadList::Gmm(adapter) => adapter.is_speech(bytes),
+            VadList::Custom(adapter) => adapter.is_speech(bytes),
         }
     }
+
+    fn noise_floor(&self) -> Option<f32> {
+        match self {
+            VadList::Local(adapter) => adapter.noise_floor(),
+            VadList::Silero(adapter) => adapter.noise_floor(),
+            VadList::Gmm(adapter) => adapter.noise_floor(),
+            VadList::Custom(adapter) => adapter.noise_floor(),
+        }
+    }
+}
+
+impl From<LocalVadAdapter> for VadList {
+    fn from(adapter: LocalVadAdapter) -> Self {
+        VadList::Local(adapter)
+    }
+}
+
+impl From<SileroVadAdapter> for VadList {
+    fn from(adapter: SileroVadAdapter) -> Self {
+        VadList::Silero(adapter)
+    }
+}
+
+impl From<GmmVadAdapter> for VadList {
+    fn from(adapter: GmmVadAdapter) -> Self {
+        VadList::Gmm(Box::new(adapter))
+    }
+}
+
+impl From<Box<dyn Vad>> for VadList {
+    fn from(adapter: Box<dyn Vad>) -> Self {
+        VadList::Custom(adapter)
+    }
 }
@@ -0,0 +1,128 @@
+use std::path::PathBuf;
+
+use clap::Parser;
+use voicehanler_rs::{
+    application::{
+        env::{
+            agent::AgentEnv, aistudio::AiStudioEnv, capture::CaptureEnv, elevenlabs::ElevenLabsEnv,
+            logger::LoggerEnv, tools::ToolsEnv, vad::VadEnv,
+        },
+        http::app_state::AppState,
+        registry::ProviderRegistry,
+        simulator::{self, scenario::Scenario},
+    },
+    domain::entities::{audio_buffer::DEFAULT_RETENTION, pipeline::pool_manager::PoolManager},
+};
+
+/// Plays a scripted call against the agent and reports the latency of each turn.
+#[derive(Debug, Parser)]
+struct SimulatorArgs {
+    /// YAML scenario
+    scenario: PathBuf,
+
+    /// Print the report as JSON
+    #[arg(long)]
+    json: bool,
+
+    #[command(flatten)]
+    logger: LoggerEnv,
+
+    #[command(flatten)]
+    agent: AgentEnv,
+
+    #[command(flatten)]
+    tools: ToolsEnv,
+
+    #[command(flatten)]
+    vad: VadEnv,
+}
+
+/// Provider credentials, only needed when the scenario does not mock them.
+#[derive(Debug, Parser)]
+struct LiveArgs {
+    #[command(flatten)]
+    elevenlabs: ElevenLabsEnv,
+
+    #[command(flatten)]
+    llm: AiStudioEnv,
+}
+
+#[tokio::main(flavor = "multi_thread")]
+async fn main() {
+    dotenv::dotenv().ok();
+
+    let args = SimulatorArgs::parse();
+
+    let subscriber = tracing_subscriber::fmt()
+        .with_env_filter(args.logger.level.to_string())
+        .with_writer(std::io::stderr);
+
+    if args.logger.prettify {
+        subscriber.json().init();
+    } else {
+        subscriber.init();
+    }
+
+    let scenario = Scenario::load(&args.scenario).unwrap_or_else(|err| {
+        eprintln!("Unreadable scenario {}: {:?}", args.scenario.display(), err);
+        std::process::exit(2);
+    });
+
+    let providers = match scenario.mock {
+        Some(_) => ProviderRegistry::new(),
+        None => {
+            let live = LiveArgs::try_parse_from(["simulator"]).unwrap_or_else(|err| err.exit());
+            let missing = [live.elevenlabs.missing(), live.llm.missing()].concat();
+            if !missing.is_empty() {
+                eprintln!(
+                    "Missing {}, needed by unmocked scenarios",
+                    missing.join(", ")
+                );
+                std::process::exit(2);
+            }
+            ProviderRegistry::builtin(&live.elevenlabs, &live.llm)
+        }
+    };
+
+    let state = AppState::new(
+        PoolManager::new(1),
+        providers,
+        args.tools.tools().expect("Unreadable TOOLS_FILE"),
+        args.agent
+            .agents(args.vad.settings().expect("Invalid VAD settings"))
+            .expect("Unreadable AGENT_PROFILES_FILE"),
+        CaptureEnv {
+            capture_dir: None,
+            capture_audio: false,
+        },
+        DEFAULT_RETENTION,
+    );
+
+    let report = match simulator::run(&state, &scenario).await {
+        Ok(report) => report,
+        Err(err) => {
+            eprintln!("{:?}", err);
+            std::process::exit(1);
+        }
+    };
+
+    if args.json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&report).expect("Serializable report")
+        );
+    } else {
+        print!("{}", report);
+    }
+
+    if let Some(max_ms) = scenario.max_first_audio_ms {
+        let slow = report.slow_turns(max_ms);
+        if !slow.is_empty() {
+            eprintln!(
+                "Turns {:?} were answered later than {} ms or not at all",
+                slow, max_ms
+            );
+            std::process::exit(1);
+        }
+    }
+}
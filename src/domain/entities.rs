@@ -1,5 +1,12 @@
+pub mod agent_profile;
+pub mod agent_registry;
 pub mod audio_buffer;
 pub mod audio_source_layer;
+pub mod barge_in;
+pub mod call_metadata;
 pub mod history;
 pub mod job;
 pub mod pipeline;
+pub mod playback;
+pub mod timeline;
+pub mod tool_registry;
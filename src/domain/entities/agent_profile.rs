@@ -0,0 +1,275 @@
+use std::{collections::HashMap, ops::RangeInclusive, str::FromStr};
+
+use anywho::Error;
+use serde::{Deserialize, Serialize};
+
+use crate::domain::{entities::barge_in::BargeInMode, utils::units::Millis};
+
+/// Everything that makes one agent different from another on the same deployment.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct AgentProfile {
+    pub name: String,
+    pub system_prompt: Option<String>,
+    pub model: String,
+    pub sampling: SamplingParams,
+    pub stt_language: String,
+    /// Voice used for the replies, the deployment default when unset.
+    pub tts_voice: Option<String>,
+    /// Endpointing tuned for this agent, on top of the deployment settings.
+    pub vad: VadOverrides,
+    /// How the agent reacts when the caller talks over it, the deployment
+    /// default when unset.
+    pub barge_in: Option<BargeInMode>,
+    /// Names of the registered tools the agent may call, all of them when unset.
+    pub tools: Option<Vec<String>>,
+    pub providers: ProviderNames,
+}
+
+/// Registered adapters the agent runs on, the deployment defaults when unset.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct ProviderNames {
+    pub stt: Option<String>,
+    pub llm: Option<String>,
+    pub tts: Option<String>,
+    pub vad: Option<String>,
+    pub turn_detector: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(default)]
+pub struct SamplingParams {
+    pub temperature: f64,
+    pub top_p: f64,
+    pub max_tokens: u32,
+}
+
+/// Voice activity detection and end of turn settings of a session.
+#[derive(Debug, Clone, Copy)]
+pub struct VadSettings {
+    /// RMS energy a frame must reach to be speech, for the energy detector.
+    pub threshold: f32,
+    /// Frame length of the energy detector, the others use the one of their model.
+    pub frame_ms: Millis,
+    /// Silence after which the turn is over.
+    pub full_stop_ms: Millis,
+    /// Speech shorter than this is not worth a pipeline.
+    pub min_speech_ms: Millis,
+    /// Frames kept up to the first speech frame included, so the onset is not clipped.
+    pub pre_roll_frames: u64,
+    /// Silent frames waited, and kept, before a pause starts a pipeline.
+    pub pause_trailer_frames: u64,
+    /// Derives the energy threshold from the line noise instead of `threshold`.
+    pub adaptive: Option<AdaptiveThreshold>,
+}
+
+/// Settings a profile or a session changes, the others are inherited.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VadOverrides {
+    pub threshold: Option<f32>,
+    /// Only read by the energy VAD, the gmm and silero ones keep their own frames.
+    pub frame_ms: Option<Millis>,
+    pub full_stop_ms: Option<Millis>,
+    pub min_speech_ms: Option<Millis>,
+    pub pre_roll_frames: Option<u64>,
+    pub pause_trailer_frames: Option<u64>,
+    pub adaptive: Option<AdaptiveThreshold>,
+}
+
+/// Speech threshold set as a margin above the background noise, measured on
+/// the frames that were not speech.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AdaptiveThreshold {
+    /// Span of non-speech audio the noise floor is estimated over.
+    pub window_ms: Millis,
+    /// Margin above the floor a frame must reach to start speech.
+    pub onset_db: f32,
+    /// Margin above the floor a frame must keep for speech to go on.
+    pub offset_db: f32,
+    /// Lowest floor assumed, so digital silence does not make every click speech.
+    pub min_floor: f32,
+}
+
+impl Default for AgentProfile {
+    fn default() -> Self {
+        Self {
+            name: "default".to_string(),
+            system_prompt: None,
+            model: "gemini-2.0-flash".to_string(),
+            sampling: SamplingParams::default(),
+            stt_language: "fra".to_string(),
+            tts_voice: None,
+            vad: VadOverrides::default(),
+            barge_in: None,
+            tools: None,
+            providers: ProviderNames::default(),
+        }
+    }
+}
+
+impl Default for SamplingParams {
+    fn default() -> Self {
+        Self {
+            temperature: 0.2,
+            top_p: 0.8,
+            max_tokens: 512,
+        }
+    }
+}
+
+impl Default for VadSettings {
+    fn default() -> Self {
+        Self {
+            threshold: 800.0,
+            full_stop_ms: Millis(2000),
+            min_speech_ms: Millis(200),
+            frame_ms: Millis(32),
+            pre_roll_frames: 3,
+            pause_trailer_frames: 2,
+            adaptive: None,
+        }
+    }
+}
+
+impl VadSettings {
+    /// Silences that may end a turn, shorter ones cut callers mid-sentence.
+    pub const FULL_STOP_RANGE: RangeInclusive<Millis> = Millis(100)..=Millis(10_000);
+
+    pub fn merged(&self, overrides: &VadOverrides) -> Self {
+        Self {
+            threshold: overrides.threshold.unwrap_or(self.threshold),
+            frame_ms: overrides.frame_ms.unwrap_or(self.frame_ms),
+            full_stop_ms: overrides.full_stop_ms.unwrap_or(self.full_stop_ms),
+            min_speech_ms: overrides.min_speech_ms.unwrap_or(self.min_speech_ms),
+            pre_roll_frames: overrides.pre_roll_frames.unwrap_or(self.pre_roll_frames),
+            pause_trailer_frames: overrides
+                .pause_trailer_frames
+                .unwrap_or(self.pause_trailer_frames),
+            adaptive: overrides.adaptive.or(self.adaptive),
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), Error> {
+        let check = |valid: bool, message: String| match valid {
+            true => Ok(()),
+            false => Err(Error::msg(format!("Invalid VAD settings: {}", message))),
+        };
+
+        check(
+            self.threshold > 0.0,
+            format!("threshold must be positive, got {}", self.threshold),
+        )?;
+        check(
+            (Millis(10)..=Millis(100)).contains(&self.frame_ms),
+            format!(
+                "frame_ms must be between 10 and 100 ms, got {}",
+                self.frame_ms
+            ),
+        )?;
+        check(
+            Self::FULL_STOP_RANGE.contains(&self.full_stop_ms),
+            format!(
+                "full_stop_ms must be between 100 and 10000 ms, got {}",
+                self.full_stop_ms
+            ),
+        )?;
+        check(
+            self.min_speech_ms <= self.full_stop_ms,
+            format!(
+                "min_speech_ms ({}) cannot exceed full_stop_ms ({})",
+                self.min_speech_ms, self.full_stop_ms
+            ),
+        )?;
+        check(
+            (1..=10).contains(&self.pre_roll_frames),
+            format!(
+                "pre_roll_frames must be between 1 and 10, got {}",
+                self.pre_roll_frames
+            ),
+        )?;
+        check(
+            (1..=10).contains(&self.pause_trailer_frames),
+            format!(
+                "pause_trailer_frames must be between 1 and 10, got {}",
+                self.pause_trailer_frames
+            ),
+        )?;
+        check(
+            self.frame_ms * self.pause_trailer_frames < self.full_stop_ms,
+            format!(
+                "pause_trailer_frames ({} x {}) must end before full_stop_ms ({})",
+                self.pause_trailer_frames, self.frame_ms, self.full_stop_ms
+            ),
+        )?;
+
+        if let Some(adaptive) = &self.adaptive {
+            check(
+                adaptive.window_ms >= self.frame_ms,
+                format!(
+                    "adaptive.window_ms ({}) must hold at least one frame",
+                    adaptive.window_ms
+                ),
+            )?;
+            check(
+                0.0 <= adaptive.offset_db && adaptive.offset_db <= adaptive.onset_db,
+                format!(
+                    "adaptive margins must satisfy 0 <= offset_db ({}) <= onset_db ({})",
+                    adaptive.offset_db, adaptive.onset_db
+                ),
+            )?;
+            check(
+                adaptive.min_floor > 0.0,
+                format!(
+                    "adaptive.min_floor must be positive, got {}",
+                    adaptive.min_floor
+                ),
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+impl VadOverrides {
+    /// Reads the `vad_*` parameters of a WebSocket query string or of Twilio
+    /// custom parameters, ignoring every other key.
+    pub fn from_params(params: &HashMap<String, String>) -> Result<Self, Error> {
+        fn parse<T: FromStr>(
+            params: &HashMap<String, String>,
+            key: &str,
+        ) -> Result<Option<T>, Error> {
+            params
+                .get(key)
+                .map(|value| {
+                    value
+                        .parse::<T>()
+                        .map_err(|_| Error::msg(format!("Invalid {}: {}", key, value)))
+                })
+                .transpose()
+        }
+
+        Ok(Self {
+            threshold: parse(params, "vad_threshold")?,
+            frame_ms: parse(params, "vad_frame_ms")?,
+            full_stop_ms: parse(params, "vad_full_stop_ms")?,
+            min_speech_ms: parse(params, "vad_min_speech_ms")?,
+            pre_roll_frames: parse(params, "vad_pre_roll_frames")?,
+            pause_trailer_frames: parse(params, "vad_pause_trailer_frames")?,
+            adaptive: None,
+        })
+    }
+}
+
+impl Default for AdaptiveThreshold {
+    fn default() -> Self {
+        Self {
+            window_ms: Millis(2000),
+            onset_db: 9.0,
+            offset_db: 5.0,
+            min_floor: 50.0,
+        }
+    }
+}
@@ -0,0 +1,116 @@
+use std::{collections::HashMap, sync::Arc};
+
+use anywho::Error;
+use tracing::warn;
+
+use crate::domain::entities::{
+    agent_profile::{AgentProfile, VadOverrides, VadSettings},
+    barge_in::BargeInMode,
+};
+
+/// The agent profiles a deployment can run, sessions pick one by name.
+#[derive(Debug, Clone)]
+pub struct AgentRegistry {
+    profiles: Arc<HashMap<String, AgentProfile>>,
+    default: String,
+    vad: VadSettings,
+    barge_in: BargeInMode,
+}
+
+impl AgentRegistry {
+    /// Falls back to the first profile when no default name is given, `vad` and
+    /// `barge_in` are the settings of the deployment the profiles override.
+    pub fn new(
+        profiles: Vec<AgentProfile>,
+        default: Option<String>,
+        vad: VadSettings,
+        barge_in: BargeInMode,
+    ) -> Result<Self, Error> {
+        let profiles = match profiles.is_empty() {
+            true => vec![AgentProfile::default()],
+            false => profiles,
+        };
+
+        let default = default.unwrap_or_else(|| profiles[0].name.clone());
+        let profiles: HashMap<String, AgentProfile> = profiles
+            .into_iter()
+            .map(|profile| (profile.name.clone(), profile))
+            .collect();
+
+        if !profiles.contains_key(&default) {
+            return Err(Error::msg(format!("Unknown default agent {}", default)));
+        }
+
+        for profile in profiles.values() {
+            vad.merged(&profile.vad)
+                .validate()
+                .map_err(|err| Error::msg(format!("Agent {}: {}", profile.name, err)))?;
+        }
+
+        Ok(Self {
+            profiles: Arc::new(profiles),
+            default,
+            vad,
+            barge_in,
+        })
+    }
+
+    /// Endpointing of a session running `agent`, tuned by the session itself.
+    ///
+    /// Invalid session overrides are dropped with a warning, the call goes on
+    /// with the settings of the agent.
+    pub fn vad_settings(&self, agent: &AgentProfile, session: &VadOverrides) -> VadSettings {
+        let settings = self.vad.merged(&agent.vad);
+        let tuned = settings.merged(session);
+
+        match tuned.validate() {
+            Ok(()) => tuned,
+            Err(err) => {
+                warn!("{}, using the settings of agent {}", err, agent.name);
+                settings
+            }
+        }
+    }
+
+    pub fn vad_defaults(&self) -> VadSettings {
+        self.vad
+    }
+
+    /// Barge-in mode of a session running `agent`.
+    pub fn barge_in(&self, agent: &AgentProfile) -> BargeInMode {
+        agent.barge_in.unwrap_or(self.barge_in)
+    }
+
+    pub fn default_profile(&self) -> AgentProfile {
+        self.profiles[&self.default].clone()
+    }
+
+    /// Returns the requested profile, or the default one when it does not exist.
+    pub fn get(&self, name: Option<&str>) -> AgentProfile {
+        let Some(name) = name else {
+            return self.default_profile();
+        };
+
+        match self.profiles.get(name) {
+            Some(profile) => profile.clone(),
+            None => {
+                warn!("Unknown agent {}, using {}", name, self.default);
+                self.default_profile()
+            }
+        }
+    }
+}
+
+impl Default for AgentRegistry {
+    fn default() -> Self {
+        Self {
+            profiles: Arc::new(HashMap::from([(
+                "default".to_string(),
+                AgentProfile::default(),
+            )])),
+            default: "default".to_string(),
+            vad: VadSettings::default(),
+            barge_in: BargeInMode::default(),
+        }
+    }
+}
@@ -1,31 +1,115 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, ops::Range};
+
+use chrono::Utc;
+
+use crate::domain::{
+    entities::timeline::Timeline,
+    ports::{audio_sink::AudioSink, audio_source::InboundFrame},
+    utils::{
+        ring_buffer::RingBuffer,
+        units::{Millis, SampleRate, Samples},
+    },
+};
+
+/// Caller audio kept behind the active turn, or behind the cursor between turns.
+pub const DEFAULT_RETENTION: Millis = Millis(10_000);
 
-#[derive(Debug, Clone)]
 pub struct AudioBuffer {
     pub agent: Vec<i16>,
-    pub user: Vec<i16>,
-    pub streamed_content: String,
+    /// Caller audio of the active turn and the retention before it, `cursor`,
+    /// `start` and `end` are absolute offsets into it.
+    pub user: RingBuffer,
+    pub streamed_content: InboundFrame,
 
-    pub cursor: u64,
-    pub start: Option<u64>,
-    pub end: Option<u64>,
-    pub events: HashMap<u64, String>,
+    pub cursor: Samples,
+    pub start: Option<Samples>,
+    pub end: Option<Samples>,
+    pub events: HashMap<Samples, String>,
+    /// Wall-clock time of the offsets of `user`.
+    pub timeline: Timeline,
+
+    retention: Samples,
+    sink: Option<Box<dyn AudioSink>>,
 }
 
 impl AudioBuffer {
     pub fn new() -> Self {
         AudioBuffer {
             agent: Vec::new(),
-            user: Vec::new(),
-            streamed_content: "".to_string(),
-            cursor: 0,
+            user: RingBuffer::new(),
+            streamed_content: InboundFrame::Text(String::new()),
+            cursor: Samples::ZERO,
             start: None,
             end: None,
             events: HashMap::new(),
+            timeline: Timeline::new(SampleRate::PIPELINE),
+            retention: SampleRate::PIPELINE.samples(DEFAULT_RETENTION),
+            sink: None,
         }
     }
 
-    pub fn override_streamed_buffer(&mut self, content: String) {
+    pub fn with_retention(mut self, retention: Millis) -> Self {
+        self.retention = SampleRate::PIPELINE.samples(retention);
+        self
+    }
+
+    /// Hands every caller sample to `sink` before it can be dropped.
+    pub fn with_sink(mut self, sink: impl AudioSink + 'static) -> Self {
+        self.sink = Some(Box::new(sink));
+        self
+    }
+
+    pub fn override_streamed_buffer(&mut self, content: InboundFrame) {
         self.streamed_content = content;
     }
+
+    /// Appends caller audio and drops what is older than the active turn plus
+    /// the retention.
+    pub fn push_user(&mut self, samples: &[i16]) {
+        let stall = self
+            .timeline
+            .record(self.user.written(), Samples::of(samples), Utc::now());
+        if let Some(sink) = &mut self.sink {
+            // the recording keeps the stalls as silence, so it stays on the wall clock
+            if stall > Samples::ZERO {
+                sink.write(&vec![0; stall.as_usize()]);
+            }
+            sink.write(samples);
+        }
+        self.user.push(samples);
+
+        let keep_from = self
+            .start
+            .unwrap_or(self.cursor)
+            .min(self.cursor)
+            .saturating_sub(self.retention);
+        if keep_from > self.user.oldest() {
+            self.user.discard_before(keep_from);
+            self.events.retain(|offset, _| *offset >= keep_from);
+        }
+    }
+
+    /// Caller audio of `range`, `None` once part of it was dropped.
+    pub fn user_audio(&self, range: Range<Samples>) -> Option<Vec<i16>> {
+        self.user.get(range).map(|samples| samples.into_owned())
+    }
+}
+
+impl Default for AudioBuffer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Debug for AudioBuffer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AudioBuffer")
+            .field("oldest", &self.user.oldest())
+            .field("written", &self.user.written())
+            .field("cursor", &self.cursor)
+            .field("start", &self.start)
+            .field("end", &self.end)
+            .field("retention", &self.retention)
+            .finish_non_exhaustive()
+    }
 }
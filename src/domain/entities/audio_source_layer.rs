@@ -1,66 +1,259 @@
-use std::{pin::Pin, sync::Arc};
+use std::{pin::Pin, sync::Arc, time::Duration};
 
 use anywho::Error;
-use tracing::warn;
+use chrono::Utc;
+use tokio::time::{Instant, sleep};
+use tracing::{debug, info, warn};
 use uuid::Uuid;
 
 use crate::{
-    application::{llm::LlmList, stt::SttList, vad::VadList},
+    application::{
+        http::app_state::AppState, llm::LlmList, registry::ProviderRegistry, stt::SttList,
+        tts::TtsList, turn_detector::TurnDetectorList, vad::VadList,
+    },
     domain::{
         entities::{
+            agent_profile::{AgentProfile, VadOverrides, VadSettings},
+            agent_registry::AgentRegistry,
             audio_buffer::AudioBuffer,
-            history::history::History,
-            pipeline::{pipeline::PipelineStatus, pool_manager::PoolManager},
+            barge_in::BargeInMode,
+            call_metadata::CallMetadata,
+            history::{
+                history::History, history_event::HistoryEventPayload, history_member::HistoryMember,
+            },
+            pipeline::{
+                pipeline::{PipelineContext, PipelineStatus},
+                pool_manager::PoolManager,
+            },
+            playback::Playback,
+            tool_registry::ToolRegistry,
+        },
+        ports::{
+            audio_source::{AudioSource, AudioSourceEvent, SessionEvent},
+            turn_detector::TurnDetector,
+            vad::{Vad, VadEvent},
+        },
+        utils::{
+            reactive::Reactive,
+            units::{Millis, SampleRate, Samples},
         },
-        ports::vad::{Vad, VadEvent},
     },
 };
 
+/// Frames fed by the file and simulated callers, the pace of a phone line.
+pub const FRAME: Millis = Millis(20);
+
+/// Longest time the last answer may take once the caller is done.
+const PIPELINE_DRAIN_TIMEOUT: Duration = Duration::from_secs(60);
+
 pub struct AudioSourceLayer<'a> {
     pub id: Uuid,
+    pub agent: AgentProfile,
+    pub agents: AgentRegistry,
+    pub providers: ProviderRegistry,
     pub vad: &'a mut VadList,
+    /// Endpointing asked by the session, on top of the one of its agent.
+    pub vad_overrides: VadOverrides,
+    pub turn_detector: TurnDetectorList,
+    /// Silence the VAD ends a turn after, the longest an answer waits.
+    pub full_stop_ms: Millis,
     pub stt: SttList,
     pub llm: LlmList,
+    pub tts: TtsList,
     pub pool_manager: PoolManager,
+    /// Speculative transcript of the latest pipeline, each pipeline gets its
+    /// own so a cancelled one cannot overwrite it.
+    pub transcript: Reactive<Option<String>>,
     pub history: &'a mut History,
     pub audio_buffer: &'a mut AudioBuffer,
     pub send_audio: SendAudioCallback,
+    pub clear_audio: ClearAudioCallback,
+    pub send_event: SessionEventCallback,
+    pub playback: Playback,
+    pub tools: ToolRegistry,
+    pub barge_in: BargeInMode,
+    pub pending_barge_in: bool,
+    pub metadata: CallMetadata,
+    pub ended: bool,
 }
 
-impl AudioSourceLayer<'_> {
-    pub async fn process(&mut self, pcm: &Vec<i16>) {
-        self.audio_buffer.user.extend_from_slice(&pcm);
+impl<'a> AudioSourceLayer<'a> {
+    /// A session on the default adapters of `providers`, its agent still has
+    /// to be applied with `apply_agent`.
+    pub fn new(
+        id: Uuid,
+        state: &AppState,
+        providers: &ProviderRegistry,
+        callbacks: SessionCallbacks,
+        vad: &'a mut VadList,
+        history: &'a mut History,
+        audio_buffer: &'a mut AudioBuffer,
+    ) -> Result<Self, Error> {
+        Ok(Self {
+            id,
+            agent: AgentProfile::default(),
+            agents: state.agents.clone(),
+            providers: providers.clone(),
+            vad,
+            vad_overrides: VadOverrides::default(),
+            turn_detector: TurnDetectorList::default(),
+            full_stop_ms: VadSettings::default().full_stop_ms,
+            stt: providers
+                .stt(None)
+                .ok_or_else(|| Error::msg("No STT registered"))?,
+            llm: providers
+                .llm(None)
+                .ok_or_else(|| Error::msg("No LLM registered"))?,
+            tts: providers
+                .tts(None)
+                .ok_or_else(|| Error::msg("No TTS registered"))?,
+            pool_manager: state.pool_manager.clone(),
+            transcript: Reactive::new(None),
+            history,
+            audio_buffer,
+            send_audio: callbacks.send_audio,
+            clear_audio: callbacks.clear_audio,
+            send_event: callbacks.send_event,
+            playback: Playback::new(),
+            tools: state.tools.clone(),
+            barge_in: state.agents.barge_in(&AgentProfile::default()),
+            pending_barge_in: false,
+            metadata: CallMetadata::new(),
+            ended: false,
+        })
+    }
+
+    pub async fn dispatch(&mut self, event: AudioSourceEvent) {
+        self.history.sync();
+
+        match event {
+            AudioSourceEvent::Started(metadata) => {
+                info!(
+                    "Session {} started call_sid={:?} stream_sid={:?}",
+                    self.id, metadata.call_sid, metadata.stream_sid
+                );
+
+                let params = &metadata.custom_parameters;
+                let tuned = match VadOverrides::from_params(params) {
+                    Ok(overrides) => {
+                        self.vad_overrides = overrides;
+                        params.keys().any(|key| key.starts_with("vad_"))
+                    }
+                    Err(err) => {
+                        warn!("Session {} ignores its VAD parameters: {}", self.id, err);
+                        false
+                    }
+                };
+
+                match params.get("agent") {
+                    Some(name) => self.apply_agent(self.agents.get(Some(name))),
+                    None if tuned => self.apply_agent(self.agent.clone()),
+                    None => {}
+                }
+
+                self.metadata = metadata;
+            }
+            AudioSourceEvent::Stopped => {
+                info!("Session {} stopped by the audio source", self.id);
+                self.pool_manager.stop_pipeline(&self.id).await;
+                self.ended = true;
+            }
+            AudioSourceEvent::Mark(name) => {
+                debug!("Session {} reached mark {}", self.id, name);
+                self.audio_buffer
+                    .events
+                    .insert(self.audio_buffer.cursor, name);
+            }
+            AudioSourceEvent::Dtmf(digit) => {
+                debug!("Session {} received DTMF {}", self.id, digit);
+                self.history.add(HistoryEventPayload {
+                    member: HistoryMember::User,
+                    content: Some(format!("[DTMF] {}", digit)),
+                    created_at: self
+                        .audio_buffer
+                        .timeline
+                        .at(self.audio_buffer.user.written())
+                        .unwrap_or_else(Utc::now),
+                    tool_call: None,
+                });
+            }
+        }
+    }
+
+    /// Switches the session to another agent, reconfiguring its providers.
+    pub fn apply_agent(&mut self, agent: AgentProfile) {
+        info!("Session {} runs agent {}", self.id, agent.name);
+
+        let names = &agent.providers;
+        if let Some(stt) = self.providers.stt(names.stt.as_deref()) {
+            self.stt = stt;
+        }
+        if let Some(llm) = self.providers.llm(names.llm.as_deref()) {
+            self.llm = llm;
+        }
+        if let Some(tts) = self.providers.tts(names.tts.as_deref()) {
+            self.tts = tts;
+        }
+        if let Some(vad) = self.providers.vad(names.vad.as_deref()) {
+            *self.vad = vad;
+        }
+        if let Some(turn_detector) = self.providers.turn_detector(names.turn_detector.as_deref()) {
+            self.turn_detector = turn_detector;
+        }
+
+        let mut settings = self.agents.vad_settings(&agent, &self.vad_overrides);
+        self.turn_detector.configure(&settings);
+        // the detector ends the turn itself, the VAD full stop only bounds its wait
+        let range = VadSettings::FULL_STOP_RANGE;
+        settings.full_stop_ms = self
+            .turn_detector
+            .max_silence_ms()
+            .clamp(*range.start(), *range.end());
+        self.vad.configure(&settings);
+        self.full_stop_ms = settings.full_stop_ms;
+
+        self.barge_in = self.agents.barge_in(&agent);
+        self.pending_barge_in = false;
+        self.agent = agent;
+    }
+
+    pub async fn process(&mut self, pcm: &[i16]) {
+        self.history.sync();
+        self.audio_buffer.push_user(pcm);
+
+        let mut event = self.vad.process_audio(self.audio_buffer);
+        if matches!(event, VadEvent::WaitingMoreChunks) && self.is_turn_over() {
+            self.audio_buffer.start = None;
+            self.audio_buffer.end = None;
+            event = VadEvent::SpeechFullStop;
+        }
+        if !matches!(event, VadEvent::WaitingMoreChunks) {
+            self.send_event
+                .call(SessionEvent::Vad {
+                    kind: event.name(),
+                    noise_floor: self.vad.noise_floor(),
+                })
+                .await;
+        }
 
-        match self.vad.process_audio(self.audio_buffer) {
+        match event {
             VadEvent::SpeechStarted => {
-                // TODO handle user interuption
-                // TODO handle speech start UTC for history
                 println!("Event {:?}", VadEvent::SpeechStarted);
+
+                match self.barge_in {
+                    BargeInMode::Off => {}
+                    BargeInMode::Immediate => self.interrupt_agent().await,
+                    BargeInMode::Sustained(_) => self.pending_barge_in = true,
+                }
             }
             VadEvent::SpeechPaused(start, end) => {
                 println!("Event {:?}", VadEvent::SpeechPaused(start, end));
-                // let _ = self
-                //     .stt
-                //     .write_audio_file(
-                //         format!("{}-{}.wav", self.id, Utc::now().to_string()),
-                //         &self.audio_buffer.user[start as usize..end as usize].to_vec(),
-                //     )
-                //     .await;
-
-                self.pool_manager
-                    .start_pipeline(
-                        self.id,
-                        self.stt.clone(),
-                        self.llm.clone(),
-                        self.audio_buffer.user[start as usize..end as usize].to_vec(),
-                        self.send_audio.clone(),
-                        &self.history,
-                    )
-                    .await;
+                self.start_pipeline(start, end).await;
             }
             VadEvent::SpeechResumed => {
                 println!("Event {:?}", VadEvent::SpeechResumed);
                 self.pool_manager.stop_pipeline(&self.id).await;
+                self.transcript = Reactive::new(None);
             }
             VadEvent::SpeechFullStop => {
                 warn!("Event {:?}", VadEvent::SpeechFullStop);
@@ -73,29 +266,170 @@ impl AudioSourceLayer<'_> {
                         self.history.add(entry.clone());
                     }
 
-                    let _ = pipeline.status.set(PipelineStatus::CanSendAudio);
+                    let _ = pipeline.status.set(PipelineStatus::CanSendAudio).await;
                 }
             }
             VadEvent::WaitingMoreChunks => {
                 //println!("Event {:?}", VadEvent::WaitingMoreChunks);
             }
         }
+
+        if let BargeInMode::Sustained(ms) = self.barge_in
+            && self.pending_barge_in
+        {
+            match (self.audio_buffer.start, self.audio_buffer.end) {
+                (Some(start), None) => {
+                    if self.audio_buffer.cursor - start >= SampleRate::PIPELINE.samples(ms) {
+                        self.interrupt_agent().await;
+                    }
+                }
+                (Some(_), Some(_)) => {} // short pause, the caller may go on
+                _ => self.pending_barge_in = false,
+            }
+        }
+    }
+
+    /// Runs the turn heard so far, from `start` to `end`, through a new pipeline.
+    async fn start_pipeline(&mut self, start: Samples, end: Samples) {
+        self.transcript = Reactive::new(None);
+
+        let Some(audio) = self.audio_buffer.user_audio(start..end) else {
+            warn!(
+                "Session {} lost the audio of its turn {}..{}",
+                self.id, start.0, end.0
+            );
+            return;
+        };
+
+        // let _ = self
+        //     .stt
+        //     .write_audio_file(
+        //         format!("{}-{}.wav", self.id, Utc::now().to_string()),
+        //         &audio,
+        //     )
+        //     .await;
+
+        let context = PipelineContext {
+            agent: self.agent.clone(),
+            stt: self.stt.clone(),
+            llm: self.llm.clone(),
+            tts: self.tts.clone(),
+            send_audio: self.send_audio.clone(),
+            send_event: self.send_event.clone(),
+            playback: self.playback.clone(),
+            history: self.history.writer(),
+            tools: self.tools.select(self.agent.tools.as_deref()),
+            spoken_at: self
+                .audio_buffer
+                .timeline
+                .at(start)
+                .unwrap_or_else(Utc::now),
+            transcript: self.transcript.clone(),
+            full_stop_ms: self.full_stop_ms,
+        };
+
+        self.pool_manager
+            .start_pipeline(self.id, context, audio, self.history)
+            .await;
+    }
+
+    /// Whether the current pause ends the turn before the VAD full stop, given
+    /// the speculative transcript of the running pipeline.
+    fn is_turn_over(&self) -> bool {
+        let Some(end) = self.audio_buffer.end else {
+            return false;
+        };
+
+        let transcript = self.transcript.get();
+        let silence_ms = self.turn_detector.silence_ms(transcript.as_deref());
+        self.audio_buffer.cursor - end > SampleRate::PIPELINE.samples(silence_ms)
+    }
+
+    /// Waits for the last answer, then stops whatever is left of the pipeline.
+    /// Until the source ended, the caller stays on the line in silence so the
+    /// turn detector can still release the answer.
+    pub async fn drain_pipeline(&mut self) {
+        let silence = vec![0; SampleRate::PIPELINE.samples(FRAME).as_usize()];
+        let started = Instant::now();
+
+        while self.pool_manager.is_running(&self.id).await {
+            if started.elapsed() >= PIPELINE_DRAIN_TIMEOUT {
+                warn!(
+                    "Pipeline still running after {:?}, giving up",
+                    started.elapsed()
+                );
+                break;
+            }
+
+            if !self.ended {
+                self.process(&silence).await;
+            }
+            sleep(FRAME.as_duration()).await;
+        }
+
+        self.pool_manager.stop_pipeline(&self.id).await;
+        self.history.sync();
+    }
+
+    async fn interrupt_agent(&mut self) {
+        self.pending_barge_in = false;
+
+        let is_queued = self.pool_manager.is_running(&self.id).await;
+        if !is_queued && !self.playback.is_playing().await {
+            return;
+        }
+
+        info!("Session {} barge-in, interrupting the agent", self.id);
+        self.pool_manager.stop_pipeline(&self.id).await;
+
+        if let Err(err) = self.clear_audio.call().await {
+            warn!("Session {} could not flush agent audio: {:?}", self.id, err);
+        }
+
+        if let Some(interruption) = self.playback.interrupt().await {
+            self.history
+                .interrupt(interruption.generation, interruption.heard);
+        }
+    }
+}
+
+/// How a session talks back to the caller.
+#[derive(Clone)]
+pub struct SessionCallbacks {
+    pub send_audio: SendAudioCallback,
+    pub clear_audio: ClearAudioCallback,
+    pub send_event: SessionEventCallback,
+}
+
+impl SessionCallbacks {
+    /// Callbacks writing to a connected audio source.
+    pub fn from_source(source: Arc<dyn AudioSource>) -> Self {
+        Self {
+            send_audio: SendAudioCallback::new({
+                let source = source.clone();
+                move |bytes| source.send_audio(bytes)
+            }),
+            clear_audio: ClearAudioCallback::new({
+                let source = source.clone();
+                move || source.clear_audio()
+            }),
+            send_event: SessionEventCallback::new(move |event| source.send_event(event)),
+        }
     }
 }
 
 pub type SendAudioCallbackFnReturn = Pin<Box<dyn Future<Output = Result<(), Error>> + Send>>;
-pub type SendAudioCallbackFn =
-    dyn Fn(&Vec<i16>) -> SendAudioCallbackFnReturn + Send + Sync + 'static;
+pub type SendAudioCallbackFn = dyn Fn(&[i16]) -> SendAudioCallbackFnReturn + Send + Sync + 'static;
 
 #[derive(Clone)]
 pub struct SendAudioCallback {
-    inner: Arc<dyn Fn(&Vec<i16>) -> SendAudioCallbackFnReturn + Send + Sync>,
+    inner: Arc<SendAudioCallbackFn>,
 }
 
 impl SendAudioCallback {
     pub fn new<F, Fut>(f: F) -> Self
     where
-        F: Fn(&Vec<i16>) -> Fut + Send + Sync + 'static,
+        F: Fn(&[i16]) -> Fut + Send + Sync + 'static,
         Fut: Future<Output = Result<(), Error>> + Send + 'static,
     {
         Self {
@@ -103,7 +437,57 @@ impl SendAudioCallback {
         }
     }
 
-    pub fn call(&self, bytes: &Vec<i16>) -> SendAudioCallbackFnReturn {
+    pub fn call(&self, bytes: &[i16]) -> SendAudioCallbackFnReturn {
         (self.inner)(bytes)
     }
 }
+
+pub type ClearAudioCallbackFn = dyn Fn() -> SendAudioCallbackFnReturn + Send + Sync + 'static;
+
+#[derive(Clone)]
+pub struct ClearAudioCallback {
+    inner: Arc<ClearAudioCallbackFn>,
+}
+
+impl ClearAudioCallback {
+    pub fn new<F, Fut>(f: F) -> Self
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), Error>> + Send + 'static,
+    {
+        Self {
+            inner: Arc::new(move || Box::pin(f())),
+        }
+    }
+
+    pub fn call(&self) -> SendAudioCallbackFnReturn {
+        (self.inner)()
+    }
+}
+
+pub type SessionEventCallbackFn =
+    dyn Fn(SessionEvent) -> SendAudioCallbackFnReturn + Send + Sync + 'static;
+
+#[derive(Clone)]
+pub struct SessionEventCallback {
+    inner: Arc<SessionEventCallbackFn>,
+}
+
+impl SessionEventCallback {
+    pub fn new<F, Fut>(f: F) -> Self
+    where
+        F: Fn(SessionEvent) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), Error>> + Send + 'static,
+    {
+        Self {
+            inner: Arc::new(move |event| Box::pin(f(event))),
+        }
+    }
+
+    /// Session events are diagnostics, losing one must never break the call.
+    pub async fn call(&self, event: SessionEvent) {
+        if let Err(err) = (self.inner)(event).await {
+            debug!("Session event dropped: {:?}", err);
+        }
+    }
+}
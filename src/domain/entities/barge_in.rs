@@ -0,0 +1,14 @@
+use serde::Deserialize;
+
+use crate::domain::utils::units::Millis;
+
+/// Written `"off"`, `"immediate"` or `{"sustained": 300}` in agent profiles.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BargeInMode {
+    Off,
+    #[default]
+    Immediate,
+    /// Interrupt the agent only once the caller has been speaking for the given duration.
+    Sustained(Millis),
+}
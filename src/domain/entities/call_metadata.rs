@@ -0,0 +1,23 @@
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Default)]
+pub struct CallMetadata {
+    pub call_sid: Option<String>,
+    pub account_sid: Option<String>,
+    pub stream_sid: Option<String>,
+    pub custom_parameters: HashMap<String, String>,
+    pub media_format: Option<MediaFormat>,
+}
+
+#[derive(Debug, Clone)]
+pub struct MediaFormat {
+    pub encoding: String,
+    pub sample_rate: u32,
+    pub channels: u16,
+}
+
+impl CallMetadata {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
@@ -1,3 +1,4 @@
+#[allow(clippy::module_inception)]
 pub mod history;
 pub mod history_event;
 pub mod history_member;
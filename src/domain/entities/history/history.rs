@@ -1,17 +1,48 @@
 use chrono::{DateTime, Utc};
+use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender, unbounded_channel};
 
 use crate::domain::entities::history::{
     history_event::{HistoryEvent, HistoryEventPayload},
     history_member::HistoryMember,
 };
 
+/// An event sent by a pipeline, with the generation of its reply.
+type Pending = (Option<u64>, HistoryEventPayload);
+
 pub struct History {
     pub events: Vec<HistoryEvent>,
+    sender: UnboundedSender<Pending>,
+    receiver: UnboundedReceiver<Pending>,
+}
+
+/// Lets pipelines append events to a session history they do not own.
+/// Events are applied the next time the session calls [`History::sync`].
+#[derive(Debug, Clone)]
+pub struct HistoryWriter {
+    sender: UnboundedSender<Pending>,
+}
+
+impl HistoryWriter {
+    pub fn add(&self, payload: HistoryEventPayload) {
+        let _ = self.sender.send((None, payload));
+    }
+
+    /// Adds an event of the reply of pipeline `generation`, which a barge-in
+    /// may cut down later.
+    pub fn add_reply(&self, generation: u64, payload: HistoryEventPayload) {
+        let _ = self.sender.send((Some(generation), payload));
+    }
 }
 
 impl History {
     pub fn new() -> Self {
-        History { events: Vec::new() }
+        let (sender, receiver) = unbounded_channel();
+
+        History {
+            events: Vec::new(),
+            sender,
+            receiver,
+        }
     }
 
     pub fn add(&mut self, payload: HistoryEventPayload) {
@@ -19,12 +50,84 @@ impl History {
         self.events.push(event);
     }
 
+    pub fn writer(&self) -> HistoryWriter {
+        HistoryWriter {
+            sender: self.sender.clone(),
+        }
+    }
+
+    pub fn sync(&mut self) {
+        while let Ok((generation, payload)) = self.receiver.try_recv() {
+            let mut event = HistoryEvent::new(payload);
+            event.generation = generation;
+            self.events.push(event);
+        }
+    }
+
+    /// Cuts the reply of pipeline `generation` down to the `heard` words, the
+    /// first ones it spoke. What it said in each tool round is kept apart from
+    /// its answer, so the words are handed out to its agent events in order.
+    pub fn interrupt(&mut self, generation: u64, heard: String) {
+        self.sync();
+
+        let mut heard = heard.split_whitespace();
+        let mut recorded = false;
+        let mut unheard = Vec::new();
+
+        for (index, event) in self.events.iter_mut().enumerate() {
+            if event.generation != Some(generation) || !matches!(event.member, HistoryMember::Agent)
+            {
+                continue;
+            }
+            recorded = true;
+
+            let Some(content) = &event.content else {
+                continue;
+            };
+            let words = content.split_whitespace().count();
+            let kept: Vec<&str> = heard.by_ref().take(words).collect();
+
+            if kept.len() == words {
+                continue;
+            }
+
+            event.content = (!kept.is_empty()).then(|| kept.join(" "));
+            if event.content.is_none() {
+                unheard.push(index);
+            }
+        }
+
+        // an unheard round still holds its tool calls, an unheard answer goes
+        for index in unheard.into_iter().rev() {
+            let holds_tools = self
+                .events
+                .get(index + 1)
+                .is_some_and(|next| matches!(next.member, HistoryMember::ToolCall));
+            if !holds_tools {
+                self.events.remove(index);
+            }
+        }
+
+        // the pipeline was cancelled before it recorded its reply
+        let rest: Vec<&str> = heard.collect();
+        if !recorded && !rest.is_empty() {
+            let mut event = HistoryEvent::new(HistoryEventPayload {
+                member: HistoryMember::Agent,
+                content: Some(rest.join(" ")),
+                created_at: Utc::now(),
+                tool_call: None,
+            });
+            event.generation = Some(generation);
+            self.events.push(event);
+        }
+    }
+
     pub fn create_mark(&self, datetime: DateTime<Utc>) -> Vec<HistoryEvent> {
         let date_text_french = datetime.format("%A %d %B %Y").to_string();
         let date_format_french = datetime.format("%d/%m/%Y").to_string();
         let hour_min = datetime.format("%-Hh%M").to_string();
 
-        let content = vec![
+        let content = [
             String::from("Voici des informations supplémentaires qui pourraient t'aider à répondre au client :"),
             format!("- Aujourd'hui, nous sommes le : {}", date_text_french),
             format!(
@@ -39,6 +142,7 @@ impl History {
             member: HistoryMember::System,
             content: Some(content),
             created_at: datetime,
+            tool_call: None,
         });
 
         let mut events = Vec::<HistoryEvent>::with_capacity(self.events.len() + 1);
@@ -48,3 +152,9 @@ impl History {
         events
     }
 }
+
+impl Default for History {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -7,7 +7,10 @@ pub struct HistoryEvent {
     pub member: HistoryMember,
     pub content: Option<String>,
     pub created_at: DateTime<Utc>,
+    pub tool_call: Option<ToolCallRecord>,
     pub is_saved: bool,
+    /// Pipeline generation whose reply recorded the event, if any.
+    pub generation: Option<u64>,
 }
 
 #[derive(Debug, Clone)]
@@ -15,6 +18,16 @@ pub struct HistoryEventPayload {
     pub member: HistoryMember,
     pub content: Option<String>,
     pub created_at: DateTime<Utc>,
+    pub tool_call: Option<ToolCallRecord>,
+}
+
+/// A tool the agent ran during its turn, with the raw JSON exchanged.
+#[derive(Debug, Clone)]
+pub struct ToolCallRecord {
+    pub id: String,
+    pub name: String,
+    pub arguments: String,
+    pub result: String,
 }
 
 impl HistoryEvent {
@@ -28,7 +41,9 @@ impl HistoryEvent {
             member: payload.member,
             content: payload.content,
             created_at: payload.created_at,
+            tool_call: payload.tool_call,
             is_saved,
+            generation: None,
         }
     }
 }
@@ -16,8 +16,8 @@ pub struct Job {
     pub data: Vec<i16>,
 }
 
-impl Job {
-    pub fn default() -> Self {
+impl Default for Job {
+    fn default() -> Self {
         Job {
             id: Utils::generate_uuid(),
             state: JobState::Pending,
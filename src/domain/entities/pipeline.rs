@@ -1,3 +1,4 @@
+#[allow(clippy::module_inception)]
 pub mod pipeline;
 pub mod pool;
 pub mod pool_manager;
@@ -1,35 +1,86 @@
 use anywho::Error;
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use tokio_util::sync::CancellationToken;
+use tracing::debug;
 use uuid::Uuid;
 
 use crate::{
-    application::{llm::LlmList, stt::SttList},
+    application::{llm::LlmList, stt::SttList, tts::TtsList},
     domain::{
         entities::{
-            audio_source_layer::SendAudioCallback,
+            agent_profile::AgentProfile,
+            audio_source_layer::{SendAudioCallback, SessionEventCallback},
             history::{
-                history_event::{HistoryEvent, HistoryEventPayload},
+                history::HistoryWriter,
+                history_event::{HistoryEvent, HistoryEventPayload, ToolCallRecord},
                 history_member::HistoryMember,
             },
+            playback::Playback,
+            tool_registry::ToolRegistry,
         },
         ports::{
-            llm::Llm,
+            audio_source::SessionEvent,
+            llm::{Llm, LlmProcessResponse, LlmRequest, LlmStreamEvent, LlmToolCall},
             stt::{Stt, SttPayload},
+            tts::Tts,
         },
-        utils::reactive::Reactive,
+        utils::{reactive::Reactive, segmenter::SentenceSegmenter, units::Millis},
     },
 };
 
+/// How many times the model may chain tool calls before it has to answer.
+const MAX_TOOL_ROUNDS: usize = 4;
+
+/// Extra wait for the release of the answer past the session's full stop.
+const RELEASE_MARGIN: Duration = Duration::from_secs(5);
+
+/// Session services a pipeline needs to turn a user turn into agent audio.
+#[derive(Clone)]
+pub struct PipelineContext {
+    pub agent: AgentProfile,
+    pub stt: SttList,
+    pub llm: LlmList,
+    pub tts: TtsList,
+    pub send_audio: SendAudioCallback,
+    pub send_event: SessionEventCallback,
+    pub playback: Playback,
+    pub history: HistoryWriter,
+    pub tools: ToolRegistry,
+    /// When the caller started the turn, the date of its history entry.
+    pub spoken_at: DateTime<Utc>,
+    /// Where the speculative transcript is published for the turn detector.
+    pub transcript: Reactive<Option<String>>,
+    /// Longest silence before the session releases the answer.
+    pub full_stop_ms: Millis,
+}
+
+/// What the agent did during its turn, recorded once the turn is over.
+#[derive(Debug, Clone, Default)]
+pub struct PipelineReply {
+    /// Each round that asked for tools is an agent event, holding what was
+    /// said during it, followed by one event per tool call.
+    pub tool_rounds: Vec<HistoryEventPayload>,
+    pub text: Option<String>,
+}
+
 #[derive(Clone)]
 pub struct Pipeline {
     pub id: Uuid,
     pub generation: u64,
+    pub agent: AgentProfile,
     pub stt: SttList,
     pub llm: LlmList,
+    pub tts: TtsList,
     pub cancellation_token: CancellationToken,
     pub send_audio: SendAudioCallback,
+    pub send_event: SessionEventCallback,
+    pub playback: Playback,
+    pub history: HistoryWriter,
+    pub tools: ToolRegistry,
+    pub spoken_at: DateTime<Utc>,
     pub status: Reactive<PipelineStatus>,
+    pub transcript: Reactive<Option<String>>,
+    pub full_stop_ms: Millis,
     pub transcripted: Arc<Mutex<Vec<HistoryEventPayload>>>,
 }
 
@@ -37,54 +88,214 @@ impl Pipeline {
     pub fn new(
         id: Uuid,
         generation: u64,
-        stt: SttList,
-        llm: LlmList,
+        context: PipelineContext,
         cancellation_token: CancellationToken,
-        send_audio: SendAudioCallback,
     ) -> Self {
         Pipeline {
             id,
             generation,
-            stt,
-            llm,
+            agent: context.agent,
+            stt: context.stt,
+            llm: context.llm,
+            tts: context.tts,
             cancellation_token,
-            send_audio,
+            send_audio: context.send_audio,
+            send_event: context.send_event,
+            playback: context.playback,
+            history: context.history,
+            tools: context.tools,
+            spoken_at: context.spoken_at,
             status: Reactive::new(PipelineStatus::Pending),
+            transcript: context.transcript,
+            full_stop_ms: context.full_stop_ms,
             transcripted: Arc::new(Mutex::new(Vec::new())),
         }
     }
 
-    pub async fn execute_stt(&mut self, bytes: &Vec<i16>) -> Result<SttPayload, Error> {
-        let result = self.stt.execute(bytes).await?;
+    pub async fn execute_stt(&mut self, bytes: &[i16]) -> Result<SttPayload, Error> {
+        let result = self.stt.execute(bytes, &self.agent.stt_language).await?;
+
+        if let Some(text) = &result.text {
+            self.send_event
+                .call(SessionEvent::Transcript {
+                    member: HistoryMember::User,
+                    text: text.clone(),
+                })
+                .await;
+        }
+
+        let _ = self.transcript.set(result.text.clone()).await;
 
         let mut transcripted = self.transcripted.lock().await;
         transcripted.push(HistoryEventPayload {
             member: HistoryMember::User,
             content: result.text.clone(),
-            created_at: Utc::now(),
+            created_at: self.spoken_at,
+            tool_call: None,
         });
 
         Ok(result)
     }
 
-    pub async fn execute_llm(&mut self, history_event: Vec<HistoryEvent>) -> Result<(), Error> {
-        let _llm_result = self
-            .llm
-            .process("gemini-2.0-flash".to_string(), history_event)
-            .await;
+    pub async fn execute_llm(
+        &mut self,
+        history_event: Vec<HistoryEvent>,
+    ) -> Result<LlmProcessResponse, Error> {
+        let request = self.build_llm_request(history_event, true);
+        self.llm.process(request).await
+    }
+
+    pub async fn execute_tts(&self, text: &str) -> Result<Vec<i16>, Error> {
+        self.tts
+            .synthesize(text, self.agent.tts_voice.as_deref())
+            .await
+    }
+
+    /// Streams the LLM reply into TTS clause by clause and sends each clause as soon
+    /// as it is rendered. Tool calls requested by the model are run and fed back
+    /// until it produces a spoken answer.
+    pub async fn execute_streamed_reply(
+        &mut self,
+        mut history_events: Vec<HistoryEvent>,
+    ) -> Result<PipelineReply, Error> {
+        let mut reply = PipelineReply::default();
+
+        for round in 0..=MAX_TOOL_ROUNDS {
+            // last round without tools so the model has to answer
+            let request = self.build_llm_request(history_events.clone(), round < MAX_TOOL_ROUNDS);
+            let (clauses, tool_calls) = self.execute_streamed_round(request).await?;
+            let text = (!clauses.is_empty()).then(|| clauses.join(" "));
+
+            if tool_calls.is_empty() {
+                reply.text = text;
+                break;
+            }
+
+            // the model must see what it already said before the tool results,
+            // or it repeats itself in the next round
+            let mut round_events = vec![HistoryEventPayload {
+                member: HistoryMember::Agent,
+                content: text,
+                created_at: Utc::now(),
+                tool_call: None,
+            }];
+            for call in tool_calls {
+                round_events.push(self.execute_tool(call).await);
+            }
+
+            history_events.extend(round_events.iter().cloned().map(HistoryEvent::new));
+            reply.tool_rounds.extend(round_events);
+        }
+
+        Ok(reply)
+    }
+
+    async fn execute_streamed_round(
+        &mut self,
+        request: LlmRequest,
+    ) -> Result<(Vec<String>, Vec<LlmToolCall>), Error> {
+        let mut stream = self.llm.process_stream(request).await?;
+
+        let (clause_tx, mut clause_rx) = unbounded_channel::<String>();
+
+        let produce = async move {
+            let mut segmenter = SentenceSegmenter::new();
+            let mut tool_calls = Vec::new();
+
+            while let Some(event) = stream.next().await {
+                match event? {
+                    LlmStreamEvent::Delta(delta) => {
+                        for clause in segmenter.push(&delta) {
+                            let _ = clause_tx.send(clause);
+                        }
+                    }
+                    LlmStreamEvent::Finished(response) => tool_calls = response.tool_calls,
+                }
+            }
+
+            if let Some(clause) = segmenter.flush() {
+                let _ = clause_tx.send(clause);
+            }
+
+            Ok::<Vec<LlmToolCall>, Error>(tool_calls)
+        };
+
+        let speak = async {
+            let mut spoken = Vec::<String>::new();
+
+            while let Some(clause) = clause_rx.recv().await {
+                let audio = self.execute_tts(&clause).await?;
+                self.execute_send_audio(&clause, &audio).await?;
+                spoken.push(clause);
+            }
+
+            Ok::<Vec<String>, Error>(spoken)
+        };
+
+        let (tool_calls, spoken) = try_join!(produce, speak)?;
+
+        Ok((spoken, tool_calls))
+    }
+
+    pub async fn execute_tool(&self, call: LlmToolCall) -> HistoryEventPayload {
+        debug!(
+            "Pipeline {} calls tool {}({})",
+            self.id, call.name, call.arguments
+        );
+        let result = self.tools.call(&call.name, &call.arguments).await;
 
-        Ok(())
+        HistoryEventPayload {
+            member: HistoryMember::ToolCall,
+            content: None,
+            created_at: Utc::now(),
+            tool_call: Some(ToolCallRecord {
+                id: call.id,
+                name: call.name,
+                arguments: call.arguments,
+                result: result.to_string(),
+            }),
+        }
     }
 
-    pub async fn execute_tts(&self) -> Result<(), Error> {
-        call_future().await.map(|_| ())
+    fn build_llm_request(&self, history_events: Vec<HistoryEvent>, with_tools: bool) -> LlmRequest {
+        let mut events = Vec::<HistoryEvent>::with_capacity(history_events.len() + 1);
+        if let Some(system_prompt) = &self.agent.system_prompt {
+            events.push(HistoryEvent::new(HistoryEventPayload {
+                member: HistoryMember::System,
+                content: Some(system_prompt.clone()),
+                created_at: Utc::now(),
+                tool_call: None,
+            }));
+        }
+        events.extend(history_events);
+
+        LlmRequest {
+            model: self.agent.model.clone(),
+            sampling: self.agent.sampling,
+            history_events: events,
+            tools: match with_tools {
+                true => self.tools.definitions(),
+                false => Vec::new(),
+            },
+        }
     }
 
-    pub async fn execute_send_audio(&mut self, bytes: &Vec<i16>) -> Result<(), Error> {
-        let result = timeout(Duration::from_secs(5), async {
+    pub async fn execute_send_audio(&mut self, text: &str, bytes: &[i16]) -> Result<(), Error> {
+        // the answer is held until the caller's silence reaches the full stop
+        let wait = self.full_stop_ms.as_duration() + RELEASE_MARGIN;
+        let result = timeout(wait, async {
             loop {
                 if self.status.get() == PipelineStatus::CanSendAudio {
-                    return self.send_audio.call(&bytes).await;
+                    self.send_audio.call(bytes).await?;
+                    self.playback.push(self.generation, text, bytes.len()).await;
+                    self.send_event
+                        .call(SessionEvent::Transcript {
+                            member: HistoryMember::Agent,
+                            text: text.to_string(),
+                        })
+                        .await;
+
+                    return Ok(());
                 }
 
                 self.status.changed().await?
@@ -97,19 +308,35 @@ impl Pipeline {
             Err(_) => Err(Error::msg("timeout waiting for CanSendAudio")),
         }
     }
+
+    /// Records the agent turn once its audio has been handed to the caller.
+    pub fn commit_reply(&self, reply: PipelineReply) {
+        for event in reply.tool_rounds {
+            self.history.add_reply(self.generation, event);
+        }
+
+        if let Some(text) = reply.text {
+            self.history.add_reply(
+                self.generation,
+                HistoryEventPayload {
+                    member: HistoryMember::Agent,
+                    content: Some(text),
+                    created_at: Utc::now(),
+                    tool_call: None,
+                },
+            );
+        }
+    }
 }
 
+use futures::StreamExt;
 use std::{sync::Arc, time::Duration};
 use tokio::{
-    sync::Mutex,
-    time::{sleep, timeout},
+    sync::{Mutex, mpsc::unbounded_channel},
+    time::timeout,
+    try_join,
 };
 
-async fn call_future() -> Result<String, Error> {
-    sleep(Duration::from_millis(150)).await;
-    Ok("CALL_SERVICE_X".to_string())
-}
-
 #[derive(Clone, PartialEq)]
 pub enum PipelineStatus {
     Pending,
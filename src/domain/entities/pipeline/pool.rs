@@ -19,6 +19,12 @@ pub struct Pool {
     pub jobs: HashMap<Uuid, Job>,
 }
 
+impl Default for Pool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Pool {
     pub fn new() -> Self {
         Self {
@@ -50,7 +56,7 @@ impl Pool {
 
         spawn(async move {
             while let Some(job) = rx.recv().await {
-                let _ = stt.execute(&job.data).await;
+                let _ = stt.execute(&job.data, "fra").await;
                 let _ = stt
                     .write_audio_file(format!("job_{}.wav", job.id), &job.data)
                     .await;
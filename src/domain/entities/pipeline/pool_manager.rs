@@ -6,7 +6,6 @@ use std::{
     },
 };
 
-use chrono::Utc;
 use tokio::{
     select, spawn,
     sync::{Mutex, Semaphore},
@@ -15,17 +14,13 @@ use tokio_util::sync::CancellationToken;
 use tracing::{debug, error};
 use uuid::Uuid;
 
-use crate::{
-    application::{llm::LlmList, stt::SttList},
-    domain::entities::{
-        audio_source_layer::SendAudioCallback,
-        history::{
-            history::History,
-            history_event::{HistoryEvent, HistoryEventPayload},
-            history_member::HistoryMember,
-        },
-        pipeline::pipeline::Pipeline,
+use crate::domain::entities::{
+    history::{
+        history::History,
+        history_event::{HistoryEvent, HistoryEventPayload},
+        history_member::HistoryMember,
     },
+    pipeline::pipeline::{Pipeline, PipelineContext},
 };
 
 #[derive(Clone)]
@@ -47,10 +42,8 @@ impl PoolManager {
     pub async fn start_pipeline(
         &self,
         id: Uuid,
-        stt: SttList,
-        llm: LlmList,
+        context: PipelineContext,
         bytes: Vec<i16>,
-        send_audio: SendAudioCallback,
         history: &History,
     ) {
         let generation = self.gen_counter.fetch_add(1, Ordering::SeqCst) + 1;
@@ -66,94 +59,66 @@ impl PoolManager {
         let semaphore = Arc::clone(&self.semaphore);
         let pipelines_map = Arc::clone(&self.pipelines);
 
-        let pipeline = Pipeline::new(
-            id,
-            generation,
-            stt.clone(),
-            llm.clone(),
-            cancellation_token.clone(),
-            send_audio.clone(),
-        );
+        let pipeline = Pipeline::new(id, generation, context, cancellation_token.clone());
 
         let mut pipeline_clone = pipeline.clone();
         let mut history_events = history.events.clone();
 
+        {
+            let mut map = self.pipelines.lock().await;
+            map.insert(id, pipeline);
+        }
+
         spawn(async move {
             let permit = semaphore.acquire_owned().await.expect("Semaphore closed");
 
-            let _stt_response = select! {
-                _ = cancellation_token.cancelled() => {
-                    debug!("Pipeline {} cancelled before STT", id);
-                    return ;
-                }
-
-                result = pipeline_clone.execute_stt(&bytes) => {
-                    match result {
-                        Ok(payload) => {
-                            debug!("Pipeline {} STT OK", id);
-                            let event = HistoryEventPayload {
-                                member: HistoryMember::User,
-                                content: payload.text.clone(),
-                                created_at: Utc::now(),
-                            };
-
-                            history_events.push(HistoryEvent::new(event));
-                            Ok(payload)
-                        }
-                        Err(e) => {
-                            error!("Pipeline {} STT failed: {:?}", id, e);
-                            Err(e)
-                        }
+            'stages: {
+                let stt_result = select! {
+                    _ = cancellation_token.cancelled() => {
+                        debug!("Pipeline {} cancelled before STT", id);
+                        return;
                     }
-                }
-            };
 
-            // let _ = pipeline_clone
-            //     .stt
-            //     .write_audio_file(format!("{}.wav", id), &bytes)
-            //     .await;
+                    result = pipeline_clone.execute_stt(&bytes) => result
+                };
 
-            let _llm_result = select! {
-                _ = cancellation_token.cancelled() => {
-                    debug!("Pipeline {} cancelled before LLM call", id);
-                    return;
-                }
+                match stt_result {
+                    Ok(payload) => {
+                        debug!("Pipeline {} STT OK", id);
+                        let event = HistoryEventPayload {
+                            member: HistoryMember::User,
+                            content: payload.text.clone(),
+                            created_at: pipeline_clone.spoken_at,
+                            tool_call: None,
+                        };
 
-                llm_res = pipeline_clone.execute_llm(history_events) => {
-                    match llm_res {
-                        Ok(_) => {
-                            debug!("LLM success for pipeline ({})", id);
-                            Ok(())
-                        },
-                        Err(e) => {
-                            error!("LLM result for pipeline ({}) failed: {:?}", id, e);
-                            Err(e)
-                        }
+                        history_events.push(HistoryEvent::new(event));
+                    }
+                    Err(e) => {
+                        error!("Pipeline {} STT failed: {:?}", id, e);
+                        break 'stages;
                     }
                 }
-            };
 
-            let _tts_result = select! {
-                _ = cancellation_token.cancelled() => {
-                    debug!("Pipeline {} cancelled before TTS call", id);
-                    return
-                }
+                let reply_result = select! {
+                    _ = cancellation_token.cancelled() => {
+                        debug!("Pipeline {} cancelled while streaming the reply", id);
+                        return;
+                    }
+
+                    reply_res = pipeline_clone.execute_streamed_reply(history_events) => reply_res
+                };
 
-                llm_res = pipeline_clone.execute_tts() => {
-                    match llm_res {
-                        Ok(_) => {
-                            debug!("LLM success for pipeline ({})", id);
-                            Ok(())
-                        },
-                        Err(e) => {
-                            error!("LLM result for pipeline ({}) failed: {:?}", id, e);
-                            Err(e)
+                match reply_result {
+                    Ok(reply) => {
+                        if reply.text.is_none() {
+                            debug!("Pipeline ({}) produced no spoken reply", id);
                         }
+                        pipeline_clone.commit_reply(reply);
                     }
+                    Err(e) => error!("Reply for pipeline ({}) failed: {:?}", id, e),
                 }
-            };
-
-            let _ = pipeline_clone.execute_send_audio(&Vec::new()).await;
+            }
 
             debug!(
                 "Pipeline {} gen={} finished; releasing permit",
@@ -161,17 +126,18 @@ impl PoolManager {
             );
 
             let mut map = pipelines_map.lock().await;
-            if let Some(entry) = map.get(&id) {
-                if entry.generation == generation {
-                    map.remove(&id);
-                }
+            if let Some(entry) = map.get(&id)
+                && entry.generation == generation
+            {
+                map.remove(&id);
             }
 
             drop(permit);
         });
+    }
 
-        let mut map = self.pipelines.lock().await;
-        map.insert(id, pipeline.clone());
+    pub async fn is_running(&self, id: &Uuid) -> bool {
+        self.pipelines.lock().await.contains_key(id)
     }
 
     pub async fn stop_pipeline(&self, id: &Uuid) {
@@ -0,0 +1,129 @@
+use std::{collections::VecDeque, sync::Arc};
+
+use tokio::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use crate::domain::utils::units::{SampleRate, Samples};
+
+#[derive(Debug, Clone)]
+struct PlaybackSegment {
+    generation: u64,
+    text: String,
+    start: Instant,
+    duration: Duration,
+}
+
+impl PlaybackSegment {
+    fn end(&self) -> Instant {
+        self.start + self.duration
+    }
+
+    fn heard_text(&self, now: Instant) -> Option<String> {
+        if now >= self.end() {
+            return Some(self.text.clone());
+        }
+
+        if now <= self.start || self.duration.is_zero() {
+            return None;
+        }
+
+        let ratio = (now - self.start).as_secs_f64() / self.duration.as_secs_f64();
+        let budget = (self.text.chars().count() as f64 * ratio) as usize;
+
+        let mut heard = Vec::new();
+        let mut consumed = 0;
+        for word in self.text.split_whitespace() {
+            consumed += word.chars().count() + 1;
+            if consumed > budget {
+                break;
+            }
+            heard.push(word);
+        }
+
+        match heard.is_empty() {
+            true => None,
+            false => Some(heard.join(" ")),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Interruption {
+    /// Pipeline generation of the reply that was cut.
+    pub generation: u64,
+    /// The words of the reply that were played before the caller cut in.
+    pub heard: String,
+}
+
+/// Keeps track of the agent audio handed to the audio source, so we know
+/// whether the caller is still listening to it and how much they heard.
+#[derive(Debug, Clone)]
+pub struct Playback {
+    segments: Arc<Mutex<VecDeque<PlaybackSegment>>>,
+}
+
+impl Playback {
+    pub fn new() -> Self {
+        Self {
+            segments: Arc::new(Mutex::new(VecDeque::new())),
+        }
+    }
+
+    pub async fn push(&self, generation: u64, text: &str, samples: usize) {
+        let now = Instant::now();
+        let mut segments = self.segments.lock().await;
+
+        // only the reply being played matters, previous ones are forgotten
+        segments.retain(|segment| segment.generation == generation || segment.end() > now);
+
+        let start = segments
+            .back()
+            .map(|segment| segment.end().max(now))
+            .unwrap_or(now);
+
+        segments.push_back(PlaybackSegment {
+            generation,
+            text: text.to_string(),
+            start,
+            duration: SampleRate::PIPELINE.duration(Samples(samples as u64)),
+        });
+    }
+
+    pub async fn is_playing(&self) -> bool {
+        let now = Instant::now();
+        let segments = self.segments.lock().await;
+        segments.back().is_some_and(|segment| segment.end() > now)
+    }
+
+    pub async fn interrupt(&self) -> Option<Interruption> {
+        let now = Instant::now();
+        let mut segments = self.segments.lock().await;
+
+        if segments.back().is_none_or(|segment| segment.end() <= now) {
+            segments.clear();
+            return None;
+        }
+
+        let generation = segments.back().map(|segment| segment.generation)?;
+        segments.retain(|segment| segment.generation == generation);
+
+        let heard = segments
+            .iter()
+            .filter_map(|segment| segment.heard_text(now))
+            .filter(|text| !text.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        segments.clear();
+
+        Some(Interruption { generation, heard })
+    }
+}
+
+impl Default for Playback {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,81 @@
+use chrono::{DateTime, TimeDelta, Utc};
+
+use crate::domain::utils::units::{Millis, SampleRate, Samples};
+
+/// Lateness of a chunk on its own clock that is network jitter, not a stall.
+const MAX_JITTER: Millis = Millis(500);
+
+/// Wall-clock time of the caller audio of a session, from the sample offsets
+/// of `AudioBuffer::user`.
+///
+/// The audio is its own clock: a sample is dated from its anchor plus its
+/// offset at the sample rate, which stays exact however irregularly chunks come
+/// off the network. When the stream stalls (a muted tab, a frozen carrier leg)
+/// the audio falls behind the wall clock, the timeline re-anchors on the
+/// arrival of the next chunk and reports the gap.
+#[derive(Debug, Clone)]
+pub struct Timeline {
+    rate: SampleRate,
+    /// Offsets the audio clock was set against the wall clock at, in order.
+    anchors: Vec<(Samples, DateTime<Utc>)>,
+}
+
+impl Timeline {
+    pub fn new(rate: SampleRate) -> Self {
+        Self {
+            rate,
+            anchors: Vec::new(),
+        }
+    }
+
+    /// Dates the chunk of `len` samples at `offset` that arrived at `at`,
+    /// returning the samples missing before it when the stream stalled.
+    pub fn record(&mut self, offset: Samples, len: Samples, at: DateTime<Utc>) -> Samples {
+        let start = at - delta(self.rate, len);
+        let Some(expected) = self.at(offset) else {
+            self.anchors.push((offset, start));
+            return Samples::ZERO;
+        };
+
+        let late = start - expected;
+        if late <= TimeDelta::milliseconds(MAX_JITTER.0 as i64) {
+            return Samples::ZERO;
+        }
+
+        self.anchors.push((offset, start));
+        self.rate.samples_in(late.to_std().unwrap_or_default())
+    }
+
+    /// When the first sample was heard, `None` before any audio.
+    pub fn origin(&self) -> Option<DateTime<Utc>> {
+        self.anchors.first().map(|(_, time)| *time)
+    }
+
+    /// When the sample at `offset` was heard, `None` before any audio.
+    pub fn at(&self, offset: Samples) -> Option<DateTime<Utc>> {
+        let index = self
+            .anchors
+            .partition_point(|(anchor, _)| *anchor <= offset)
+            .max(1);
+        let (anchor, time) = self.anchors.get(index - 1)?;
+
+        Some(match offset >= *anchor {
+            true => *time + delta(self.rate, offset - *anchor),
+            false => *time - delta(self.rate, *anchor - offset),
+        })
+    }
+
+    pub fn rate(&self) -> SampleRate {
+        self.rate
+    }
+}
+
+impl Default for Timeline {
+    fn default() -> Self {
+        Self::new(SampleRate::PIPELINE)
+    }
+}
+
+fn delta(rate: SampleRate, samples: Samples) -> TimeDelta {
+    TimeDelta::from_std(rate.duration(samples)).unwrap_or(TimeDelta::MAX)
+}
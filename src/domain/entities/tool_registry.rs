@@ -0,0 +1,92 @@
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use anywho::Error;
+use serde_json::{Value, json};
+use tokio::time::timeout;
+
+use crate::domain::ports::{llm::ToolDefinition, tool::Tool};
+
+#[derive(Clone)]
+pub struct ToolRegistry {
+    tools: HashMap<String, Arc<dyn Tool>>,
+    timeout: Duration,
+}
+
+impl ToolRegistry {
+    pub fn new(timeout: Duration) -> Self {
+        Self {
+            tools: HashMap::new(),
+            timeout,
+        }
+    }
+
+    pub fn register(&mut self, tool: Arc<dyn Tool>) {
+        self.tools.insert(tool.name().to_string(), tool);
+    }
+
+    /// Keeps only the named tools, or all of them when no list is given.
+    pub fn select(&self, names: Option<&[String]>) -> Self {
+        let Some(names) = names else {
+            return self.clone();
+        };
+
+        Self {
+            tools: self
+                .tools
+                .iter()
+                .filter(|(name, _)| names.contains(name))
+                .map(|(name, tool)| (name.clone(), Arc::clone(tool)))
+                .collect(),
+            timeout: self.timeout,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tools.is_empty()
+    }
+
+    pub fn definitions(&self) -> Vec<ToolDefinition> {
+        let mut definitions: Vec<ToolDefinition> = self
+            .tools
+            .values()
+            .map(|tool| ToolDefinition {
+                name: tool.name().to_string(),
+                description: tool.description().to_string(),
+                parameters: tool.parameters(),
+            })
+            .collect();
+
+        definitions.sort_by(|a, b| a.name.cmp(&b.name));
+        definitions
+    }
+
+    /// Runs a tool requested by the model. Failures are turned into a JSON error
+    /// so the model can recover and tell the caller.
+    pub async fn call(&self, name: &str, arguments: &str) -> Value {
+        match self.try_call(name, arguments).await {
+            Ok(value) => value,
+            Err(err) => json!({ "error": err.to_string() }),
+        }
+    }
+
+    async fn try_call(&self, name: &str, arguments: &str) -> Result<Value, Error> {
+        let Some(tool) = self.tools.get(name) else {
+            return Err(Error::msg(format!("Unknown tool {}", name)));
+        };
+
+        let arguments = match arguments.trim().is_empty() {
+            true => json!({}),
+            false => serde_json::from_str::<Value>(arguments)?,
+        };
+
+        timeout(self.timeout, tool.call(arguments))
+            .await
+            .map_err(|_| Error::msg(format!("Tool {} timed out", name)))?
+    }
+}
+
+impl Default for ToolRegistry {
+    fn default() -> Self {
+        Self::new(Duration::from_secs(10))
+    }
+}
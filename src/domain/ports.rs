@@ -1,4 +1,8 @@
+pub mod audio_sink;
 pub mod audio_source;
 pub mod llm;
 pub mod stt;
+pub mod tool;
+pub mod tts;
+pub mod turn_detector;
 pub mod vad;
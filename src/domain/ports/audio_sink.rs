@@ -0,0 +1,5 @@
+/// Receives every caller sample of a session, for consumers that need the
+/// whole call while the session buffer only keeps the active turn.
+pub trait AudioSink: Send + Sync {
+    fn write(&mut self, samples: &[i16]);
+}
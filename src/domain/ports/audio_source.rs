@@ -1,11 +1,16 @@
-use std::{pin::Pin, sync::Arc};
+use std::sync::Arc;
 
 use anywho::Error;
+use futures::future::BoxFuture;
+use tokio::sync::mpsc::Sender;
 use uuid::Uuid;
 
 use crate::{
     application::{http::app_state::AppState, vad::VadList},
-    domain::entities::{audio_buffer::AudioBuffer, audio_source_layer::AudioSourceLayer},
+    domain::entities::{
+        audio_buffer::AudioBuffer, audio_source_layer::AudioSourceLayer,
+        call_metadata::CallMetadata, history::history_member::HistoryMember,
+    },
 };
 
 pub struct AudioSourcePayload<'a> {
@@ -15,10 +20,51 @@ pub struct AudioSourcePayload<'a> {
     pub audio_buffer: AudioBuffer,
 }
 
-pub trait AudioSource: Clone + Send + Sync {
-    fn handle(&self, layer: &mut AudioSourceLayer) -> impl Future<Output = Result<(), Error>>;
-    fn send_audio(
-        &self,
-        bytes: &Vec<i16>,
-    ) -> Pin<Box<dyn Future<Output = Result<(), Error>> + Send>>;
+#[derive(Debug, Clone, PartialEq)]
+pub enum InboundFrame {
+    Text(String),
+    Binary(Vec<u8>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum OutboundFrame {
+    Text(String),
+    Binary(Vec<u8>),
+}
+
+#[derive(Debug, Clone)]
+pub enum AudioSourceEvent {
+    Started(CallMetadata),
+    Stopped,
+    Mark(String),
+    Dtmf(String),
+}
+
+/// What the session tells the client besides audio, for live diagnostics.
+#[derive(Debug, Clone)]
+pub enum SessionEvent {
+    Vad {
+        kind: &'static str,
+        /// Noise estimate of the detector, when it measures one.
+        noise_floor: Option<f32>,
+    },
+    Transcript {
+        member: HistoryMember,
+        text: String,
+    },
+}
+
+pub trait AudioSource: Send + Sync {
+    /// Binds the adapter to a freshly accepted socket.
+    fn connect(&self, outbound: Sender<OutboundFrame>) -> Arc<dyn AudioSource>;
+    fn handle<'a>(
+        &'a self,
+        layer: &'a mut AudioSourceLayer<'_>,
+    ) -> BoxFuture<'a, Result<(), Error>>;
+    fn send_audio(&self, bytes: &[i16]) -> BoxFuture<'static, Result<(), Error>>;
+    fn clear_audio(&self) -> BoxFuture<'static, Result<(), Error>>;
+    /// Protocols without a diagnostics channel simply ignore session events.
+    fn send_event(&self, _event: SessionEvent) -> BoxFuture<'static, Result<(), Error>> {
+        Box::pin(async { Ok(()) })
+    }
 }
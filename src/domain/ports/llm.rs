@@ -1,14 +1,61 @@
+use std::pin::Pin;
+
 use anywho::Error;
+use futures::{Stream, future::BoxFuture};
+use serde_json::Value;
+
+use crate::domain::entities::{
+    agent_profile::SamplingParams, history::history_event::HistoryEvent,
+};
+
+#[derive(Debug, Clone)]
+pub struct LlmRequest {
+    pub model: String,
+    pub sampling: SamplingParams,
+    pub history_events: Vec<HistoryEvent>,
+    pub tools: Vec<ToolDefinition>,
+}
 
-use crate::domain::entities::history::history_event::HistoryEvent;
+#[derive(Debug, Clone)]
+pub struct ToolDefinition {
+    pub name: String,
+    pub description: String,
+    pub parameters: Value,
+}
+
+#[derive(Debug, Clone)]
+pub struct LlmProcessResponse {
+    pub text: Option<String>,
+    pub finish_reason: Option<String>,
+    pub usage: Option<LlmUsage>,
+    pub tool_calls: Vec<LlmToolCall>,
+}
+
+#[derive(Debug, Clone)]
+pub struct LlmUsage {
+    pub prompt_tokens: u32,
+    pub completion_tokens: u32,
+    pub total_tokens: u32,
+}
+
+#[derive(Debug, Clone)]
+pub struct LlmToolCall {
+    pub id: String,
+    pub name: String,
+    pub arguments: String,
+}
+
+#[derive(Debug, Clone)]
+pub enum LlmStreamEvent {
+    /// A piece of the reply text, in generation order.
+    Delta(String),
+    /// The whole reply, sent once the provider closed the stream.
+    Finished(LlmProcessResponse),
+}
 
-#[derive(Clone)]
-pub struct LlmProcessResponse {}
+pub type LlmStream = Pin<Box<dyn Stream<Item = Result<LlmStreamEvent, Error>> + Send>>;
 
 pub trait Llm: Send + Sync + 'static {
-    fn process(
-        &mut self,
-        model: String,
-        history_events: Vec<HistoryEvent>,
-    ) -> impl Future<Output = Result<LlmProcessResponse, Error>>;
+    fn process(&self, request: LlmRequest) -> BoxFuture<'_, Result<LlmProcessResponse, Error>>;
+    fn process_stream(&self, request: LlmRequest) -> BoxFuture<'_, Result<LlmStream, Error>>;
 }
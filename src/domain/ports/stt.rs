@@ -1,4 +1,5 @@
 use anywho::Error;
+use futures::future::BoxFuture;
 
 use serde::{Deserialize, Serialize};
 
@@ -9,11 +10,15 @@ pub struct SttPayload {
     pub language_probability: Option<f32>,
 }
 
-pub trait Stt: Clone + Send + Sync {
-    fn execute(&self, audio: &Vec<i16>) -> impl Future<Output = Result<SttPayload, Error>>;
-    fn write_audio_file(
-        &self,
+pub trait Stt: Send + Sync {
+    fn execute<'a>(
+        &'a self,
+        audio: &'a [i16],
+        language_code: &'a str,
+    ) -> BoxFuture<'a, Result<SttPayload, Error>>;
+    fn write_audio_file<'a>(
+        &'a self,
         filename: String,
-        bytes: &Vec<i16>,
-    ) -> impl Future<Output = Result<(), Error>>;
+        bytes: &'a [i16],
+    ) -> BoxFuture<'a, Result<(), Error>>;
 }
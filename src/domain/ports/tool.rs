@@ -0,0 +1,14 @@
+use std::pin::Pin;
+
+use anywho::Error;
+use serde_json::Value;
+
+pub type ToolFuture = Pin<Box<dyn Future<Output = Result<Value, Error>> + Send>>;
+
+pub trait Tool: Send + Sync {
+    fn name(&self) -> &str;
+    fn description(&self) -> &str;
+    /// JSON schema of the arguments object the model has to produce.
+    fn parameters(&self) -> Value;
+    fn call(&self, arguments: Value) -> ToolFuture;
+}
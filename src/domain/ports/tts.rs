@@ -0,0 +1,12 @@
+use anywho::Error;
+use futures::future::BoxFuture;
+
+pub trait Tts: Send + Sync {
+    /// Renders `text` as 16 kHz mono PCM, with the adapter default voice when
+    /// `voice_id` is not set.
+    fn synthesize<'a>(
+        &'a self,
+        text: &'a str,
+        voice_id: Option<&'a str>,
+    ) -> BoxFuture<'a, Result<Vec<i16>, Error>>;
+}
@@ -0,0 +1,12 @@
+use crate::domain::{entities::agent_profile::VadSettings, utils::units::Millis};
+
+/// Decides when the caller's turn is over, from the pause the VAD measured and
+/// what the caller said so far.
+pub trait TurnDetector: Send + Sync {
+    fn configure(&mut self, settings: &VadSettings);
+    /// Longest pause the detector may wait for, the VAD full stop is pushed back to it.
+    fn max_silence_ms(&self) -> Millis;
+    /// Pause after which the turn is over, given the speculative transcript of
+    /// the turn once the STT returned it.
+    fn silence_ms(&self, transcript: Option<&str>) -> Millis;
+}
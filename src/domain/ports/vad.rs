@@ -1,4 +1,7 @@
-use crate::domain::entities::audio_buffer::AudioBuffer;
+use crate::domain::{
+    entities::{agent_profile::VadSettings, audio_buffer::AudioBuffer},
+    utils::units::Samples,
+};
 
 #[derive(Debug, Clone)]
 pub enum VadState {
@@ -6,16 +9,33 @@ pub enum VadState {
     Speaking,
 }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq, Eq)]
 pub enum VadEvent {
     SpeechStarted,
-    SpeechPaused(u64, u64),
+    SpeechPaused(Samples, Samples),
     SpeechResumed,
     SpeechFullStop,
     WaitingMoreChunks,
 }
 
-pub trait Vad: Clone + Send + Sync {
-    fn process_audio<'a>(&mut self, audio_buffer: &'a mut AudioBuffer) -> VadEvent;
+impl VadEvent {
+    pub fn name(&self) -> &'static str {
+        match self {
+            VadEvent::SpeechStarted => "speech_started",
+            VadEvent::SpeechPaused(_, _) => "speech_paused",
+            VadEvent::SpeechResumed => "speech_resumed",
+            VadEvent::SpeechFullStop => "speech_full_stop",
+            VadEvent::WaitingMoreChunks => "waiting_more_chunks",
+        }
+    }
+}
+
+pub trait Vad: Send + Sync {
+    fn configure(&mut self, settings: &VadSettings);
+    fn process_audio(&mut self, audio_buffer: &mut AudioBuffer) -> VadEvent;
     fn is_speech(&self, bytes: &[i16]) -> bool;
+    /// Current background noise estimate, for detectors that measure it.
+    fn noise_floor(&self) -> Option<f32> {
+        None
+    }
 }
@@ -1,3 +1,29 @@
+use crate::domain::utils::units::{Millis, SampleRate};
+
+pub mod codec;
+pub mod resampler;
+
+/// Operations on PCM samples, whatever format they came in.
 pub struct Audio;
 
-impl Audio {}
+impl Audio {
+    /// Averages the interleaved `channels` of `samples` into one.
+    pub fn downmix(samples: &[i16], channels: usize) -> Vec<i16> {
+        samples
+            .chunks(channels.max(1))
+            .map(|frame| (frame.iter().map(|&s| s as i32).sum::<i32>() / frame.len() as i32) as i16)
+            .collect()
+    }
+
+    /// Surrounds `samples` with `left` and `right` of silence at `rate`.
+    pub fn add_padding(samples: &[i16], rate: SampleRate, left: Millis, right: Millis) -> Vec<i16> {
+        let left = rate.samples(left).as_usize();
+        let right = rate.samples(right).as_usize();
+
+        let mut result = Vec::with_capacity(left + samples.len() + right);
+        result.resize(left, 0);
+        result.extend(samples);
+        result.resize(result.len() + right, 0);
+        result
+    }
+}
@@ -0,0 +1,45 @@
+use std::fmt::Debug;
+
+use anywho::Error;
+
+use crate::domain::utils::{
+    audio::codec::{
+        alaw::ALaw,
+        l16::{L16, L16Le},
+        mulaw::MuLaw,
+        wav::Wav,
+    },
+    units::SampleRate,
+};
+
+pub mod alaw;
+pub mod l16;
+pub mod mulaw;
+pub mod wav;
+
+/// Turns PCM samples into the bytes of a wire or file format and back.
+///
+/// Codecs do not resample: the samples are at the rate of the format, the
+/// audio sources convert them to the pipeline rate.
+pub trait Codec: Debug + Send + Sync {
+    /// MIME type the format is selected by.
+    fn name(&self) -> &'static str;
+    fn encode(&self, samples: &[i16]) -> Result<Vec<u8>, Error>;
+    fn decode(&self, bytes: &[u8]) -> Result<Vec<i16>, Error>;
+}
+
+/// Codec of the format an audio source announced, like the `audio/x-mulaw`
+/// of a Twilio stream. Parameters after a `;` are ignored, `rate` is the one
+/// of the WAV files.
+pub fn by_name(name: &str, rate: SampleRate) -> Option<Box<dyn Codec>> {
+    let name = name.split(';').next().unwrap_or_default().trim();
+
+    match name.to_ascii_lowercase().as_str() {
+        mulaw::NAME | "audio/pcmu" => Some(Box::new(MuLaw)),
+        alaw::NAME | "audio/pcma" => Some(Box::new(ALaw)),
+        l16::NAME => Some(Box::new(L16)),
+        l16::LE_NAME => Some(Box::new(L16Le)),
+        wav::NAME | "audio/x-wav" | "audio/wave" => Some(Box::new(Wav::new(rate))),
+        _ => None,
+    }
+}
@@ -0,0 +1,78 @@
+use anywho::Error;
+
+use crate::domain::utils::audio::codec::Codec;
+
+pub const NAME: &str = "audio/x-alaw";
+
+/// Even bits are inverted on the line, so silence does not read as a run of zeros.
+const EVEN_BITS: u8 = 0x55;
+/// Largest 13 bit magnitude of each segment.
+const SEGMENT_ENDS: [i32; 8] = [0x1F, 0x3F, 0x7F, 0xFF, 0x1FF, 0x3FF, 0x7FF, 0xFFF];
+/// Linear value of every A-law byte.
+const DECODED: [i16; 256] = decoded();
+
+/// G.711 A-law, the 8 bit companding of European and most international phone
+/// lines.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ALaw;
+
+impl ALaw {
+    pub fn encode_sample(sample: i16) -> u8 {
+        // A-law keeps 13 bits, negative values are stored one's complement
+        let value = (sample as i32) >> 3;
+        let (sign, magnitude) = match value >= 0 {
+            true => (0x80, value),
+            false => (0x00, -value - 1),
+        };
+
+        let code = match SEGMENT_ENDS.iter().position(|&end| magnitude <= end) {
+            Some(segment) => {
+                let shift = segment.max(1);
+                ((segment as i32) << 4) | ((magnitude >> shift) & 0x0F)
+            }
+            None => 0x7F,
+        };
+        (sign | code) as u8 ^ EVEN_BITS
+    }
+
+    pub fn decode_sample(byte: u8) -> i16 {
+        DECODED[byte as usize]
+    }
+}
+
+impl Codec for ALaw {
+    fn name(&self) -> &'static str {
+        NAME
+    }
+
+    fn encode(&self, samples: &[i16]) -> Result<Vec<u8>, Error> {
+        Ok(samples.iter().map(|&s| Self::encode_sample(s)).collect())
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<Vec<i16>, Error> {
+        Ok(bytes.iter().map(|&b| Self::decode_sample(b)).collect())
+    }
+}
+
+const fn decoded() -> [i16; 256] {
+    let mut table = [0; 256];
+    let mut byte = 0;
+    while byte < 256 {
+        let alaw = (byte as u8 ^ EVEN_BITS) as i32;
+        let segment = (alaw >> 4) & 0x07;
+        let mantissa = alaw & 0x0F;
+
+        // decoded to the middle of the quantization step
+        let magnitude = match segment {
+            0 => (mantissa << 4) + 8,
+            _ => ((mantissa << 4) + 0x108) << (segment - 1),
+        };
+        table[byte] = if alaw & 0x80 != 0 {
+            magnitude
+        } else {
+            -magnitude
+        } as i16;
+        byte += 1;
+    }
+    table
+}
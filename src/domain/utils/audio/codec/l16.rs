@@ -0,0 +1,58 @@
+use anywho::Error;
+
+use crate::domain::utils::audio::codec::Codec;
+
+pub const NAME: &str = "audio/l16";
+/// Not a registered MIME type, the format of the browser client and the TTS.
+pub const LE_NAME: &str = "audio/x-l16-le";
+
+/// Raw 16 bit PCM in network byte order, as RFC 2586 and RFC 3551 define
+/// `audio/L16`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct L16;
+
+/// Raw 16 bit PCM, little endian like the browser client and the TTS send it.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct L16Le;
+
+impl Codec for L16 {
+    fn name(&self) -> &'static str {
+        NAME
+    }
+
+    fn encode(&self, samples: &[i16]) -> Result<Vec<u8>, Error> {
+        Ok(samples
+            .iter()
+            .flat_map(|sample| sample.to_be_bytes())
+            .collect())
+    }
+
+    /// A trailing odd byte is not a sample and is dropped.
+    fn decode(&self, bytes: &[u8]) -> Result<Vec<i16>, Error> {
+        Ok(bytes
+            .chunks_exact(2)
+            .map(|chunk| i16::from_be_bytes([chunk[0], chunk[1]]))
+            .collect())
+    }
+}
+
+impl Codec for L16Le {
+    fn name(&self) -> &'static str {
+        LE_NAME
+    }
+
+    fn encode(&self, samples: &[i16]) -> Result<Vec<u8>, Error> {
+        Ok(samples
+            .iter()
+            .flat_map(|sample| sample.to_le_bytes())
+            .collect())
+    }
+
+    /// A trailing odd byte is not a sample and is dropped.
+    fn decode(&self, bytes: &[u8]) -> Result<Vec<i16>, Error> {
+        Ok(bytes
+            .chunks_exact(2)
+            .map(|chunk| i16::from_le_bytes([chunk[0], chunk[1]]))
+            .collect())
+    }
+}
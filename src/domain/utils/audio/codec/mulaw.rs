@@ -0,0 +1,79 @@
+use anywho::Error;
+
+use crate::domain::utils::audio::codec::Codec;
+
+pub const NAME: &str = "audio/x-mulaw";
+
+/// Added to the magnitude so every segment starts on a power of two.
+const BIAS: i32 = 0x84;
+/// Largest magnitude that still fits the last segment once biased.
+const CLIP: i32 = 32635;
+
+/// Segment of a biased magnitude, indexed by its bits 7 to 14.
+const SEGMENTS: [u8; 256] = segments();
+/// Linear value of every μ-law byte.
+const DECODED: [i16; 256] = decoded();
+
+/// G.711 μ-law, the 8 bit companding of North American and Japanese phone
+/// lines, and of Twilio media streams.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MuLaw;
+
+impl MuLaw {
+    pub fn encode_sample(sample: i16) -> u8 {
+        let sign = if sample < 0 { 0x80 } else { 0x00 };
+        let magnitude = (sample as i32).abs().min(CLIP) + BIAS;
+
+        let exponent = SEGMENTS[(magnitude >> 7) as usize] as i32;
+        let mantissa = (magnitude >> (exponent + 3)) & 0x0F;
+        !(sign | (exponent << 4) | mantissa) as u8
+    }
+
+    pub fn decode_sample(byte: u8) -> i16 {
+        DECODED[byte as usize]
+    }
+}
+
+impl Codec for MuLaw {
+    fn name(&self) -> &'static str {
+        NAME
+    }
+
+    fn encode(&self, samples: &[i16]) -> Result<Vec<u8>, Error> {
+        Ok(samples.iter().map(|&s| Self::encode_sample(s)).collect())
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<Vec<i16>, Error> {
+        Ok(bytes.iter().map(|&b| Self::decode_sample(b)).collect())
+    }
+}
+
+const fn segments() -> [u8; 256] {
+    let mut table = [0; 256];
+    let mut index = 2;
+    while index < 256 {
+        table[index] = table[index / 2] + 1;
+        index += 1;
+    }
+    table
+}
+
+const fn decoded() -> [i16; 256] {
+    let mut table = [0; 256];
+    let mut byte = 0;
+    while byte < 256 {
+        let ulaw = !(byte as u8) as i32;
+        let exponent = (ulaw >> 4) & 0x07;
+        let mantissa = ulaw & 0x0F;
+
+        // the bias added by the encoder comes off again
+        let magnitude = (((mantissa << 3) + BIAS) << exponent) - BIAS;
+        table[byte] = if ulaw & 0x80 != 0 {
+            -magnitude
+        } else {
+            magnitude
+        } as i16;
+        byte += 1;
+    }
+    table
+}
@@ -0,0 +1,87 @@
+use std::io::Cursor;
+
+use anywho::Error;
+use hound::{SampleFormat, WavReader, WavSpec, WavWriter};
+
+use crate::domain::utils::{
+    audio::{Audio, codec::Codec},
+    units::SampleRate,
+};
+
+pub const NAME: &str = "audio/wav";
+
+/// Mono 16 bit PCM in a WAV container, what the STT uploads and the captures are.
+#[derive(Debug, Clone, Copy)]
+pub struct Wav {
+    rate: SampleRate,
+}
+
+impl Wav {
+    pub fn new(rate: SampleRate) -> Self {
+        Self { rate }
+    }
+
+    pub fn spec(&self) -> WavSpec {
+        WavSpec {
+            channels: 1,
+            sample_rate: self.rate.0,
+            bits_per_sample: 16,
+            sample_format: SampleFormat::Int,
+        }
+    }
+
+    /// Samples of a 16 bit PCM file at whatever rate it was recorded, its
+    /// channels mixed down to one.
+    pub fn read(bytes: &[u8]) -> Result<(SampleRate, Vec<i16>), Error> {
+        let mut reader = WavReader::new(Cursor::new(bytes))?;
+        let spec = reader.spec();
+
+        if spec.sample_format != SampleFormat::Int || spec.bits_per_sample != 16 {
+            return Err(Error::msg(format!(
+                "WAV must be 16 bits PCM, found {:?} {} bits",
+                spec.sample_format, spec.bits_per_sample
+            )));
+        }
+
+        let samples = reader.samples::<i16>().collect::<Result<Vec<i16>, _>>()?;
+        let mono = Audio::downmix(&samples, spec.channels as usize);
+
+        Ok((SampleRate(spec.sample_rate), mono))
+    }
+}
+
+impl Default for Wav {
+    fn default() -> Self {
+        Self::new(SampleRate::PIPELINE)
+    }
+}
+
+impl Codec for Wav {
+    fn name(&self) -> &'static str {
+        NAME
+    }
+
+    fn encode(&self, samples: &[i16]) -> Result<Vec<u8>, Error> {
+        let mut cursor = Cursor::new(Vec::new());
+        let mut writer = WavWriter::new(&mut cursor, self.spec())?;
+        for &sample in samples {
+            writer.write_sample(sample)?;
+        }
+        writer.finalize()?;
+
+        Ok(cursor.into_inner())
+    }
+
+    /// Fails on files recorded at another rate than the codec's, `read` takes any.
+    fn decode(&self, bytes: &[u8]) -> Result<Vec<i16>, Error> {
+        let (rate, samples) = Self::read(bytes)?;
+        if rate != self.rate {
+            return Err(Error::msg(format!(
+                "WAV is sampled at {}, expected {}",
+                rate, self.rate
+            )));
+        }
+
+        Ok(samples)
+    }
+}
@@ -0,0 +1,227 @@
+use std::f64::consts::PI;
+
+use anywho::Error;
+
+use crate::domain::utils::units::SampleRate;
+
+/// Rates the sources and the TTS deliver audio at.
+pub const SUPPORTED_RATES: [SampleRate; 6] = [
+    SampleRate(8_000),
+    SampleRate(16_000),
+    SampleRate(22_050),
+    SampleRate(24_000),
+    SampleRate(44_100),
+    SampleRate(48_000),
+];
+
+/// Half the length of the filter when neither side is slower, in input samples.
+const HALF_TAPS: u64 = 24;
+/// Cutoff of the filter relative to the Nyquist frequency of the slower side,
+/// low enough for the transition band to end just below it.
+const ROLLOFF: f64 = 0.88;
+/// Shape of the Kaiser window, about 80 dB of stopband attenuation.
+const KAISER_BETA: f64 = 8.0;
+
+/// Polyphase windowed-sinc resampler between two of the `SUPPORTED_RATES`.
+///
+/// It is a stream: each chunk continues the previous one, so a call cut in 20 ms
+/// frames resamples exactly like the whole call would, without clicks at the
+/// frame boundaries. Outputs are aligned on their inputs, which costs a latency
+/// of half the filter (3 ms from 8 to 16 kHz): the last samples of a chunk come
+/// out with the next one, or with `flush` at the end of the stream.
+///
+/// After `n` inputs and a flush the stream holds exactly `ceil(n * to / from)`
+/// outputs, so sample offsets never drift from the wall clock.
+#[derive(Debug, Clone)]
+pub struct Resampler {
+    from: SampleRate,
+    to: SampleRate,
+    /// The ratio `to / from` reduced, the stream is upsampled by `up` then
+    /// decimated by `down`.
+    up: u64,
+    down: u64,
+    half: u64,
+    /// `2 * half` coefficients for each of the `up` phases.
+    filters: Vec<f32>,
+    /// Input still needed by the next outputs, `buffer[0]` is input `base`.
+    buffer: Vec<f32>,
+    base: i64,
+    received: u64,
+    produced: u64,
+}
+
+impl Resampler {
+    pub fn new(from: SampleRate, to: SampleRate) -> Result<Self, Error> {
+        for rate in [from, to] {
+            if !SUPPORTED_RATES.contains(&rate) {
+                return Err(Error::msg(format!(
+                    "Cannot resample {}, supported rates are 8, 16, 22.05, 24, 44.1 and 48 kHz",
+                    rate
+                )));
+            }
+        }
+
+        let divisor = gcd(from.hz(), to.hz());
+        let up = to.hz() / divisor;
+        let down = from.hz() / divisor;
+        // decimating narrows the band, the filter gets longer in proportion
+        let half = (HALF_TAPS * down).div_ceil(up).max(HALF_TAPS);
+
+        let mut resampler = Self {
+            from,
+            to,
+            up,
+            down,
+            half,
+            filters: filters(up, down, half),
+            buffer: Vec::new(),
+            base: 0,
+            received: 0,
+            produced: 0,
+        };
+        resampler.reset();
+
+        Ok(resampler)
+    }
+
+    pub fn from(&self) -> SampleRate {
+        self.from
+    }
+
+    pub fn to(&self) -> SampleRate {
+        self.to
+    }
+
+    /// Resamples the next chunk of the stream. The outputs that still need
+    /// input past the chunk are held back for the next one.
+    pub fn process(&mut self, input: &[i16]) -> Vec<i16> {
+        if self.from == self.to {
+            return input.to_vec();
+        }
+
+        self.buffer
+            .extend(input.iter().map(|&sample| sample as f32));
+        self.received += input.len() as u64;
+        self.drain(self.received, u64::MAX)
+    }
+
+    /// Ends the stream, returning the outputs held back, and starts a new one.
+    pub fn flush(&mut self) -> Vec<i16> {
+        if self.from == self.to {
+            return Vec::new();
+        }
+
+        // the input is followed by silence
+        self.buffer
+            .resize(self.buffer.len() + self.half as usize, 0.0);
+        let end = (self.received * self.up).div_ceil(self.down);
+        let output = self.drain(self.received + self.half, end);
+        self.reset();
+
+        output
+    }
+
+    /// Resamples a whole signal at once.
+    pub fn resample(&mut self, input: &[i16]) -> Vec<i16> {
+        let mut output = self.process(input);
+        output.extend(self.flush());
+        output
+    }
+
+    /// Drops the stream under way, like a flush without its output.
+    pub fn reset(&mut self) {
+        // the stream is preceded by silence
+        self.buffer = vec![0.0; self.half as usize - 1];
+        self.base = 1 - self.half as i64;
+        self.received = 0;
+        self.produced = 0;
+    }
+
+    /// Computes the outputs below `end` whose inputs are within the `available` ones.
+    fn drain(&mut self, available: u64, end: u64) -> Vec<i16> {
+        let taps = 2 * self.half as usize;
+        let mut output = Vec::new();
+
+        while self.produced < end {
+            let position = self.produced * self.down;
+            let index = position / self.up;
+            if index + self.half >= available {
+                break;
+            }
+
+            let phase = (position % self.up) as usize;
+            let filter = &self.filters[phase * taps..(phase + 1) * taps];
+            let start = (index as i64 - (self.half as i64 - 1) - self.base) as usize;
+            let value: f32 = self.buffer[start..start + taps]
+                .iter()
+                .zip(filter)
+                .map(|(sample, coefficient)| sample * coefficient)
+                .sum();
+
+            output.push(value.round().clamp(i16::MIN as f32, i16::MAX as f32) as i16);
+            self.produced += 1;
+        }
+
+        let next = (self.produced * self.down / self.up) as i64 - (self.half as i64 - 1);
+        let consumed = (next - self.base).clamp(0, self.buffer.len() as i64);
+        self.buffer.drain(..consumed as usize);
+        self.base += consumed;
+
+        output
+    }
+}
+
+/// Kaiser windowed sinc low pass, sampled at the `up` fractional delays of the
+/// outputs between two inputs. Every phase is normalized to a unity gain.
+fn filters(up: u64, down: u64, half: u64) -> Vec<f32> {
+    let cutoff = ROLLOFF * (up as f64 / down as f64).min(1.0);
+    let norm = bessel_i0(KAISER_BETA);
+    let mut filters = Vec::with_capacity((up * 2 * half) as usize);
+
+    for phase in 0..up {
+        let fraction = phase as f64 / up as f64;
+        let coefficients: Vec<f64> = (0..2 * half)
+            .map(|tap| {
+                let distance = tap as f64 - (half as f64 - 1.0) - fraction;
+                let x = distance / half as f64;
+                let window = match x.abs() <= 1.0 {
+                    true => bessel_i0(KAISER_BETA * (1.0 - x * x).sqrt()) / norm,
+                    false => 0.0,
+                };
+                cutoff * sinc(cutoff * distance) * window
+            })
+            .collect();
+
+        let gain: f64 = coefficients.iter().sum();
+        filters.extend(coefficients.iter().map(|c| (c / gain) as f32));
+    }
+
+    filters
+}
+
+fn sinc(x: f64) -> f64 {
+    match x == 0.0 {
+        true => 1.0,
+        false => (PI * x).sin() / (PI * x),
+    }
+}
+
+/// Modified Bessel function of the first kind and order zero, from its series.
+fn bessel_i0(x: f64) -> f64 {
+    let mut sum = 1.0;
+    let mut term = 1.0;
+    let mut k = 1.0;
+    while term > sum * 1e-12 {
+        term *= (x / (2.0 * k)).powi(2);
+        sum += term;
+        k += 1.0;
+    }
+    sum
+}
+
+fn gcd(a: u64, b: u64) -> u64 {
+    match b {
+        0 => a,
+        _ => gcd(b, a % b),
+    }
+}
@@ -20,7 +20,7 @@ where
     }
 
     pub async fn set(&self, value: T) -> Result<(), Error> {
-        self.tx.send(value).map_err(|err| Error::from(err))
+        self.tx.send(value).map_err(Error::from)
     }
 
     pub fn get(&self) -> T {
@@ -28,6 +28,6 @@ where
     }
 
     pub async fn changed(&mut self) -> Result<(), Error> {
-        self.rx.changed().await.map_err(|err| Error::from(err))
+        self.rx.changed().await.map_err(Error::from)
     }
 }
@@ -0,0 +1,71 @@
+use std::{borrow::Cow, collections::VecDeque, ops::Range};
+
+use crate::domain::utils::units::Samples;
+
+/// Samples of a stream addressed by their absolute offset since its start,
+/// keeping only the most recent ones.
+///
+/// Offsets never move when old samples are dropped, so positions taken before
+/// a drop stay valid as long as the audio they point to is kept.
+#[derive(Debug, Clone, Default)]
+pub struct RingBuffer {
+    samples: VecDeque<i16>,
+    /// Absolute offset of `samples[0]`.
+    offset: Samples,
+}
+
+impl RingBuffer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, samples: &[i16]) {
+        self.samples.extend(samples);
+    }
+
+    /// Offset of the oldest sample still kept.
+    pub fn oldest(&self) -> Samples {
+        self.offset
+    }
+
+    /// Number of samples ever pushed, the offset of the next one.
+    pub fn written(&self) -> Samples {
+        self.offset + Samples(self.samples.len() as u64)
+    }
+
+    /// Number of samples currently kept.
+    pub fn retained(&self) -> usize {
+        self.samples.len()
+    }
+
+    /// Samples of `range`, borrowed unless they wrap around the ring. `None`
+    /// when part of it was dropped or not written yet.
+    pub fn get(&self, range: Range<Samples>) -> Option<Cow<'_, [i16]>> {
+        if range.start < self.offset || range.end > self.written() || range.start > range.end {
+            return None;
+        }
+
+        let start = (range.start - self.offset).as_usize();
+        let end = (range.end - self.offset).as_usize();
+        let (head, tail) = self.samples.as_slices();
+
+        if end <= head.len() {
+            Some(Cow::Borrowed(&head[start..end]))
+        } else if start >= head.len() {
+            Some(Cow::Borrowed(&tail[start - head.len()..end - head.len()]))
+        } else {
+            Some(Cow::Owned(
+                self.samples.range(start..end).copied().collect(),
+            ))
+        }
+    }
+
+    /// Drops every sample before `offset`.
+    pub fn discard_before(&mut self, offset: Samples) {
+        let count = offset
+            .saturating_sub(self.offset)
+            .min(Samples(self.samples.len() as u64));
+        self.samples.drain(..count.as_usize());
+        self.offset += count;
+    }
+}
@@ -0,0 +1,110 @@
+/// Words ending with a dot that do not close a sentence in French.
+const ABBREVIATIONS: [&str; 16] = [
+    "m", "mm", "mme", "mmes", "mlle", "mlles", "dr", "pr", "me", "st", "ste", "av", "bd", "cf",
+    "env", "tél",
+];
+
+/// Closing marks that may sit between a terminator and the following space.
+const CLOSING_MARKS: [char; 4] = ['»', '"', ')', '\''];
+
+/// Splits a streamed LLM reply into clauses that can be spoken on their own.
+///
+/// A clause ends on `.`, `!`, `?`, `…`, `;` or `:` followed by a space, taking
+/// French typography into account (space before `!`/`?`, guillemets, abbreviations
+/// such as `M.` or `Mme.`, decimals like `3.5`). Long clauses are also split on commas
+/// so the first audio frame does not wait for a whole paragraph.
+#[derive(Debug, Clone)]
+pub struct SentenceSegmenter {
+    buffer: String,
+    min_comma_chars: usize,
+}
+
+impl SentenceSegmenter {
+    pub fn new() -> Self {
+        Self {
+            buffer: String::new(),
+            min_comma_chars: 60,
+        }
+    }
+
+    pub fn push(&mut self, delta: &str) -> Vec<String> {
+        self.buffer.push_str(delta);
+
+        let mut clauses = Vec::new();
+        while let Some(end) = self.find_boundary() {
+            let clause: String = self.buffer.drain(..end).collect();
+            let clause = clause.trim();
+            if !clause.is_empty() {
+                clauses.push(clause.to_string());
+            }
+        }
+
+        clauses
+    }
+
+    pub fn flush(&mut self) -> Option<String> {
+        let rest = std::mem::take(&mut self.buffer);
+        let rest = rest.trim();
+
+        match rest.is_empty() {
+            true => None,
+            false => Some(rest.to_string()),
+        }
+    }
+
+    /// Byte offset right after the first complete clause of the buffer.
+    fn find_boundary(&self) -> Option<usize> {
+        let chars: Vec<(usize, char)> = self.buffer.char_indices().collect();
+
+        for (position, &(offset, c)) in chars.iter().enumerate() {
+            let is_terminator = matches!(c, '.' | '!' | '?' | '…' | ';' | ':');
+            let is_comma =
+                c == ',' && self.buffer[..offset].trim().chars().count() >= self.min_comma_chars;
+
+            if !is_terminator && !is_comma {
+                continue;
+            }
+
+            // skip closing quotes or parenthesis glued to the terminator
+            let mut next = position + 1;
+            while next < chars.len() && CLOSING_MARKS.contains(&chars[next].1) {
+                next += 1;
+            }
+
+            // the next character decides, wait for it if it did not arrive yet
+            let &(next_offset, next_char) = chars.get(next)?;
+
+            if !next_char.is_whitespace() {
+                continue;
+            }
+
+            if c == '.' && self.is_abbreviation(offset) {
+                continue;
+            }
+
+            return Some(next_offset);
+        }
+
+        None
+    }
+
+    fn is_abbreviation(&self, dot_offset: usize) -> bool {
+        let word = self.buffer[..dot_offset]
+            .rsplit(|c: char| c.is_whitespace() || c == '(' || c == '«')
+            .next()
+            .unwrap_or_default();
+
+        let mut letters = word.chars();
+        match (letters.next(), letters.next()) {
+            // initials such as "J. Dupont"
+            (Some(first), None) => first.is_uppercase(),
+            _ => ABBREVIATIONS.contains(&word.to_lowercase().as_str()),
+        }
+    }
+}
+
+impl Default for SentenceSegmenter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
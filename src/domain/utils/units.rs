@@ -0,0 +1,176 @@
+use std::{
+    fmt,
+    num::ParseIntError,
+    ops::{Add, AddAssign, Mul, Sub, SubAssign},
+    str::FromStr,
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+
+/// A number of audio samples, or the absolute offset of a sample since the
+/// start of its stream.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Serialize, Deserialize,
+)]
+#[serde(transparent)]
+pub struct Samples(pub u64);
+
+impl Samples {
+    pub const ZERO: Self = Self(0);
+
+    /// Length of `samples`.
+    pub fn of(samples: &[i16]) -> Self {
+        Self(samples.len() as u64)
+    }
+
+    pub fn as_usize(self) -> usize {
+        self.0 as usize
+    }
+
+    pub fn saturating_sub(self, other: Self) -> Self {
+        Self(self.0.saturating_sub(other.0))
+    }
+}
+
+impl Add for Samples {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self(self.0 + other.0)
+    }
+}
+
+impl AddAssign for Samples {
+    fn add_assign(&mut self, other: Self) {
+        self.0 += other.0;
+    }
+}
+
+impl Sub for Samples {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Self(self.0 - other.0)
+    }
+}
+
+impl SubAssign for Samples {
+    fn sub_assign(&mut self, other: Self) {
+        self.0 -= other.0;
+    }
+}
+
+impl Mul<u64> for Samples {
+    type Output = Self;
+
+    fn mul(self, factor: u64) -> Self {
+        Self(self.0 * factor)
+    }
+}
+
+impl fmt::Display for Samples {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} samples", self.0)
+    }
+}
+
+/// A duration in milliseconds, the unit settings and logs are written in.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Serialize, Deserialize,
+)]
+#[serde(transparent)]
+pub struct Millis(pub u64);
+
+impl Millis {
+    pub fn as_duration(self) -> Duration {
+        Duration::from_millis(self.0)
+    }
+}
+
+impl Add for Millis {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self(self.0 + other.0)
+    }
+}
+
+impl Sub for Millis {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Self(self.0 - other.0)
+    }
+}
+
+impl Mul<u64> for Millis {
+    type Output = Self;
+
+    fn mul(self, factor: u64) -> Self {
+        Self(self.0 * factor)
+    }
+}
+
+impl FromStr for Millis {
+    type Err = ParseIntError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        value.parse().map(Self)
+    }
+}
+
+impl fmt::Display for Millis {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ms", self.0)
+    }
+}
+
+/// Samples per second of a stream, what converts between [`Samples`] and
+/// [`Millis`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct SampleRate(pub u32);
+
+impl SampleRate {
+    /// Rate of the caller audio once it entered the pipeline, whatever the source.
+    pub const PIPELINE: Self = Self(16_000);
+    /// Rate of μ-law phone lines.
+    pub const TELEPHONY: Self = Self(8_000);
+
+    pub fn hz(self) -> u64 {
+        self.0 as u64
+    }
+
+    /// Samples lasting `millis`, rounded down.
+    pub fn samples(self, millis: Millis) -> Samples {
+        Samples(millis.0 * self.hz() / 1000)
+    }
+
+    /// Samples lasting `duration`, rounded down.
+    pub fn samples_in(self, duration: Duration) -> Samples {
+        Samples((duration.as_nanos() * self.hz() as u128 / 1_000_000_000) as u64)
+    }
+
+    /// Length of `samples` in whole milliseconds, rounded down.
+    pub fn millis(self, samples: Samples) -> Millis {
+        Millis(samples.0 * 1000 / self.hz())
+    }
+
+    /// Exact length of `samples`.
+    pub fn duration(self, samples: Samples) -> Duration {
+        Duration::from_nanos((samples.0 as u128 * 1_000_000_000 / self.hz() as u128) as u64)
+    }
+}
+
+impl Default for SampleRate {
+    fn default() -> Self {
+        Self::PIPELINE
+    }
+}
+
+impl fmt::Display for SampleRate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} Hz", self.0)
+    }
+}
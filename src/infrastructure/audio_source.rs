@@ -1,2 +1,3 @@
+pub mod file_source_adapter;
 pub mod local_source_adapter;
 pub mod twilio_source_adapter;
@@ -0,0 +1,121 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use anywho::Error;
+use futures::future::BoxFuture;
+use tokio::{
+    sync::{Mutex, mpsc::Sender},
+    time::sleep,
+};
+use tracing::debug;
+
+use crate::domain::{
+    entities::{
+        audio_source_layer::{AudioSourceLayer, FRAME},
+        call_metadata::{CallMetadata, MediaFormat},
+    },
+    ports::audio_source::{AudioSource, AudioSourceEvent, OutboundFrame},
+    utils::{
+        audio::{
+            codec::{l16, wav::Wav},
+            resampler::Resampler,
+        },
+        units::{Millis, SampleRate},
+    },
+};
+
+/// Silence appended to the file so the VAD can close the last turn.
+const TRAILING_SILENCE: Millis = Millis(5000);
+
+/// Plays a WAV file as if a caller was speaking it.
+///
+/// `speed` is how many times faster than real time the file is fed, `0.0`
+/// meaning as fast as possible. Agent audio is kept so it can be inspected.
+#[derive(Debug, Clone)]
+pub struct FileAudioSource {
+    path: PathBuf,
+    speed: f32,
+    agent_audio: Arc<Mutex<Vec<i16>>>,
+}
+
+impl FileAudioSource {
+    pub fn new(path: PathBuf, speed: f32) -> Self {
+        Self {
+            path,
+            speed,
+            agent_audio: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    /// Reads the file as 16 kHz mono PCM.
+    pub fn read_pcm(path: &Path) -> Result<Vec<i16>, Error> {
+        let (rate, mono) = Wav::read(&std::fs::read(path)?)
+            .map_err(|err| Error::msg(format!("{}: {}", path.display(), err)))?;
+        let mut resampler = Resampler::new(rate, SampleRate::PIPELINE)
+            .map_err(|err| Error::msg(format!("{}: {}", path.display(), err)))?;
+
+        Ok(resampler.resample(&mono))
+    }
+
+    pub async fn agent_audio(&self) -> Vec<i16> {
+        self.agent_audio.lock().await.clone()
+    }
+
+    async fn play(&self, layer: &mut AudioSourceLayer<'_>) -> Result<(), Error> {
+        let mut pcm = Self::read_pcm(&self.path)?;
+        let trailing_silence = SampleRate::PIPELINE.samples(TRAILING_SILENCE).as_usize();
+        pcm.resize(pcm.len() + trailing_silence, 0);
+
+        layer
+            .dispatch(AudioSourceEvent::Started(CallMetadata {
+                media_format: Some(MediaFormat {
+                    encoding: l16::LE_NAME.to_string(),
+                    sample_rate: SampleRate::PIPELINE.0,
+                    channels: 1,
+                }),
+                ..CallMetadata::new()
+            }))
+            .await;
+
+        for frame in pcm.chunks(SampleRate::PIPELINE.samples(FRAME).as_usize()) {
+            layer.process(frame).await;
+
+            if self.speed > 0.0 {
+                sleep(FRAME.as_duration().div_f32(self.speed)).await;
+            }
+        }
+
+        debug!("{} fully played", self.path.display());
+        Ok(())
+    }
+}
+
+impl AudioSource for FileAudioSource {
+    fn connect(&self, _outbound: Sender<OutboundFrame>) -> Arc<dyn AudioSource> {
+        Arc::new(Self::new(self.path.clone(), self.speed))
+    }
+
+    /// Plays the whole file through the layer, there is no socket frame to wait for.
+    fn handle<'a>(
+        &'a self,
+        layer: &'a mut AudioSourceLayer<'_>,
+    ) -> BoxFuture<'a, Result<(), Error>> {
+        Box::pin(self.play(layer))
+    }
+
+    fn send_audio(&self, bytes: &[i16]) -> BoxFuture<'static, Result<(), Error>> {
+        let bytes = bytes.to_vec();
+        let agent_audio = Arc::clone(&self.agent_audio);
+
+        Box::pin(async move {
+            agent_audio.lock().await.extend_from_slice(&bytes);
+            Ok(())
+        })
+    }
+
+    fn clear_audio(&self) -> BoxFuture<'static, Result<(), Error>> {
+        Box::pin(async { Ok(()) })
+    }
+}
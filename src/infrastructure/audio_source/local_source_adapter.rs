@@ -1,48 +1,275 @@
-use std::pin::Pin;
+use std::{collections::HashMap, sync::Arc};
 
 use anywho::Error;
-use serde::Deserialize;
-use serde_json::from_str;
+use futures::future::BoxFuture;
+use serde::{Deserialize, Serialize};
+use serde_json::{from_str, to_string};
+use tokio::sync::{Mutex, mpsc::Sender};
+use tracing::{debug, warn};
 
 use crate::domain::{
-    entities::audio_source_layer::AudioSourceLayer, ports::audio_source::AudioSource,
+    entities::{
+        audio_source_layer::AudioSourceLayer,
+        call_metadata::{CallMetadata, MediaFormat},
+    },
+    ports::audio_source::{
+        AudioSource, AudioSourceEvent, InboundFrame, OutboundFrame, SessionEvent,
+    },
+    utils::{
+        audio::{
+            codec::{
+                Codec,
+                l16::{self, L16Le},
+            },
+            resampler::Resampler,
+        },
+        units::{Millis, SampleRate},
+    },
 };
 
+/// 20 ms of PCM per binary frame sent back to the client.
+const OUTBOUND_FRAME: Millis = Millis(20);
+
+/// Browser and CLI clients. Audio flows both ways as binary frames of mono
+/// PCM16 LE, text frames carry the JSON control messages.
+///
+/// The frames are at 16 kHz unless the `start` message announces another
+/// `sample_rate`, like the 48 kHz of a browser microphone.
 #[derive(Debug, Clone)]
-pub struct LocalAdapter;
+pub struct LocalAdapter {
+    outbound: Option<Sender<OutboundFrame>>,
+    /// Client audio to the pipeline rate.
+    to_pipeline: Arc<Mutex<Resampler>>,
+    /// Agent audio to the client rate, each clause resampled whole so its
+    /// tail is not held back for a clause that may never come.
+    to_client: Arc<Mutex<Resampler>>,
+    /// Odd byte ending the last binary frame, the first half of a sample cut
+    /// by the client.
+    leftover: Arc<Mutex<Option<u8>>>,
+}
 
 impl LocalAdapter {
     pub fn new() -> Self {
-        Self {}
+        Self {
+            outbound: None,
+            to_pipeline: Arc::new(Mutex::new(
+                Resampler::new(SampleRate::PIPELINE, SampleRate::PIPELINE)
+                    .expect("pipeline rate is supported"),
+            )),
+            to_client: Arc::new(Mutex::new(
+                Resampler::new(SampleRate::PIPELINE, SampleRate::PIPELINE)
+                    .expect("pipeline rate is supported"),
+            )),
+            leftover: Arc::new(Mutex::new(None)),
+        }
     }
-}
 
-impl AudioSource for LocalAdapter {
-    async fn handle(&self, layer: &mut AudioSourceLayer<'_>) -> Result<(), Error> {
-        if let Ok(body) = from_str::<Message>(&layer.audio_buffer.streamed_content) {
-            if body.event == "media" {
-                let pcm = body.content.clone();
+    async fn handle_control(&self, layer: &mut AudioSourceLayer<'_>, content: &str) {
+        let message = match from_str::<Message>(content) {
+            Ok(message) => message,
+            Err(err) => {
+                warn!("Unreadable local control frame: {}", err);
+                return;
+            }
+        };
+
+        match message {
+            Message::Start {
+                parameters,
+                sample_rate,
+            } => {
+                let rate = sample_rate.unwrap_or(SampleRate::PIPELINE);
+                match (
+                    Resampler::new(rate, SampleRate::PIPELINE),
+                    Resampler::new(SampleRate::PIPELINE, rate),
+                ) {
+                    (Ok(to_pipeline), Ok(to_client)) => {
+                        *self.to_pipeline.lock().await = to_pipeline;
+                        *self.to_client.lock().await = to_client;
+                    }
+                    (Err(err), _) | (_, Err(err)) => {
+                        warn!("{}, expecting 16 kHz frames", err)
+                    }
+                }
+                *self.leftover.lock().await = None;
+
+                let metadata = CallMetadata {
+                    custom_parameters: parameters,
+                    media_format: Some(MediaFormat {
+                        encoding: l16::LE_NAME.to_string(),
+                        sample_rate: self.to_pipeline.lock().await.from().0,
+                        channels: 1,
+                    }),
+                    ..CallMetadata::new()
+                };
+
+                layer.dispatch(AudioSourceEvent::Started(metadata)).await;
+            }
+            Message::Media { content } => {
+                let pcm = self.to_pipeline.lock().await.process(&content);
                 layer.process(&pcm).await;
             }
+            Message::Stop => layer.dispatch(AudioSourceEvent::Stopped).await,
+            Message::Mark { name } => layer.dispatch(AudioSourceEvent::Mark(name)).await,
+            Message::Dtmf { digit } => layer.dispatch(AudioSourceEvent::Dtmf(digit)).await,
         }
+    }
+}
+
+impl Default for LocalAdapter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AudioSource for LocalAdapter {
+    fn connect(&self, outbound: Sender<OutboundFrame>) -> Arc<dyn AudioSource> {
+        Arc::new(Self {
+            outbound: Some(outbound),
+            ..Self::new()
+        })
+    }
+
+    fn handle<'a>(
+        &'a self,
+        layer: &'a mut AudioSourceLayer<'_>,
+    ) -> BoxFuture<'a, Result<(), Error>> {
+        Box::pin(async move {
+            match &layer.audio_buffer.streamed_content {
+                InboundFrame::Binary(bytes) => {
+                    let mut leftover = self.leftover.lock().await;
+                    let mut bytes = match leftover.take() {
+                        Some(byte) => [&[byte], bytes.as_slice()].concat(),
+                        None => bytes.clone(),
+                    };
+                    if bytes.len() % 2 != 0 {
+                        debug!("Keeping the odd trailing byte of a local PCM frame");
+                        *leftover = bytes.pop();
+                    }
+                    drop(leftover);
+
+                    let samples = L16Le.decode(&bytes)?;
+                    let pcm = self.to_pipeline.lock().await.process(&samples);
+                    layer.process(&pcm).await;
+                }
+                InboundFrame::Text(content) => {
+                    let content = content.clone();
+                    self.handle_control(layer, &content).await;
+                }
+            }
 
-        Ok(())
+            Ok(())
+        })
     }
 
-    fn send_audio(
-        &self,
-        bytes: &Vec<i16>,
-    ) -> Pin<Box<dyn Future<Output = Result<(), Error>> + Send>> {
-        let bytes = bytes.clone();
+    fn send_audio(&self, bytes: &[i16]) -> BoxFuture<'static, Result<(), Error>> {
+        let bytes = bytes.to_vec();
+        let outbound = self.outbound.clone();
+        let to_client = Arc::clone(&self.to_client);
+
         Box::pin(async move {
-            println!("Local send_audio with {} samples", bytes.len());
+            let Some(outbound) = outbound else {
+                return Err(Error::msg("Local adapter is not connected to a socket"));
+            };
+
+            let (samples, frame) = {
+                let mut to_client = to_client.lock().await;
+                (
+                    to_client.resample(&bytes),
+                    to_client.to().samples(OUTBOUND_FRAME),
+                )
+            };
+
+            for chunk in samples.chunks(frame.as_usize()) {
+                let frame = L16Le.encode(chunk)?;
+
+                outbound
+                    .send(OutboundFrame::Binary(frame))
+                    .await
+                    .map_err(|_| Error::msg("Local socket is closed"))?;
+            }
+
             Ok(())
         })
     }
+
+    fn clear_audio(&self) -> BoxFuture<'static, Result<(), Error>> {
+        let outbound = self.outbound.clone();
+
+        Box::pin(async move {
+            let Some(outbound) = outbound else {
+                return Ok(());
+            };
+
+            let message = to_string(&OutboundMessage::Clear)?;
+            outbound
+                .send(OutboundFrame::Text(message))
+                .await
+                .map_err(|_| Error::msg("Local socket is closed"))
+        })
+    }
+
+    fn send_event(&self, event: SessionEvent) -> BoxFuture<'static, Result<(), Error>> {
+        let outbound = self.outbound.clone();
+
+        Box::pin(async move {
+            let Some(outbound) = outbound else {
+                return Ok(());
+            };
+
+            let message = match event {
+                SessionEvent::Vad { kind, noise_floor } => {
+                    OutboundMessage::Vad { kind, noise_floor }
+                }
+                SessionEvent::Transcript { member, text } => OutboundMessage::Transcript {
+                    member: member.to_string(),
+                    text,
+                },
+            };
+
+            outbound
+                .send(OutboundFrame::Text(to_string(&message)?))
+                .await
+                .map_err(|_| Error::msg("Local socket is closed"))
+        })
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "event", rename_all = "lowercase")]
+enum Message {
+    Start {
+        #[serde(default)]
+        parameters: HashMap<String, String>,
+        /// Rate of the audio frames both ways, 16 kHz when absent.
+        #[serde(default)]
+        sample_rate: Option<SampleRate>,
+    },
+    /// Legacy JSON audio frame, binary frames are preferred.
+    Media {
+        content: Vec<i16>,
+    },
+    Stop,
+    Mark {
+        name: String,
+    },
+    Dtmf {
+        digit: String,
+    },
 }
 
-#[derive(Debug, Clone, Deserialize)]
-struct Message {
-    pub event: String,
-    pub content: Vec<i16>,
+#[derive(Debug, Serialize)]
+#[serde(tag = "event", rename_all = "lowercase")]
+enum OutboundMessage {
+    /// The client must drop the agent audio it still has queued.
+    Clear,
+    Vad {
+        kind: &'static str,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        noise_floor: Option<f32>,
+    },
+    Transcript {
+        member: String,
+        text: String,
+    },
 }
@@ -1,68 +1,317 @@
-use std::pin::Pin;
+use std::{collections::HashMap, sync::Arc};
 
 use anywho::Error;
 use base64::{Engine, engine::general_purpose};
-use serde::Deserialize;
-use serde_json::from_str;
+use futures::future::BoxFuture;
+use serde::{Deserialize, Serialize};
+use serde_json::{from_str, to_string};
+use tokio::sync::{Mutex, mpsc::Sender};
+use tracing::{debug, warn};
 
 use crate::domain::{
-    entities::audio_source_layer::AudioSourceLayer, ports::audio_source::AudioSource,
-    utils::Convert,
+    entities::{
+        audio_source_layer::AudioSourceLayer,
+        call_metadata::{CallMetadata, MediaFormat},
+    },
+    ports::audio_source::{AudioSource, AudioSourceEvent, InboundFrame, OutboundFrame},
+    utils::{
+        audio::{
+            codec::{self, Codec, mulaw::MuLaw},
+            resampler::Resampler,
+        },
+        units::{Millis, SampleRate},
+    },
 };
 
+/// 20 ms of line audio, the frame size Twilio sends us.
+const OUTBOUND_FRAME: Millis = Millis(20);
+
 #[derive(Debug, Clone)]
-pub struct TwilioAdapter;
+pub struct TwilioAdapter {
+    outbound: Option<Sender<OutboundFrame>>,
+    stream_sid: Arc<Mutex<Option<String>>>,
+    /// Encoding announced by the stream, μ-law until it starts.
+    codec: Arc<Mutex<Box<dyn Codec>>>,
+    /// Caller audio from the line rate to the pipeline's.
+    to_pipeline: Arc<Mutex<Resampler>>,
+    /// Agent audio from the pipeline rate to the line's, each clause resampled
+    /// whole so its tail is not held back for a clause that may never come.
+    to_line: Arc<Mutex<Resampler>>,
+}
 
 impl TwilioAdapter {
     pub fn new() -> Self {
-        Self {}
+        Self {
+            outbound: None,
+            stream_sid: Arc::new(Mutex::new(None)),
+            codec: Arc::new(Mutex::new(Box::new(MuLaw))),
+            to_pipeline: Arc::new(Mutex::new(
+                Resampler::new(SampleRate::TELEPHONY, SampleRate::PIPELINE)
+                    .expect("telephony rate is supported"),
+            )),
+            to_line: Arc::new(Mutex::new(
+                Resampler::new(SampleRate::PIPELINE, SampleRate::TELEPHONY)
+                    .expect("telephony rate is supported"),
+            )),
+        }
     }
-}
 
-impl AudioSource for TwilioAdapter {
-    async fn handle(&self, layer: &mut AudioSourceLayer<'_>) -> Result<(), Error> {
-        if let Ok(envelope) = from_str::<Message>(&layer.audio_buffer.streamed_content) {
-            if envelope.event == "media" {
-                if let Ok(raw_bytes) = general_purpose::STANDARD.decode(&envelope.media.payload) {
-                    let int16_8k = Convert::decode_ulaw_bytes(&raw_bytes);
-                    let pcm = Convert::int16_8k_to_16k(&int16_8k);
+    async fn handle_message(&self, layer: &mut AudioSourceLayer<'_>) -> Result<(), Error> {
+        let InboundFrame::Text(content) = &layer.audio_buffer.streamed_content else {
+            warn!("Twilio sent an unexpected binary frame");
+            return Ok(());
+        };
+
+        let envelope = match from_str::<Message>(content) {
+            Ok(envelope) => envelope,
+            Err(err) => {
+                warn!("Unreadable Twilio frame: {}", err);
+                return Ok(());
+            }
+        };
+
+        match envelope {
+            Message::Connected { protocol, version } => {
+                debug!("Twilio connected protocol={} version={}", protocol, version);
+            }
+            Message::Start { start, stream_sid } => {
+                *self.stream_sid.lock().await = Some(stream_sid.clone());
+
+                if let Some(format) = &start.media_format {
+                    let rate = SampleRate(format.sample_rate);
+                    match codec::by_name(&format.encoding, rate) {
+                        Some(codec) => *self.codec.lock().await = codec,
+                        None => warn!(
+                            "Twilio announced {}, decoding the stream as μ-law",
+                            format.encoding
+                        ),
+                    }
+
+                    match (
+                        Resampler::new(rate, SampleRate::PIPELINE),
+                        Resampler::new(SampleRate::PIPELINE, rate),
+                    ) {
+                        (Ok(to_pipeline), Ok(to_line)) => {
+                            *self.to_pipeline.lock().await = to_pipeline;
+                            *self.to_line.lock().await = to_line;
+                        }
+                        (Err(err), _) | (_, Err(err)) => {
+                            warn!("{}, resampling the stream as 8 kHz", err)
+                        }
+                    }
+                }
+
+                let metadata = CallMetadata {
+                    call_sid: Some(start.call_sid),
+                    account_sid: Some(start.account_sid),
+                    stream_sid: Some(stream_sid),
+                    custom_parameters: start.custom_parameters,
+                    media_format: start.media_format.map(|format| MediaFormat {
+                        encoding: format.encoding,
+                        sample_rate: format.sample_rate,
+                        channels: format.channels,
+                    }),
+                };
+
+                layer.dispatch(AudioSourceEvent::Started(metadata)).await;
+            }
+            Message::Media { media, stream_sid } => {
+                {
+                    let mut known_stream_sid = self.stream_sid.lock().await;
+                    if known_stream_sid.is_none() {
+                        *known_stream_sid = Some(stream_sid);
+                    }
+                }
+
+                if let Ok(raw_bytes) = general_purpose::STANDARD.decode(&media.payload) {
+                    let samples = match self.codec.lock().await.decode(&raw_bytes) {
+                        Ok(samples) => samples,
+                        Err(err) => {
+                            warn!("Undecodable Twilio media: {}", err);
+                            return Ok(());
+                        }
+                    };
+                    let pcm = self.to_pipeline.lock().await.process(&samples);
 
                     layer.process(&pcm).await;
                 }
             }
+            Message::Stop => {
+                layer.dispatch(AudioSourceEvent::Stopped).await;
+            }
+            Message::Mark { mark } => {
+                layer.dispatch(AudioSourceEvent::Mark(mark.name)).await;
+            }
+            Message::Dtmf { dtmf } => {
+                layer.dispatch(AudioSourceEvent::Dtmf(dtmf.digit)).await;
+            }
         }
 
         Ok(())
     }
+}
+
+impl Default for TwilioAdapter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AudioSource for TwilioAdapter {
+    fn connect(&self, outbound: Sender<OutboundFrame>) -> Arc<dyn AudioSource> {
+        Arc::new(Self {
+            outbound: Some(outbound),
+            ..Self::new()
+        })
+    }
+
+    fn handle<'a>(
+        &'a self,
+        layer: &'a mut AudioSourceLayer<'_>,
+    ) -> BoxFuture<'a, Result<(), Error>> {
+        Box::pin(self.handle_message(layer))
+    }
+
+    fn send_audio(&self, bytes: &[i16]) -> BoxFuture<'static, Result<(), Error>> {
+        let bytes = bytes.to_vec();
+        let outbound = self.outbound.clone();
+        let stream_sid = Arc::clone(&self.stream_sid);
+        let codec = Arc::clone(&self.codec);
+        let to_line = Arc::clone(&self.to_line);
 
-    fn send_audio(
-        &self,
-        bytes: &Vec<i16>,
-    ) -> Pin<Box<dyn Future<Output = Result<(), Error>> + Send>> {
-        let bytes = bytes.clone();
         Box::pin(async move {
-            println!("Local send_audio with {} samples", bytes.len());
+            let Some(outbound) = outbound else {
+                return Err(Error::msg("Twilio adapter is not connected to a socket"));
+            };
+
+            let Some(stream_sid) = stream_sid.lock().await.clone() else {
+                return Err(Error::msg("Twilio streamSid is not known yet"));
+            };
+
+            let (samples, frame) = {
+                let mut to_line = to_line.lock().await;
+                (
+                    to_line.resample(&bytes),
+                    to_line.to().samples(OUTBOUND_FRAME),
+                )
+            };
+            let payloads = {
+                let codec = codec.lock().await;
+                samples
+                    .chunks(frame.as_usize())
+                    .map(|chunk| codec.encode(chunk))
+                    .collect::<Result<Vec<_>, _>>()?
+            };
+
+            for payload in payloads {
+                let payload = general_purpose::STANDARD.encode(payload);
+                let message = to_string(&OutboundMessage::Media {
+                    stream_sid: &stream_sid,
+                    media: OutboundMedia { payload },
+                })?;
+
+                outbound
+                    .send(OutboundFrame::Text(message))
+                    .await
+                    .map_err(|_| Error::msg("Twilio socket is closed"))?;
+            }
+
             Ok(())
         })
     }
+
+    fn clear_audio(&self) -> BoxFuture<'static, Result<(), Error>> {
+        let outbound = self.outbound.clone();
+        let stream_sid = Arc::clone(&self.stream_sid);
+
+        Box::pin(async move {
+            let (Some(outbound), Some(stream_sid)) = (outbound, stream_sid.lock().await.clone())
+            else {
+                return Ok(());
+            };
+
+            let message = to_string(&OutboundMessage::Clear {
+                stream_sid: &stream_sid,
+            })?;
+
+            outbound
+                .send(OutboundFrame::Text(message))
+                .await
+                .map_err(|_| Error::msg("Twilio socket is closed"))
+        })
+    }
 }
 
 #[derive(Debug, Deserialize)]
-struct Message {
-    pub event: String,
+#[serde(
+    tag = "event",
+    rename_all = "lowercase",
+    rename_all_fields = "camelCase"
+)]
+enum Message {
+    Connected { protocol: String, version: String },
+    Start { start: Start, stream_sid: String },
+    Media { media: Media, stream_sid: String },
+    Stop,
+    Mark { mark: Mark },
+    Dtmf { dtmf: Dtmf },
+}
 
-    #[serde(rename = "sequenceNumber")]
-    pub _sequence_number: String,
-    pub media: Media,
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Start {
+    pub account_sid: String,
+    pub call_sid: String,
 
-    #[serde(rename = "streamSid")]
-    pub _stream_sid: String,
+    #[serde(default)]
+    pub custom_parameters: HashMap<String, String>,
+    pub media_format: Option<TwilioMediaFormat>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TwilioMediaFormat {
+    pub encoding: String,
+    pub sample_rate: u32,
+    pub channels: u16,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct Media {
-    pub track: String,
+    pub track: Option<String>,
     pub chunk: String,
     pub timestamp: String,
     pub payload: String,
 }
+
+#[derive(Debug, Deserialize)]
+pub struct Mark {
+    pub name: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Dtmf {
+    pub track: String,
+    pub digit: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(
+    tag = "event",
+    rename_all = "lowercase",
+    rename_all_fields = "camelCase"
+)]
+enum OutboundMessage<'a> {
+    Media {
+        stream_sid: &'a str,
+        media: OutboundMedia,
+    },
+    Clear {
+        stream_sid: &'a str,
+    },
+}
+
+#[derive(Debug, Serialize)]
+struct OutboundMedia {
+    pub payload: String,
+}
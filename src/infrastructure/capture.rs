@@ -0,0 +1,3 @@
+pub mod capture_file;
+pub mod session_recorder;
+pub mod wav_sink;
@@ -0,0 +1,124 @@
+use std::{
+    fs::File,
+    io::{BufRead, BufReader},
+    path::Path,
+};
+
+use anywho::Error;
+use base64::{Engine, engine::general_purpose};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::domain::{
+    entities::agent_profile::VadOverrides,
+    ports::audio_source::{InboundFrame, OutboundFrame},
+};
+
+/// First line of a capture file, frames follow as one JSON object per line.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CaptureHeader {
+    /// Name of the audio source the socket was served by.
+    pub protocol: String,
+    pub session: Uuid,
+    /// RFC 3339 wall clock time the recording started.
+    pub started_at: String,
+    #[serde(default)]
+    pub agent: Option<String>,
+    /// Endpointing the session asked for in its query, Twilio sends its own
+    /// in the start frame, which is recorded with the others.
+    #[serde(default)]
+    pub vad: VadOverrides,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Direction {
+    Inbound,
+    Outbound,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CaptureFrame {
+    /// Microseconds since the session started, from a monotonic clock.
+    pub at_us: u64,
+    pub direction: Direction,
+    #[serde(flatten)]
+    pub payload: CapturePayload,
+}
+
+/// Binary frames are kept base64 encoded.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CapturePayload {
+    Text(String),
+    Binary(String),
+}
+
+impl CapturePayload {
+    pub fn inbound_frame(&self) -> Result<InboundFrame, Error> {
+        match self {
+            CapturePayload::Text(text) => Ok(InboundFrame::Text(text.clone())),
+            CapturePayload::Binary(encoded) => Ok(InboundFrame::Binary(
+                general_purpose::STANDARD.decode(encoded)?,
+            )),
+        }
+    }
+}
+
+impl From<&InboundFrame> for CapturePayload {
+    fn from(frame: &InboundFrame) -> Self {
+        match frame {
+            InboundFrame::Text(text) => CapturePayload::Text(text.clone()),
+            InboundFrame::Binary(bytes) => {
+                CapturePayload::Binary(general_purpose::STANDARD.encode(bytes))
+            }
+        }
+    }
+}
+
+impl From<&OutboundFrame> for CapturePayload {
+    fn from(frame: &OutboundFrame) -> Self {
+        match frame {
+            OutboundFrame::Text(text) => CapturePayload::Text(text.clone()),
+            OutboundFrame::Binary(bytes) => {
+                CapturePayload::Binary(general_purpose::STANDARD.encode(bytes))
+            }
+        }
+    }
+}
+
+/// A recorded WebSocket session.
+#[derive(Debug, Clone)]
+pub struct CaptureFile {
+    pub header: CaptureHeader,
+    pub frames: Vec<CaptureFrame>,
+}
+
+impl CaptureFile {
+    pub fn read(path: &Path) -> Result<Self, Error> {
+        let mut lines = BufReader::new(File::open(path)?).lines();
+
+        let header = match lines.next() {
+            Some(line) => serde_json::from_str::<CaptureHeader>(&line?)?,
+            None => return Err(Error::msg(format!("{} is empty", path.display()))),
+        };
+
+        let mut frames = Vec::new();
+        for line in lines {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            frames.push(serde_json::from_str::<CaptureFrame>(&line)?);
+        }
+
+        Ok(Self { header, frames })
+    }
+
+    pub fn inbound(&self) -> impl Iterator<Item = &CaptureFrame> {
+        self.frames
+            .iter()
+            .filter(|frame| frame.direction == Direction::Inbound)
+    }
+}
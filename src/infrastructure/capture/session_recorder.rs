@@ -0,0 +1,118 @@
+use std::path::{Path, PathBuf};
+
+use anywho::Error;
+use chrono::Utc;
+use tokio::{
+    fs::{File, create_dir_all},
+    io::{AsyncWriteExt, BufWriter},
+    spawn,
+    sync::mpsc::{UnboundedSender, unbounded_channel},
+    time::Instant,
+};
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::{
+    domain::{
+        entities::agent_profile::VadOverrides,
+        ports::audio_source::{InboundFrame, OutboundFrame},
+    },
+    infrastructure::capture::capture_file::{
+        CaptureFrame, CaptureHeader, CapturePayload, Direction,
+    },
+};
+
+/// Writes every frame of a WebSocket session to a capture file.
+///
+/// Recording never blocks the call: frames are timestamped on the spot and
+/// written by a background task, which flushes and stops once every clone of
+/// the recorder is dropped.
+#[derive(Debug, Clone)]
+pub struct SessionRecorder {
+    sender: UnboundedSender<CaptureFrame>,
+    started: Instant,
+}
+
+impl SessionRecorder {
+    /// Starts `<dir>/<date>-<protocol>-<session>.jsonl`.
+    pub async fn create(
+        dir: &Path,
+        protocol: &str,
+        session: Uuid,
+        agent: Option<String>,
+        vad: VadOverrides,
+    ) -> Result<(Self, PathBuf), Error> {
+        create_dir_all(dir).await?;
+
+        let now = Utc::now();
+        let path = dir.join(format!(
+            "{}-{}-{}.jsonl",
+            now.format("%Y%m%dT%H%M%S"),
+            protocol,
+            session
+        ));
+
+        let header = CaptureHeader {
+            protocol: protocol.to_string(),
+            session,
+            started_at: now.to_rfc3339(),
+            agent,
+            vad,
+        };
+
+        let mut writer = BufWriter::new(File::create(&path).await?);
+        writer
+            .write_all(format!("{}\n", serde_json::to_string(&header)?).as_bytes())
+            .await?;
+
+        let (sender, mut receiver) = unbounded_channel::<CaptureFrame>();
+        let capture_path = path.clone();
+        spawn(async move {
+            while let Some(frame) = receiver.recv().await {
+                let line = match serde_json::to_string(&frame) {
+                    Ok(line) => line,
+                    Err(err) => {
+                        warn!("Capture frame dropped: {}", err);
+                        continue;
+                    }
+                };
+
+                if let Err(err) = writer.write_all(format!("{}\n", line).as_bytes()).await {
+                    warn!("Capture {} stopped: {}", capture_path.display(), err);
+                    return;
+                }
+
+                // flushing when idle keeps the file usable if the process dies
+                if receiver.is_empty() {
+                    let _ = writer.flush().await;
+                }
+            }
+
+            let _ = writer.flush().await;
+        });
+
+        Ok((
+            Self {
+                sender,
+                started: Instant::now(),
+            },
+            path,
+        ))
+    }
+
+    pub fn inbound(&self, frame: &InboundFrame) {
+        self.record(Direction::Inbound, frame.into());
+    }
+
+    pub fn outbound(&self, frame: &OutboundFrame) {
+        self.record(Direction::Outbound, frame.into());
+    }
+
+    fn record(&self, direction: Direction, payload: CapturePayload) {
+        let _ = self.sender.send(CaptureFrame {
+            at_us: self.started.elapsed().as_micros() as u64,
+            direction,
+            payload,
+        });
+    }
+}
@@ -0,0 +1,74 @@
+use std::path::{Path, PathBuf};
+
+use anywho::Error;
+use chrono::Utc;
+use hound::WavWriter;
+use tokio::{
+    fs::create_dir_all,
+    sync::mpsc::{UnboundedSender, unbounded_channel},
+    task::spawn_blocking,
+};
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::domain::{ports::audio_sink::AudioSink, utils::audio::codec::wav::Wav};
+
+/// Writes the whole caller audio of a session to a 16 kHz WAV file.
+///
+/// Like the session recorder it never blocks the call: samples are written by
+/// a background task, which finalizes the file once the sink is dropped.
+#[derive(Debug)]
+pub struct WavSink {
+    sender: UnboundedSender<Vec<i16>>,
+}
+
+impl WavSink {
+    /// Starts `<dir>/<date>-<protocol>-<session>.wav`, next to the session capture.
+    pub async fn create(
+        dir: &Path,
+        protocol: &str,
+        session: Uuid,
+    ) -> Result<(Self, PathBuf), Error> {
+        create_dir_all(dir).await?;
+
+        let path = dir.join(format!(
+            "{}-{}-{}.wav",
+            Utc::now().format("%Y%m%dT%H%M%S"),
+            protocol,
+            session
+        ));
+        let spec = Wav::default().spec();
+
+        let wav_path = path.clone();
+        let mut writer = spawn_blocking(move || WavWriter::create(wav_path, spec)).await??;
+
+        let (sender, mut receiver) = unbounded_channel::<Vec<i16>>();
+        let sink_path = path.clone();
+        spawn_blocking(move || {
+            while let Some(samples) = receiver.blocking_recv() {
+                for sample in samples {
+                    if let Err(err) = writer.write_sample(sample) {
+                        warn!("Caller audio {} stopped: {}", sink_path.display(), err);
+                        return;
+                    }
+                }
+            }
+
+            if let Err(err) = writer.finalize() {
+                warn!(
+                    "Caller audio {} not finalized: {}",
+                    sink_path.display(),
+                    err
+                );
+            }
+        });
+
+        Ok((Self { sender }, path))
+    }
+}
+
+impl AudioSink for WavSink {
+    fn write(&mut self, samples: &[i16]) {
+        let _ = self.sender.send(samples.to_vec());
+    }
+}
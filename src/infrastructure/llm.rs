@@ -1 +1,2 @@
 pub mod gemini_adapter;
+pub mod mock_llm;
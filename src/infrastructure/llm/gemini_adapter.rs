@@ -1,100 +1,432 @@
-use std::sync::Arc;
+use std::collections::VecDeque;
 
 use anywho::Error;
+use futures::{future::BoxFuture, stream};
 use openai_api_rs::v1::{
-    api::OpenAIClient,
     chat_completion::{
-        ChatCompletionMessage, ChatCompletionRequest, Content, MessageRole, ToolChoiceType,
+        ChatCompletionMessage, ChatCompletionRequest, ChatCompletionResponse, Content, MessageRole,
+        Tool, ToolCall, ToolCallFunction, ToolChoiceType, ToolType,
     },
+    types::{Function, FunctionParameters, JSONSchemaType},
 };
-use tokio::sync::Mutex;
+use reqwest::{Client, Response, header::CONTENT_TYPE};
+use serde::Deserialize;
+use tracing::{debug, warn};
 
 use crate::domain::{
     entities::history::{history_event::HistoryEvent, history_member::HistoryMember},
-    ports::llm::{Llm, LlmProcessResponse},
+    ports::llm::{
+        Llm, LlmProcessResponse, LlmRequest, LlmStream, LlmStreamEvent, LlmToolCall, LlmUsage,
+    },
 };
 
+/// Talks to the OpenAI compatible endpoint of AI Studio. The reqwest client is a
+/// shared connection pool, so clones of the adapter run their requests in parallel.
 #[derive(Clone)]
 pub struct GeminiAdapter {
-    client: Arc<Mutex<OpenAIClient>>,
+    http_client: Client,
+    api_key: String,
+    endpoint: String,
 }
 
 impl GeminiAdapter {
-    pub fn new(api_key: String, endpoint: String) -> Result<Self, Error> {
-        let client = OpenAIClient::builder()
-            .with_api_key(api_key)
-            .with_endpoint(endpoint)
-            .build()
-            .map_err(|err| {
-                let message = format!("Error during OpenAI client instanciation: {}", err);
-                Error::msg(message)
-            })?;
-
-        Ok(Self {
-            client: Arc::new(Mutex::new(client)),
-        })
+    pub fn new(api_key: String, endpoint: String) -> Self {
+        Self {
+            http_client: Client::new(),
+            api_key,
+            endpoint,
+        }
     }
-}
 
-impl Llm for GeminiAdapter {
-    async fn process(
-        &mut self,
-        model: String,
-        history_events: Vec<HistoryEvent>,
-    ) -> Result<LlmProcessResponse, Error> {
-        let messages: Vec<ChatCompletionMessage> = history_events
-            .iter()
-            .map(|event| ChatCompletionMessage {
-                name: None,
-                tool_call_id: None,
-                tool_calls: None,
-                role: match event.member {
-                    HistoryMember::User => MessageRole::user,
-                    HistoryMember::Agent => MessageRole::assistant,
-                    HistoryMember::ToolCall => MessageRole::tool,
-                    HistoryMember::System => MessageRole::system,
-                },
-                content: match &event.content {
-                    Some(content) => Content::Text(content.clone()),
-                    None => Content::Text("".to_string()),
+    async fn post(&self, request: &ChatCompletionRequest) -> Result<Response, Error> {
+        let response = self
+            .http_client
+            .post(format!(
+                "{}/chat/completions",
+                self.endpoint.trim_end_matches('/')
+            ))
+            .bearer_auth(&self.api_key)
+            .header(CONTENT_TYPE, "application/json")
+            .body(serde_json::to_vec(request)?)
+            .send()
+            .await?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let message = response.text().await.unwrap_or_default();
+            return Err(Error::msg(format!(
+                "LLM request failed ({}): {}",
+                status, message
+            )));
+        }
+
+        Ok(response)
+    }
+
+    fn build_request(&self, request: LlmRequest, stream: bool) -> ChatCompletionRequest {
+        let messages = Self::build_messages(&request.history_events);
+
+        let tools: Vec<Tool> = request
+            .tools
+            .into_iter()
+            .map(|definition| Tool {
+                r#type: ToolType::Function,
+                function: Function {
+                    name: definition.name,
+                    description: Some(definition.description),
+                    parameters: serde_json::from_value(definition.parameters).unwrap_or_else(
+                        |err| {
+                            warn!("Invalid tool parameters schema: {}", err);
+                            FunctionParameters {
+                                schema_type: JSONSchemaType::Object,
+                                properties: None,
+                                required: None,
+                            }
+                        },
+                    ),
                 },
             })
             .collect();
 
-        let request = ChatCompletionRequest {
-            model,
+        let (tools, tool_choice) = match tools.is_empty() {
+            true => (None, None),
+            false => (Some(tools), Some(ToolChoiceType::Auto)),
+        };
+
+        ChatCompletionRequest {
+            model: request.model,
             messages,
-            temperature: Some(0.2),
-            top_p: Some(0.8),
+            temperature: Some(request.sampling.temperature),
+            top_p: Some(request.sampling.top_p),
             n: None,
             response_format: None,
-            stream: None,
+            stream: Some(stream),
             stop: None,
-            max_tokens: Some(512),
+            max_tokens: Some(request.sampling.max_tokens.into()),
             presence_penalty: None,
             frequency_penalty: None,
             logit_bias: None,
             user: None,
             seed: None,
-            tools: Some(Vec::new()),
+            tools,
             parallel_tool_calls: None,
-            tool_choice: Some(ToolChoiceType::Auto),
+            tool_choice,
             reasoning: None,
             transforms: None,
+        }
+    }
+
+    /// A tool call is stored as a single history event but the API expects the
+    /// assistant request followed by the tool answers. The calls of a round are
+    /// attached to the agent event recorded just before them, so the model gets
+    /// one assistant message per round with what it said and everything it asked.
+    fn build_messages(events: &[HistoryEvent]) -> Vec<ChatCompletionMessage> {
+        let mut messages = Vec::<ChatCompletionMessage>::with_capacity(events.len());
+        // index of the assistant message that takes the next tool calls
+        let mut round: Option<usize> = None;
+
+        for event in events {
+            let Some(tool_call) = &event.tool_call else {
+                round = matches!(event.member, HistoryMember::Agent).then_some(messages.len());
+                messages.push(ChatCompletionMessage {
+                    name: None,
+                    tool_call_id: None,
+                    tool_calls: None,
+                    role: match event.member {
+                        HistoryMember::User => MessageRole::user,
+                        HistoryMember::Agent => MessageRole::assistant,
+                        HistoryMember::ToolCall => MessageRole::tool,
+                        HistoryMember::System => MessageRole::system,
+                    },
+                    content: match &event.content {
+                        Some(content) => Content::Text(content.clone()),
+                        None => Content::Text("".to_string()),
+                    },
+                });
+                continue;
+            };
+
+            let call = ToolCall {
+                id: tool_call.id.clone(),
+                r#type: "function".to_string(),
+                function: ToolCallFunction {
+                    name: Some(tool_call.name.clone()),
+                    arguments: Some(tool_call.arguments.clone()),
+                },
+            };
+
+            match round {
+                Some(index) => messages[index]
+                    .tool_calls
+                    .get_or_insert_with(Vec::new)
+                    .push(call),
+                None => {
+                    round = Some(messages.len());
+                    messages.push(ChatCompletionMessage {
+                        name: None,
+                        tool_call_id: None,
+                        tool_calls: Some(vec![call]),
+                        role: MessageRole::assistant,
+                        content: Content::Text("".to_string()),
+                    });
+                }
+            }
+
+            messages.push(ChatCompletionMessage {
+                name: Some(tool_call.name.clone()),
+                tool_call_id: Some(tool_call.id.clone()),
+                tool_calls: None,
+                role: MessageRole::tool,
+                content: Content::Text(tool_call.result.clone()),
+            });
+        }
+
+        messages
+    }
+
+    async fn complete(&self, request: LlmRequest) -> Result<LlmProcessResponse, Error> {
+        let request = self.build_request(request, false);
+
+        let response = self.post(&request).await?.bytes().await?;
+        let response = serde_json::from_slice::<ChatCompletionResponse>(&response)?;
+
+        let usage = LlmUsage {
+            prompt_tokens: response.usage.prompt_tokens.max(0) as u32,
+            completion_tokens: response.usage.completion_tokens.max(0) as u32,
+            total_tokens: response.usage.total_tokens.max(0) as u32,
         };
 
-        let mut client = self.client.lock().await;
-        let response = client.chat_completion(request).await?;
+        let Some(choice) = response.choices.into_iter().next() else {
+            return Err(Error::msg("LLM response has no choice"));
+        };
+
+        let tool_calls = choice
+            .message
+            .tool_calls
+            .unwrap_or_default()
+            .into_iter()
+            .map(|call| LlmToolCall {
+                id: call.id,
+                name: call.function.name.unwrap_or_default(),
+                arguments: call.function.arguments.unwrap_or_default(),
+            })
+            .collect();
+
+        let response = LlmProcessResponse {
+            text: choice
+                .message
+                .content
+                .filter(|content| !content.trim().is_empty()),
+            finish_reason: choice.finish_reason.map(|reason| format!("{:?}", reason)),
+            usage: Some(usage),
+            tool_calls,
+        };
 
-        let mut messages = Vec::<String>::new();
-        for choice in &response.choices {
-            if let Some(content) = &choice.message.content {
-                messages.push(content.clone());
+        debug!("LLM response: {:?}", response);
+
+        Ok(response)
+    }
+
+    async fn stream(&self, request: LlmRequest) -> Result<LlmStream, Error> {
+        let request = self.build_request(request, true);
+        let response = self.post(&request).await?;
+
+        let state = SseState {
+            response,
+            buffer: Vec::new(),
+            pending: VecDeque::new(),
+            reply: ReplyAccumulator::default(),
+            finished: false,
+        };
+
+        let events = stream::unfold(state, |mut state| async move {
+            loop {
+                if let Some(event) = state.pending.pop_front() {
+                    return Some((Ok(event), state));
+                }
+
+                if state.finished {
+                    return None;
+                }
+
+                match state.response.chunk().await {
+                    Ok(Some(bytes)) => {
+                        state
+                            .buffer
+                            .extend(bytes.iter().filter(|&&byte| byte != b'\r'));
+                        if let Err(err) = state.drain_events() {
+                            state.finished = true;
+                            return Some((Err(err), state));
+                        }
+                    }
+                    Ok(None) => state.finish(),
+                    Err(err) => {
+                        state.finished = true;
+                        return Some((Err(Error::from(err)), state));
+                    }
+                }
+            }
+        });
+
+        Ok(Box::pin(events))
+    }
+}
+
+impl Llm for GeminiAdapter {
+    fn process(&self, request: LlmRequest) -> BoxFuture<'_, Result<LlmProcessResponse, Error>> {
+        Box::pin(self.complete(request))
+    }
+
+    fn process_stream(&self, request: LlmRequest) -> BoxFuture<'_, Result<LlmStream, Error>> {
+        Box::pin(self.stream(request))
+    }
+}
+
+struct SseState {
+    response: Response,
+    buffer: Vec<u8>,
+    pending: VecDeque<LlmStreamEvent>,
+    reply: ReplyAccumulator,
+    finished: bool,
+}
+
+impl SseState {
+    fn drain_events(&mut self) -> Result<(), Error> {
+        // events are split on bytes so multi-byte characters never get cut in half
+        while let Some(end) = self.buffer.windows(2).position(|window| window == b"\n\n") {
+            let event: Vec<u8> = self.buffer.drain(..end + 2).collect();
+            let event = String::from_utf8_lossy(&event);
+
+            for line in event.lines() {
+                let Some(data) = line.strip_prefix("data:").map(str::trim) else {
+                    continue;
+                };
+
+                if data == "[DONE]" {
+                    self.finish();
+                    return Ok(());
+                }
+
+                let chunk = serde_json::from_str::<StreamChunk>(data)?;
+                if let Some(delta) = self.reply.push(chunk) {
+                    self.pending.push_back(LlmStreamEvent::Delta(delta));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn finish(&mut self) {
+        if self.finished {
+            return;
+        }
+
+        self.finished = true;
+        let response = std::mem::take(&mut self.reply).into_response();
+        debug!("LLM streamed response: {:?}", response);
+        self.pending.push_back(LlmStreamEvent::Finished(response));
+    }
+}
+
+#[derive(Default)]
+struct ReplyAccumulator {
+    text: String,
+    finish_reason: Option<String>,
+    usage: Option<LlmUsage>,
+    tool_calls: Vec<LlmToolCall>,
+}
+
+impl ReplyAccumulator {
+    fn push(&mut self, chunk: StreamChunk) -> Option<String> {
+        if let Some(usage) = chunk.usage {
+            self.usage = Some(LlmUsage {
+                prompt_tokens: usage.prompt_tokens,
+                completion_tokens: usage.completion_tokens,
+                total_tokens: usage.total_tokens,
+            });
+        }
+
+        let choice = chunk.choices.into_iter().next()?;
+        if choice.finish_reason.is_some() {
+            self.finish_reason = choice.finish_reason;
+        }
+
+        for call in choice.delta.tool_calls.unwrap_or_default() {
+            let index = call.index.unwrap_or(self.tool_calls.len());
+            if self.tool_calls.len() <= index {
+                self.tool_calls.resize_with(index + 1, || LlmToolCall {
+                    id: String::new(),
+                    name: String::new(),
+                    arguments: String::new(),
+                });
+            }
+
+            let entry = &mut self.tool_calls[index];
+            if let Some(id) = call.id {
+                entry.id = id;
+            }
+            if let Some(function) = call.function {
+                entry.name.push_str(&function.name.unwrap_or_default());
+                entry
+                    .arguments
+                    .push_str(&function.arguments.unwrap_or_default());
             }
         }
 
-        println!("LLM RESPONSE: {:?}", messages);
+        let delta = choice.delta.content.filter(|content| !content.is_empty())?;
+        self.text.push_str(&delta);
+        Some(delta)
+    }
 
-        Ok(LlmProcessResponse {})
+    fn into_response(self) -> LlmProcessResponse {
+        LlmProcessResponse {
+            text: match self.text.trim().is_empty() {
+                true => None,
+                false => Some(self.text),
+            },
+            finish_reason: self.finish_reason,
+            usage: self.usage,
+            tool_calls: self.tool_calls,
+        }
     }
 }
+
+#[derive(Debug, Deserialize)]
+struct StreamChunk {
+    #[serde(default)]
+    choices: Vec<StreamChoice>,
+    usage: Option<StreamUsage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct StreamChoice {
+    delta: StreamDelta,
+    finish_reason: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct StreamDelta {
+    content: Option<String>,
+    tool_calls: Option<Vec<StreamToolCall>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct StreamToolCall {
+    index: Option<usize>,
+    id: Option<String>,
+    function: Option<StreamFunction>,
+}
+
+#[derive(Debug, Deserialize)]
+struct StreamFunction {
+    name: Option<String>,
+    arguments: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct StreamUsage {
+    prompt_tokens: u32,
+    completion_tokens: u32,
+    total_tokens: u32,
+}
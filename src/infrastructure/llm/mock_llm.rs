@@ -0,0 +1,124 @@
+use std::{sync::Arc, time::Duration};
+
+use anywho::Error;
+use futures::{future::BoxFuture, stream};
+use tokio::{sync::Mutex, time::sleep};
+
+use crate::{
+    domain::ports::llm::{
+        Llm, LlmProcessResponse, LlmRequest, LlmStream, LlmStreamEvent, LlmToolCall,
+    },
+    infrastructure::mock::Script,
+};
+
+/// Answers each new conversation state with the next scripted response.
+#[derive(Debug, Clone)]
+pub struct MockLlm {
+    script: Script<LlmProcessResponse>,
+    latency: Duration,
+    requests: Arc<Mutex<Vec<LlmRequest>>>,
+}
+
+impl MockLlm {
+    /// `Err` steps make the matching request fail with that message.
+    pub fn new(script: Vec<Result<LlmProcessResponse, String>>) -> Self {
+        Self {
+            script: Script::new(script),
+            latency: Duration::ZERO,
+            requests: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    pub fn with_latency(mut self, latency: Duration) -> Self {
+        self.latency = latency;
+        self
+    }
+
+    /// Reply to the requests past the end of the script.
+    pub fn with_fallback(mut self, response: LlmProcessResponse) -> Self {
+        self.script = self.script.with_fallback(response);
+        self
+    }
+
+    /// A spoken answer.
+    pub fn text(text: &str) -> LlmProcessResponse {
+        LlmProcessResponse {
+            text: Some(text.to_string()),
+            finish_reason: Some("stop".to_string()),
+            usage: None,
+            tool_calls: Vec::new(),
+        }
+    }
+
+    /// A single tool call with no text, `arguments` being raw JSON.
+    pub fn tool_call(name: &str, arguments: &str) -> LlmProcessResponse {
+        LlmProcessResponse {
+            text: None,
+            finish_reason: Some("tool_calls".to_string()),
+            usage: None,
+            tool_calls: vec![LlmToolCall {
+                id: format!("call_{}", name),
+                name: name.to_string(),
+                arguments: arguments.to_string(),
+            }],
+        }
+    }
+
+    pub fn script(&self) -> &Script<LlmProcessResponse> {
+        &self.script
+    }
+
+    /// Every request received, replays included.
+    pub async fn requests(&self) -> Vec<LlmRequest> {
+        self.requests.lock().await.clone()
+    }
+
+    async fn answer(&self, request: LlmRequest) -> Result<LlmProcessResponse, Error> {
+        sleep(self.latency).await;
+
+        let key: Vec<String> = request
+            .history_events
+            .iter()
+            .map(|event| {
+                format!(
+                    "{}:{:?}:{:?}",
+                    event.member,
+                    event.content,
+                    event.tool_call.as_ref().map(|call| (
+                        &call.name,
+                        &call.arguments,
+                        &call.result
+                    ))
+                )
+            })
+            .chain([format!("tools:{}", request.tools.len())])
+            .collect();
+
+        self.requests.lock().await.push(request);
+        self.script.next("MockLlm", key).await
+    }
+}
+
+impl Llm for MockLlm {
+    fn process(&self, request: LlmRequest) -> BoxFuture<'_, Result<LlmProcessResponse, Error>> {
+        Box::pin(self.answer(request))
+    }
+
+    fn process_stream(&self, request: LlmRequest) -> BoxFuture<'_, Result<LlmStream, Error>> {
+        Box::pin(async move {
+            let response = self.answer(request).await?;
+
+            // one delta per word, as a provider would stream it
+            let mut events: Vec<Result<LlmStreamEvent, Error>> = response
+                .text
+                .as_deref()
+                .unwrap_or_default()
+                .split_inclusive(' ')
+                .map(|word| Ok(LlmStreamEvent::Delta(word.to_string())))
+                .collect();
+            events.push(Ok(LlmStreamEvent::Finished(response)));
+
+            Ok(Box::pin(stream::iter(events)) as LlmStream)
+        })
+    }
+}
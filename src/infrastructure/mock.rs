@@ -0,0 +1,87 @@
+use std::{
+    collections::HashMap,
+    hash::{DefaultHasher, Hash, Hasher},
+    sync::Arc,
+};
+
+use anywho::Error;
+use tokio::sync::Mutex;
+
+/// Canned answers handed out by the mock adapters, one per distinct input.
+///
+/// A pipeline cancelled because the caller resumed runs again on the longer
+/// turn, which may be transcribed the same, and the LLM then gets the same
+/// history again. Those replays get the answer already given instead of
+/// consuming the next step of the script.
+#[derive(Debug, Clone)]
+pub struct Script<T> {
+    state: Arc<Mutex<ScriptState<T>>>,
+    fallback: Option<T>,
+}
+
+#[derive(Debug)]
+struct ScriptState<T> {
+    steps: Vec<Result<T, String>>,
+    answered: HashMap<u64, usize>,
+    calls: usize,
+}
+
+impl<T: Clone> Script<T> {
+    pub fn new(steps: Vec<Result<T, String>>) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(ScriptState {
+                steps,
+                answered: HashMap::new(),
+                calls: 0,
+            })),
+            fallback: None,
+        }
+    }
+
+    /// Answer given once the script is exhausted, instead of failing.
+    pub fn with_fallback(mut self, reply: T) -> Self {
+        self.fallback = Some(reply);
+        self
+    }
+
+    /// Answers `input`, failing once every scripted step has been used unless
+    /// there is a fallback.
+    pub async fn next(&self, name: &str, input: impl Hash) -> Result<T, Error> {
+        let mut hasher = DefaultHasher::new();
+        input.hash(&mut hasher);
+        let key = hasher.finish();
+
+        let mut state = self.state.lock().await;
+        state.calls += 1;
+
+        let index = match state.answered.get(&key) {
+            Some(index) => *index,
+            None => {
+                let index = state.answered.len();
+                state.answered.insert(key, index);
+                index
+            }
+        };
+
+        match (state.steps.get(index), &self.fallback) {
+            (Some(Ok(reply)), _) => Ok(reply.clone()),
+            (Some(Err(message)), _) => Err(Error::msg(format!("{}: {}", name, message))),
+            (None, Some(fallback)) => Ok(fallback.clone()),
+            (None, None) => Err(Error::msg(format!(
+                "{}: script exhausted after {} steps",
+                name,
+                state.steps.len()
+            ))),
+        }
+    }
+
+    /// How many times the adapter was called, replays included.
+    pub async fn calls(&self) -> usize {
+        self.state.lock().await.calls
+    }
+
+    /// How many scripted steps were consumed.
+    pub async fn consumed(&self) -> usize {
+        self.state.lock().await.answered.len()
+    }
+}
@@ -1,5 +1,10 @@
 pub mod audio_source;
+pub mod capture;
 pub mod intelligence;
 pub mod llm;
+pub mod mock;
 pub mod stt;
+pub mod tool;
+pub mod tts;
+pub mod turn_detector;
 pub mod vad;
@@ -1 +1,2 @@
+pub mod mock_stt;
 pub mod scribe_adapter;
@@ -0,0 +1,69 @@
+use std::time::Duration;
+
+use anywho::Error;
+use futures::future::BoxFuture;
+use tokio::time::sleep;
+
+use crate::{
+    domain::ports::stt::{Stt, SttPayload},
+    infrastructure::mock::Script,
+};
+
+/// Transcribes each new utterance with the next scripted transcript.
+#[derive(Debug, Clone)]
+pub struct MockStt {
+    script: Script<String>,
+    latency: Duration,
+}
+
+impl MockStt {
+    /// `Err` steps make the matching utterance fail with that message.
+    pub fn new(script: Vec<Result<String, String>>) -> Self {
+        Self {
+            script: Script::new(script),
+            latency: Duration::ZERO,
+        }
+    }
+
+    pub fn with_latency(mut self, latency: Duration) -> Self {
+        self.latency = latency;
+        self
+    }
+
+    /// Transcript of the utterances past the end of the script.
+    pub fn with_fallback(mut self, text: &str) -> Self {
+        self.script = self.script.with_fallback(text.to_string());
+        self
+    }
+
+    pub fn script(&self) -> &Script<String> {
+        &self.script
+    }
+}
+
+impl Stt for MockStt {
+    fn execute<'a>(
+        &'a self,
+        audio: &'a [i16],
+        language_code: &'a str,
+    ) -> BoxFuture<'a, Result<SttPayload, Error>> {
+        Box::pin(async move {
+            sleep(self.latency).await;
+            let text = self.script.next("MockStt", audio).await?;
+
+            Ok(SttPayload {
+                text: Some(text),
+                language_code: Some(language_code.to_string()),
+                language_probability: Some(1.0),
+            })
+        })
+    }
+
+    fn write_audio_file<'a>(
+        &'a self,
+        _filename: String,
+        _bytes: &'a [i16],
+    ) -> BoxFuture<'a, Result<(), Error>> {
+        Box::pin(async { Ok(()) })
+    }
+}
@@ -1,60 +1,62 @@
 use anywho::Error;
 use elevenlabs_stt::{ElevenLabsSTTClient, STTResponse, models::elevanlabs_models::SCRIBE_V1};
-use hound::{SampleFormat, WavSpec, WavWriter};
+use futures::future::BoxFuture;
 
 use crate::domain::{
     ports::stt::{Stt, SttPayload},
-    utils::Convert,
+    utils::audio::codec::{Codec, wav::Wav},
 };
 
 #[derive(Clone)]
 pub struct ScribeAdapter {
     elevenlab_client: ElevenLabsSTTClient,
-    spec: WavSpec,
+    wav: Wav,
 }
 
 impl ScribeAdapter {
     pub fn new(api_key: String) -> Self {
         ScribeAdapter {
             elevenlab_client: ElevenLabsSTTClient::new(api_key),
-            spec: WavSpec {
-                channels: 1,
-                sample_rate: 16000,
-                bits_per_sample: 16,
-                sample_format: SampleFormat::Int,
-            },
+            wav: Wav::default(),
         }
     }
 }
 
 impl Stt for ScribeAdapter {
-    async fn execute(&self, bytes: &Vec<i16>) -> Result<SttPayload, Error> {
-        let bytes = Convert::i16_to_i8(bytes, self.spec)?;
+    fn execute<'a>(
+        &'a self,
+        bytes: &'a [i16],
+        language_code: &'a str,
+    ) -> BoxFuture<'a, Result<SttPayload, Error>> {
+        Box::pin(async move {
+            let bytes = self.wav.encode(bytes)?;
 
-        let response = self
-            .elevenlab_client
-            .speech_to_text(bytes)
-            .model(SCRIBE_V1)
-            .language_code("fra")
-            .diarize(true)
-            .execute()
-            .await
-            .map_err(|err| Error::msg(err.to_string()))
-            .map(SttPayload::from);
+            let response = self
+                .elevenlab_client
+                .speech_to_text(bytes)
+                .model(SCRIBE_V1)
+                .language_code(language_code)
+                .diarize(true)
+                .execute()
+                .await
+                .map_err(|err| Error::msg(err.to_string()))
+                .map(SttPayload::from);
 
-        println!("Response: {:?}", response);
+            println!("Response: {:?}", response);
 
-        response
+            response
+        })
     }
 
-    async fn write_audio_file(&self, filename: String, bytes: &Vec<i16>) -> Result<(), Error> {
-        let mut writer = WavWriter::create(filename, self.spec)?;
-        for sample in bytes {
-            writer.write_sample(*sample)?;
-        }
-
-        let _ = writer.finalize();
-        Ok(())
+    fn write_audio_file<'a>(
+        &'a self,
+        filename: String,
+        bytes: &'a [i16],
+    ) -> BoxFuture<'a, Result<(), Error>> {
+        Box::pin(async move {
+            tokio::fs::write(filename, self.wav.encode(bytes)?).await?;
+            Ok(())
+        })
     }
 }
 
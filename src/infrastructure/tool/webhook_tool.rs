@@ -0,0 +1,82 @@
+use std::path::Path;
+
+use anywho::Error;
+use reqwest::{Client, header::CONTENT_TYPE};
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::domain::ports::tool::{Tool, ToolFuture};
+
+/// A tool whose arguments are POSTed as JSON to an HTTP endpoint, the JSON
+/// response being handed back to the model.
+#[derive(Debug, Clone, Deserialize)]
+pub struct WebhookTool {
+    pub name: String,
+    pub description: String,
+    pub parameters: Value,
+    pub url: String,
+
+    #[serde(skip)]
+    client: Client,
+}
+
+impl WebhookTool {
+    pub fn new(name: String, description: String, parameters: Value, url: String) -> Self {
+        Self {
+            name,
+            description,
+            parameters,
+            url,
+            client: Client::new(),
+        }
+    }
+
+    /// Reads a JSON array of `{ name, description, parameters, url }` definitions.
+    pub fn load_file(path: &Path) -> Result<Vec<WebhookTool>, Error> {
+        let content = std::fs::read_to_string(path)?;
+        let tools = serde_json::from_str::<Vec<WebhookTool>>(&content)?;
+
+        Ok(tools)
+    }
+}
+
+impl Tool for WebhookTool {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn description(&self) -> &str {
+        &self.description
+    }
+
+    fn parameters(&self) -> Value {
+        self.parameters.clone()
+    }
+
+    fn call(&self, arguments: Value) -> ToolFuture {
+        let client = self.client.clone();
+        let url = self.url.clone();
+        let name = self.name.clone();
+
+        Box::pin(async move {
+            let response = client
+                .post(url)
+                .header(CONTENT_TYPE, "application/json")
+                .body(serde_json::to_vec(&arguments)?)
+                .send()
+                .await?;
+
+            let status = response.status();
+            let body = response.text().await?;
+            if !status.is_success() {
+                return Err(Error::msg(format!(
+                    "Tool {} failed ({}): {}",
+                    name, status, body
+                )));
+            }
+
+            // plain text answers are fine too, the model only needs something to read
+            Ok(serde_json::from_str::<Value>(&body).unwrap_or(Value::String(body)))
+        })
+    }
+}
@@ -0,0 +1,2 @@
+pub mod elevenlabs_adapter;
+pub mod mock_tts;
@@ -0,0 +1,92 @@
+use anywho::Error;
+use futures::future::BoxFuture;
+use reqwest::{Client, header::CONTENT_TYPE};
+use serde::Serialize;
+
+use crate::domain::{
+    ports::tts::Tts,
+    utils::{
+        audio::{
+            codec::{Codec, l16::L16Le},
+            resampler::Resampler,
+        },
+        units::SampleRate,
+    },
+};
+
+const ELEVENLABS_API_URL: &str = "https://api.elevenlabs.io/v1";
+
+#[derive(Clone)]
+pub struct ElevenLabsTtsAdapter {
+    client: Client,
+    api_key: String,
+    voice_id: String,
+    model_id: String,
+    /// Rate of the `pcm_*` output format requested, resampled to the pipeline's.
+    sample_rate: SampleRate,
+}
+
+impl ElevenLabsTtsAdapter {
+    pub fn new(api_key: String, voice_id: String, model_id: String) -> Self {
+        ElevenLabsTtsAdapter {
+            client: Client::new(),
+            api_key,
+            voice_id,
+            model_id,
+            sample_rate: SampleRate::PIPELINE,
+        }
+    }
+
+    pub fn with_sample_rate(mut self, sample_rate: SampleRate) -> Self {
+        self.sample_rate = sample_rate;
+        self
+    }
+}
+
+impl Tts for ElevenLabsTtsAdapter {
+    fn synthesize<'a>(
+        &'a self,
+        text: &'a str,
+        voice_id: Option<&'a str>,
+    ) -> BoxFuture<'a, Result<Vec<i16>, Error>> {
+        Box::pin(async move {
+            let mut resampler = Resampler::new(self.sample_rate, SampleRate::PIPELINE)?;
+            let body = serde_json::to_vec(&SpeechRequest {
+                text,
+                model_id: &self.model_id,
+            })?;
+
+            let response = self
+                .client
+                .post(format!(
+                    "{}/text-to-speech/{}?output_format=pcm_{}",
+                    ELEVENLABS_API_URL,
+                    voice_id.unwrap_or(&self.voice_id),
+                    self.sample_rate.0
+                ))
+                .header("xi-api-key", &self.api_key)
+                .header(CONTENT_TYPE, "application/json")
+                .body(body)
+                .send()
+                .await?;
+
+            let status = response.status();
+            if !status.is_success() {
+                let message = response.text().await.unwrap_or_default();
+                return Err(Error::msg(format!(
+                    "ElevenLabs TTS failed ({}): {}",
+                    status, message
+                )));
+            }
+
+            let bytes = response.bytes().await?;
+            Ok(resampler.resample(&L16Le.decode(&bytes)?))
+        })
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct SpeechRequest<'a> {
+    text: &'a str,
+    model_id: &'a str,
+}
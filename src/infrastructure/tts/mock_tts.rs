@@ -0,0 +1,84 @@
+use std::{sync::Arc, time::Duration};
+
+use anywho::Error;
+use futures::future::BoxFuture;
+use tokio::{sync::Mutex, time::sleep};
+
+use crate::domain::ports::tts::Tts;
+
+/// Renders text as a square wave, one tone per character, so the audio only
+/// depends on the text.
+#[derive(Debug, Clone)]
+pub struct MockTts {
+    latency: Duration,
+    failures: Vec<String>,
+    texts: Arc<Mutex<Vec<String>>>,
+}
+
+impl MockTts {
+    /// 10 ms of audio per character.
+    pub const SAMPLES_PER_CHAR: usize = 160;
+    const AMPLITUDE: i16 = 4000;
+
+    pub fn new() -> Self {
+        Self {
+            latency: Duration::ZERO,
+            failures: Vec::new(),
+            texts: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    pub fn with_latency(mut self, latency: Duration) -> Self {
+        self.latency = latency;
+        self
+    }
+
+    /// Makes the synthesis of `text` fail.
+    pub fn failing_on(mut self, text: &str) -> Self {
+        self.failures.push(text.to_string());
+        self
+    }
+
+    /// The audio rendered for `text`.
+    pub fn render(text: &str) -> Vec<i16> {
+        text.chars()
+            .flat_map(|char| {
+                let half_period = 8 + char as usize % 32;
+                (0..Self::SAMPLES_PER_CHAR).map(move |idx| match (idx / half_period) % 2 {
+                    0 => Self::AMPLITUDE,
+                    _ => -Self::AMPLITUDE,
+                })
+            })
+            .collect()
+    }
+
+    /// Every text synthesized, in call order.
+    pub async fn texts(&self) -> Vec<String> {
+        self.texts.lock().await.clone()
+    }
+}
+
+impl Default for MockTts {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Tts for MockTts {
+    fn synthesize<'a>(
+        &'a self,
+        text: &'a str,
+        _voice_id: Option<&'a str>,
+    ) -> BoxFuture<'a, Result<Vec<i16>, Error>> {
+        Box::pin(async move {
+            sleep(self.latency).await;
+            self.texts.lock().await.push(text.to_string());
+
+            if self.failures.iter().any(|failure| failure == text) {
+                return Err(Error::msg(format!("MockTts: failed on {:?}", text)));
+            }
+
+            Ok(Self::render(text))
+        })
+    }
+}
@@ -0,0 +1,3 @@
+pub mod semantic_turn_detector;
+pub mod silence_turn_detector;
+pub mod turn_classifier;
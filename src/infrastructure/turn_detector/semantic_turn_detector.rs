@@ -0,0 +1,111 @@
+use std::sync::Arc;
+
+use crate::{
+    domain::{
+        entities::agent_profile::VadSettings, ports::turn_detector::TurnDetector,
+        utils::units::Millis,
+    },
+    infrastructure::turn_detector::turn_classifier::{TurnClassifier, ending, words},
+};
+
+/// Words a sentence does not end on, in French and in English: conjunctions,
+/// articles, prepositions, possessives and hesitations. Words that are also
+/// verbs or adverbs ("il y en a", "I think so") are left out.
+const TRAILING_WORDS: &[&str] = &[
+    "et", "ou", "mais", "donc", "car", "ni", "puis", "que", "qu'", "qui", "quand", "si", "parce",
+    "comme", "le", "la", "les", "l'", "un", "une", "des", "du", "de", "d'", "au", "aux", "à", "en",
+    "dans", "pour", "par", "sur", "avec", "sans", "chez", "vers", "mon", "ma", "mes", "ton", "ta",
+    "tes", "son", "sa", "ses", "notre", "nos", "votre", "vos", "leur", "leurs", "ce", "cet",
+    "cette", "c'est", "euh", "heu", "bah", "and", "or", "but", "because", "the", "an", "of", "to",
+    "in", "at", "for", "with", "from", "my", "your", "our", "their", "is", "uh", "um",
+];
+
+/// Endings of a caller trailing off rather than finishing.
+const TRAILING_ENDINGS: &[&str] = &["…", ",", "-", ":"];
+
+/// Moves the end of the turn away from the fixed full stop with what the
+/// speculative STT heard: a finished sentence ends it after `complete_ms`, a
+/// sentence left hanging ("mon numéro c'est le…") waits up to `incomplete_ms`.
+///
+/// Without a classifier, the final punctuation and the last word decide.
+#[derive(Debug, Clone)]
+pub struct SemanticTurnDetector {
+    complete_ms: Millis,
+    incomplete_ms: Millis,
+    full_stop_ms: Millis,
+    classifier: Option<Arc<TurnClassifier>>,
+}
+
+impl SemanticTurnDetector {
+    pub fn new(complete_ms: Millis, incomplete_ms: Millis) -> Self {
+        Self {
+            complete_ms,
+            incomplete_ms,
+            full_stop_ms: VadSettings::default().full_stop_ms,
+            classifier: None,
+        }
+    }
+
+    pub fn with_classifier(mut self, classifier: Arc<TurnClassifier>) -> Self {
+        self.classifier = Some(classifier);
+        self
+    }
+
+    /// Probability that the caller is done talking.
+    pub fn completion(&self, transcript: &str) -> f32 {
+        match &self.classifier {
+            Some(classifier) => classifier.probability(transcript),
+            None => heuristic(transcript),
+        }
+    }
+}
+
+impl Default for SemanticTurnDetector {
+    fn default() -> Self {
+        Self::new(Millis(500), Millis(4000))
+    }
+}
+
+impl TurnDetector for SemanticTurnDetector {
+    fn configure(&mut self, settings: &VadSettings) {
+        self.full_stop_ms = settings.full_stop_ms;
+    }
+
+    fn max_silence_ms(&self) -> Millis {
+        self.incomplete_ms.max(self.full_stop_ms)
+    }
+
+    fn silence_ms(&self, transcript: Option<&str>) -> Millis {
+        let Some(transcript) = transcript else {
+            return self.full_stop_ms;
+        };
+
+        // an undecided transcript keeps the fixed full stop, certainty moves
+        // it all the way to one of the bounds
+        let completion = self.completion(transcript).clamp(0.0, 1.0);
+        let (target, weight) = match completion >= 0.5 {
+            true => (self.complete_ms, (completion - 0.5) * 2.0),
+            false => (self.incomplete_ms, (0.5 - completion) * 2.0),
+        };
+
+        let full_stop = self.full_stop_ms.0 as f32;
+        Millis((full_stop + (target.0 as f32 - full_stop) * weight).round() as u64)
+    }
+}
+
+fn heuristic(transcript: &str) -> f32 {
+    let words = words(transcript);
+    let Some(last) = words.last() else {
+        return 0.5;
+    };
+
+    let ending = ending(transcript);
+    if TRAILING_ENDINGS.contains(&ending.as_str()) || TRAILING_WORDS.contains(&last.as_str()) {
+        return 0.1;
+    }
+
+    match ending.as_str() {
+        "." | "?" | "!" => 0.9,
+        _ => 0.5,
+    }
+}
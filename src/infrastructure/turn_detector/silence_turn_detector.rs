@@ -0,0 +1,37 @@
+use crate::domain::{
+    entities::agent_profile::VadSettings, ports::turn_detector::TurnDetector, utils::units::Millis,
+};
+
+/// Ends the turn after the fixed full stop of the VAD, whatever was said.
+#[derive(Debug, Clone)]
+pub struct SilenceTurnDetector {
+    full_stop_ms: Millis,
+}
+
+impl SilenceTurnDetector {
+    pub fn new() -> Self {
+        Self {
+            full_stop_ms: VadSettings::default().full_stop_ms,
+        }
+    }
+}
+
+impl Default for SilenceTurnDetector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TurnDetector for SilenceTurnDetector {
+    fn configure(&mut self, settings: &VadSettings) {
+        self.full_stop_ms = settings.full_stop_ms;
+    }
+
+    fn max_silence_ms(&self) -> Millis {
+        self.full_stop_ms
+    }
+
+    fn silence_ms(&self, _transcript: Option<&str>) -> Millis {
+        self.full_stop_ms
+    }
+}
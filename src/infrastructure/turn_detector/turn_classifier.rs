@@ -0,0 +1,79 @@
+use std::{collections::HashMap, path::Path};
+
+use anywho::Error;
+use serde::Deserialize;
+
+/// Logistic regression telling a finished sentence from one the caller is still
+/// building, over the final punctuation and the last words of the transcript.
+///
+/// Weights are trained offline and loaded from JSON, keyed by feature:
+/// `{"bias": 0.2, "weights": {"end:.": 1.8, "word:et": -3.1, "bigram:c'est le": -2.4}}`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct TurnClassifier {
+    bias: f32,
+    weights: HashMap<String, f32>,
+}
+
+impl TurnClassifier {
+    pub fn new(bias: f32, weights: HashMap<String, f32>) -> Self {
+        Self { bias, weights }
+    }
+
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        let content = std::fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&content)?)
+    }
+
+    /// Probability that the caller is done talking.
+    pub fn probability(&self, transcript: &str) -> f32 {
+        let score = self.bias
+            + features(transcript)
+                .iter()
+                .filter_map(|feature| self.weights.get(feature))
+                .sum::<f32>();
+
+        1.0 / (1.0 + (-score).exp())
+    }
+}
+
+/// `end:<punctuation>`, `word:<last word>` and `bigram:<last two words>`,
+/// unknown features weigh nothing.
+pub fn features(transcript: &str) -> Vec<String> {
+    let mut features = vec![format!("end:{}", ending(transcript))];
+
+    let words = words(transcript);
+    if let Some(last) = words.last() {
+        features.push(format!("word:{}", last));
+    }
+    if let [.., previous, last] = words.as_slice() {
+        features.push(format!("bigram:{} {}", previous, last));
+    }
+
+    features
+}
+
+/// Lowercased words, punctuation stripped but elisions kept (`c'est`, `l'`).
+pub fn words(transcript: &str) -> Vec<String> {
+    transcript
+        .split_whitespace()
+        .map(|word| {
+            word.replace('’', "'")
+                .trim_matches(|c: char| !c.is_alphanumeric() && c != '\'')
+                .to_lowercase()
+        })
+        .filter(|word| !word.is_empty())
+        .collect()
+}
+
+/// Final punctuation of the transcript, `…` for a trailing ellipsis, `none` without any.
+pub fn ending(transcript: &str) -> String {
+    let text = transcript.trim_end();
+    if text.ends_with("...") {
+        return "…".to_string();
+    }
+
+    match text.chars().last() {
+        Some(c) if !c.is_alphanumeric() && c != '\'' => c.to_string(),
+        _ => "none".to_string(),
+    }
+}
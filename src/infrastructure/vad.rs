@@ -1 +1,5 @@
+pub mod gmm_vad;
 pub mod local_vad;
+pub mod noise_floor;
+pub mod silero_vad;
+pub mod speech_segmenter;
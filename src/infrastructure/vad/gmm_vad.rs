@@ -0,0 +1,621 @@
+use anywho::Error;
+
+use crate::{
+    domain::{
+        entities::{agent_profile::VadSettings, audio_buffer::AudioBuffer},
+        ports::vad::{Vad, VadEvent},
+        utils::units::{Millis, SampleRate},
+    },
+    infrastructure::vad::speech_segmenter::SpeechSegmenter,
+};
+
+// Floating point port of the WebRTC VAD (common_audio/vad). The tables keep the
+// fixed point values of the reference implementation, in the Q format noted
+// beside them, and are scaled when read.
+
+const CHANNELS: usize = 6;
+const GAUSSIANS: usize = 2;
+const TABLE: usize = CHANNELS * GAUSSIANS;
+
+/// Frame lengths the thresholds are tuned for, in ms.
+const FRAME_MS: [Millis; 3] = [Millis(10), Millis(20), Millis(30)];
+
+// Split filters, Q15 for the filter bank and Q13 for the 16 kHz downsampling.
+const ALL_PASS_COEFS_Q15: [i16; 2] = [20972, 5571];
+const DOWNSAMPLING_COEFS_Q13: [i16; 2] = [5243, 1392];
+// High pass removing 0 - 80 Hz from the lowest band, Q14.
+const HP_ZERO_COEFS: [i16; 3] = [6631, -13262, 6631];
+const HP_POLE_COEFS: [i16; 3] = [16384, -7756, 5620];
+/// Added to the log energy of each band, Q4.
+const OFFSET_VECTOR: [i16; CHANNELS] = [368, 368, 272, 176, 176, 176];
+const MIN_ENERGY: f32 = 10.0;
+
+const SPECTRUM_WEIGHT: [i16; CHANNELS] = [6, 8, 10, 12, 14, 16];
+const NOISE_UPDATE_CONST: i16 = 655; // Q15
+const SPEECH_UPDATE_CONST: i16 = 6554; // Q15
+const BACK_ETA: i16 = 154; // Q8
+const MINIMUM_DIFFERENCE: [i16; CHANNELS] = [544, 544, 576, 576, 576, 576]; // Q5
+const MAXIMUM_SPEECH: [i16; CHANNELS] = [11392, 11392, 11520, 11520, 11520, 11520]; // Q7
+const MINIMUM_MEAN: [i16; GAUSSIANS] = [640, 768]; // Q7
+const MAXIMUM_NOISE: [i16; CHANNELS] = [9216, 9088, 8960, 8832, 8704, 8576]; // Q7
+const MIN_STD: i16 = 384; // Q7
+const MAX_SPEECH_FRAMES: u32 = 6;
+
+// Initial models, the first gaussian of every channel then the second one. Q7.
+const NOISE_DATA_WEIGHTS: [i16; TABLE] = [34, 62, 72, 66, 53, 25, 94, 66, 56, 62, 75, 103];
+const SPEECH_DATA_WEIGHTS: [i16; TABLE] = [48, 82, 45, 87, 50, 47, 80, 46, 83, 41, 78, 81];
+const NOISE_DATA_MEANS: [i16; TABLE] = [
+    6738, 4892, 7065, 6715, 6771, 3369, 7646, 3863, 7820, 7266, 5020, 4362,
+];
+const SPEECH_DATA_MEANS: [i16; TABLE] = [
+    8306, 10085, 10078, 11823, 11843, 6309, 9473, 9571, 10879, 7581, 8180, 7483,
+];
+const NOISE_DATA_STDS: [i16; TABLE] = [378, 1064, 493, 582, 688, 593, 474, 697, 475, 688, 421, 455];
+const SPEECH_DATA_STDS: [i16; TABLE] = [
+    555, 505, 567, 524, 585, 1231, 509, 828, 492, 1540, 1079, 850,
+];
+
+/// Hangover lengths and likelihood ratio thresholds of each aggressiveness mode,
+/// for 10, 20 and 30 ms frames.
+struct ModeThresholds {
+    over_hang_max_1: [u32; 3],
+    over_hang_max_2: [u32; 3],
+    local: [i16; 3],
+    global: [i16; 3],
+}
+
+const MODES: [ModeThresholds; 4] = [
+    // 0, quality
+    ModeThresholds {
+        over_hang_max_1: [8, 4, 3],
+        over_hang_max_2: [14, 7, 5],
+        local: [24, 21, 24],
+        global: [57, 48, 57],
+    },
+    // 1, low bitrate
+    ModeThresholds {
+        over_hang_max_1: [8, 4, 3],
+        over_hang_max_2: [14, 7, 5],
+        local: [37, 32, 37],
+        global: [100, 80, 100],
+    },
+    // 2, aggressive
+    ModeThresholds {
+        over_hang_max_1: [6, 3, 2],
+        over_hang_max_2: [9, 5, 3],
+        local: [82, 78, 82],
+        global: [285, 260, 285],
+    },
+    // 3, very aggressive
+    ModeThresholds {
+        over_hang_max_1: [6, 3, 2],
+        over_hang_max_2: [9, 5, 3],
+        local: [94, 94, 94],
+        global: [1100, 1050, 1100],
+    },
+];
+
+fn q(value: i16, bits: u32) -> f32 {
+    value as f32 / (1 << bits) as f32
+}
+
+fn table(values: &[i16; TABLE], bits: u32) -> [f32; TABLE] {
+    values.map(|value| q(value, bits))
+}
+
+/// WebRTC style voice activity detection: log energies of six sub-bands scored
+/// against a two gaussians speech model and noise model, both adapting to the line.
+///
+/// Dependency free and cheap enough to run on every session. Aggressiveness goes
+/// from 0 (flags the most speech) to 3 (flags the least), frames last 10, 20 or 30 ms.
+/// The noise model takes a few hundred ms to adapt to a new line, modes 0 and 1
+/// flag stationary hiss as speech until then.
+#[derive(Debug, Clone)]
+pub struct GmmVadAdapter {
+    segmenter: SpeechSegmenter,
+    detector: GmmDetector,
+}
+
+impl GmmVadAdapter {
+    pub fn new(mode: u8, frame_ms: Millis) -> Result<Self, Error> {
+        if mode as usize >= MODES.len() {
+            return Err(Error::msg(format!(
+                "GMM VAD aggressiveness must be between 0 and 3, got {}",
+                mode
+            )));
+        }
+
+        let Some(frame) = FRAME_MS.iter().position(|&ms| ms == frame_ms) else {
+            return Err(Error::msg(format!(
+                "GMM VAD frames last 10, 20 or 30 ms, got {}",
+                frame_ms
+            )));
+        };
+
+        Ok(Self {
+            segmenter: SpeechSegmenter::new(SampleRate::PIPELINE.samples(frame_ms)),
+            detector: GmmDetector::new(mode as usize, frame),
+        })
+    }
+}
+
+impl Default for GmmVadAdapter {
+    fn default() -> Self {
+        Self::new(2, Millis(30)).expect("Valid default GMM VAD mode")
+    }
+}
+
+impl Vad for GmmVadAdapter {
+    /// The aggressiveness of `GMM_VAD_MODE` replaces the profile `threshold`,
+    /// and the frame length stays the one the adapter was built with.
+    fn configure(&mut self, settings: &VadSettings) {
+        self.segmenter.configure(settings);
+    }
+
+    fn process_audio(&mut self, audio_buffer: &mut AudioBuffer) -> VadEvent {
+        let detector = &mut self.detector;
+        self.segmenter
+            .process(audio_buffer, |frame| detector.is_speech(frame))
+    }
+
+    /// Classifies `bytes` on their own, from the initial models.
+    fn is_speech(&self, bytes: &[i16]) -> bool {
+        let mut detector = GmmDetector::new(self.detector.mode, self.detector.frame);
+        let frame_size = SampleRate::PIPELINE
+            .samples(FRAME_MS[self.detector.frame])
+            .as_usize();
+
+        bytes
+            .chunks_exact(frame_size)
+            .any(|frame| detector.is_speech(frame))
+    }
+}
+
+#[derive(Debug, Clone)]
+struct GmmDetector {
+    mode: usize,
+    /// Index of the frame length in `FRAME_MS`.
+    frame: usize,
+    filter_bank: FilterBank,
+
+    noise_means: [f32; TABLE],
+    speech_means: [f32; TABLE],
+    noise_stds: [f32; TABLE],
+    speech_stds: [f32; TABLE],
+    minimum: [MinimumTracker; CHANNELS],
+
+    frame_counter: u32,
+    over_hang: u32,
+    num_of_speech: u32,
+}
+
+impl GmmDetector {
+    fn new(mode: usize, frame: usize) -> Self {
+        Self {
+            mode,
+            frame,
+            filter_bank: FilterBank::default(),
+            noise_means: table(&NOISE_DATA_MEANS, 7),
+            speech_means: table(&SPEECH_DATA_MEANS, 7),
+            noise_stds: table(&NOISE_DATA_STDS, 7),
+            speech_stds: table(&SPEECH_DATA_STDS, 7),
+            minimum: Default::default(),
+            frame_counter: 0,
+            over_hang: 0,
+            num_of_speech: 0,
+        }
+    }
+
+    /// `frame` is 16 kHz audio, the models work on 8 kHz.
+    fn is_speech(&mut self, frame: &[i16]) -> bool {
+        let samples = self.filter_bank.downsample(frame);
+        let (features, total_energy) = self.filter_bank.features(&samples);
+
+        let speech = total_energy > MIN_ENERGY && self.gmm_probability(&features);
+        self.hangover(speech)
+    }
+
+    /// Likelihood ratio tests of the frame, then adaptation of the models to it.
+    fn gmm_probability(&mut self, features: &[f32; CHANNELS]) -> bool {
+        let thresholds = &MODES[self.mode];
+        let local_test = thresholds.local[self.frame] as f32;
+        let global_test = thresholds.global[self.frame] as f32;
+
+        let mut speech = false;
+        let mut sum_log_likelihood_ratios = 0.0;
+        let mut delta_noise = [0.0; TABLE];
+        let mut delta_speech = [0.0; TABLE];
+        // probability of each gaussian given its model
+        let mut noise_posterior = [0.0; TABLE];
+        let mut speech_posterior = [0.0; TABLE];
+
+        for channel in 0..CHANNELS {
+            let mut noise_probability = [0.0; GAUSSIANS];
+            let mut speech_probability = [0.0; GAUSSIANS];
+
+            for k in 0..GAUSSIANS {
+                let gaussian = channel + k * CHANNELS;
+
+                let (probability, delta) = gaussian_probability(
+                    features[channel],
+                    self.noise_means[gaussian],
+                    self.noise_stds[gaussian],
+                );
+                noise_probability[k] = q(NOISE_DATA_WEIGHTS[gaussian], 7) * probability;
+                delta_noise[gaussian] = delta;
+
+                let (probability, delta) = gaussian_probability(
+                    features[channel],
+                    self.speech_means[gaussian],
+                    self.speech_stds[gaussian],
+                );
+                speech_probability[k] = q(SPEECH_DATA_WEIGHTS[gaussian], 7) * probability;
+                delta_speech[gaussian] = delta;
+            }
+
+            let h0: f32 = noise_probability.iter().sum();
+            let h1: f32 = speech_probability.iter().sum();
+
+            // 2^-28 is the smallest likelihood of the Q27 reference
+            let log_likelihood_ratio =
+                h1.max(2f32.powi(-28)).log2() - h0.max(2f32.powi(-28)).log2();
+            sum_log_likelihood_ratios += log_likelihood_ratio * SPECTRUM_WEIGHT[channel] as f32;
+
+            if log_likelihood_ratio * 4.0 > local_test {
+                speech = true;
+            }
+
+            if h0 >= 2f32.powi(-15) {
+                noise_posterior[channel] = noise_probability[0] / h0;
+                noise_posterior[channel + CHANNELS] = 1.0 - noise_posterior[channel];
+            } else {
+                noise_posterior[channel] = 1.0;
+            }
+
+            if h1 >= 2f32.powi(-15) {
+                speech_posterior[channel] = speech_probability[0] / h1;
+                speech_posterior[channel + CHANNELS] = 1.0 - speech_posterior[channel];
+            }
+        }
+
+        speech |= sum_log_likelihood_ratios >= global_test;
+
+        let mut max_speech = q(12800, 7);
+        for channel in 0..CHANNELS {
+            let feature_minimum =
+                self.minimum[channel].update(features[channel], self.frame_counter);
+            let noise_global_mean =
+                weighted_average(&self.noise_means, channel, &NOISE_DATA_WEIGHTS);
+
+            for (k, minimum_mean) in MINIMUM_MEAN.iter().enumerate() {
+                let gaussian = channel + k * CHANNELS;
+                let noise_mean = self.noise_means[gaussian];
+                let speech_mean = self.speech_means[gaussian];
+
+                let mut new_noise_mean = noise_mean;
+                if !speech {
+                    new_noise_mean += noise_posterior[gaussian]
+                        * delta_noise[gaussian]
+                        * q(NOISE_UPDATE_CONST, 15);
+                }
+
+                // long term correction towards the minimum of the band
+                new_noise_mean += (feature_minimum - noise_global_mean) * q(BACK_ETA, 8);
+                self.noise_means[gaussian] = new_noise_mean
+                    .max((k + 5) as f32)
+                    .min((72 + k) as f32 - channel as f32);
+
+                if speech {
+                    let new_speech_mean = speech_mean
+                        + speech_posterior[gaussian]
+                            * delta_speech[gaussian]
+                            * q(SPEECH_UPDATE_CONST, 15);
+                    self.speech_means[gaussian] = new_speech_mean
+                        .max(q(*minimum_mean, 7))
+                        .min(max_speech + 5.0);
+
+                    let std = self.speech_stds[gaussian];
+                    let distance = features[channel] - speech_mean;
+                    let update = speech_posterior[gaussian]
+                        * (delta_speech[gaussian] * distance - 1.0)
+                        / (10.0 * std)
+                        / 4.0;
+                    self.speech_stds[gaussian] = (std + update).max(q(MIN_STD, 7));
+                } else {
+                    let std = self.noise_stds[gaussian];
+                    let distance = features[channel] - noise_mean;
+                    let update = noise_posterior[gaussian]
+                        * (delta_noise[gaussian] * distance - 1.0)
+                        / 1024.0
+                        / std;
+                    self.noise_stds[gaussian] = (std + update).max(q(MIN_STD, 7));
+                }
+            }
+
+            // pulling the models apart when they get too close
+            let mut noise_global_mean =
+                weighted_average(&self.noise_means, channel, &NOISE_DATA_WEIGHTS);
+            let mut speech_global_mean =
+                weighted_average(&self.speech_means, channel, &SPEECH_DATA_WEIGHTS);
+
+            let difference = speech_global_mean - noise_global_mean;
+            let minimum_difference = q(MINIMUM_DIFFERENCE[channel], 5);
+            if difference < minimum_difference {
+                let gap = minimum_difference - difference;
+                speech_global_mean = shift_means(
+                    &mut self.speech_means,
+                    channel,
+                    gap * 13.0 / 16.0,
+                    &SPEECH_DATA_WEIGHTS,
+                );
+                noise_global_mean = shift_means(
+                    &mut self.noise_means,
+                    channel,
+                    -gap * 3.0 / 16.0,
+                    &NOISE_DATA_WEIGHTS,
+                );
+            }
+
+            max_speech = q(MAXIMUM_SPEECH[channel], 7);
+            if speech_global_mean > max_speech {
+                shift_means(
+                    &mut self.speech_means,
+                    channel,
+                    max_speech - speech_global_mean,
+                    &SPEECH_DATA_WEIGHTS,
+                );
+            }
+
+            let max_noise = q(MAXIMUM_NOISE[channel], 7);
+            if noise_global_mean > max_noise {
+                shift_means(
+                    &mut self.noise_means,
+                    channel,
+                    max_noise - noise_global_mean,
+                    &NOISE_DATA_WEIGHTS,
+                );
+            }
+        }
+
+        self.frame_counter += 1;
+
+        speech
+    }
+
+    /// Keeps flagging speech for a few frames after it stops, longer after a long run.
+    fn hangover(&mut self, speech: bool) -> bool {
+        let thresholds = &MODES[self.mode];
+
+        if !speech {
+            self.num_of_speech = 0;
+            if self.over_hang > 0 {
+                self.over_hang -= 1;
+                return true;
+            }
+
+            return false;
+        }
+
+        self.num_of_speech += 1;
+        if self.num_of_speech > MAX_SPEECH_FRAMES {
+            self.num_of_speech = MAX_SPEECH_FRAMES;
+            self.over_hang = thresholds.over_hang_max_2[self.frame];
+        } else {
+            self.over_hang = thresholds.over_hang_max_1[self.frame];
+        }
+
+        true
+    }
+}
+
+/// `(1 / std) * exp(-(x - mean)^2 / (2 * std^2))` and `(x - mean) / std^2`.
+fn gaussian_probability(input: f32, mean: f32, std: f32) -> (f32, f32) {
+    let delta = (input - mean) / (std * std);
+    let exponent = delta * (input - mean) / 2.0;
+
+    // the reference rounds anything past this exponent to zero
+    let probability = match exponent < q(22005, 10) {
+        true => (-exponent).exp() / std,
+        false => 0.0,
+    };
+
+    (probability, delta)
+}
+
+fn weighted_average(means: &[f32; TABLE], channel: usize, weights: &[i16; TABLE]) -> f32 {
+    (0..GAUSSIANS)
+        .map(|k| means[channel + k * CHANNELS] * q(weights[channel + k * CHANNELS], 7))
+        .sum()
+}
+
+/// Moves both gaussians of `channel` by `offset` and returns the new weighted mean.
+fn shift_means(
+    means: &mut [f32; TABLE],
+    channel: usize,
+    offset: f32,
+    weights: &[i16; TABLE],
+) -> f32 {
+    for k in 0..GAUSSIANS {
+        means[channel + k * CHANNELS] += offset;
+    }
+
+    weighted_average(means, channel, weights)
+}
+
+/// Smoothed minimum of a band over the last 100 frames, the noise floor the
+/// noise model is pulled towards.
+#[derive(Debug, Clone)]
+struct MinimumTracker {
+    /// The 16 smallest values, ascending, with their age in frames.
+    smallest: [(f32, u32); 16],
+    mean_value: f32,
+}
+
+impl Default for MinimumTracker {
+    fn default() -> Self {
+        Self {
+            smallest: [(q(10000, 4), 0); 16],
+            mean_value: q(1600, 4),
+        }
+    }
+}
+
+impl MinimumTracker {
+    fn update(&mut self, feature: f32, frame_counter: u32) -> f32 {
+        for entry in self.smallest.iter_mut() {
+            entry.1 += 1;
+        }
+
+        // values older than 100 frames leave, larger ones move down
+        let mut kept: Vec<(f32, u32)> = self
+            .smallest
+            .iter()
+            .copied()
+            .filter(|(_, age)| *age <= 100)
+            .collect();
+        kept.resize(16, (q(10000, 4), 101));
+
+        if let Some(position) = kept.iter().position(|(value, _)| feature < *value) {
+            kept.insert(position, (feature, 1));
+        }
+
+        self.smallest.copy_from_slice(&kept[..16]);
+
+        let current_median = match frame_counter {
+            0 => q(1600, 4),
+            1 | 2 => self.smallest[0].0,
+            _ => self.smallest[2].0,
+        };
+
+        if frame_counter > 0 {
+            let alpha = match current_median < self.mean_value {
+                true => q(6553, 15),
+                false => q(32439, 15),
+            };
+            self.mean_value = alpha * self.mean_value + (1.0 - alpha) * current_median;
+        } else {
+            self.mean_value = current_median;
+        }
+
+        self.mean_value
+    }
+}
+
+/// Split filters carrying their state from frame to frame.
+#[derive(Debug, Clone, Default)]
+struct FilterBank {
+    downsampling: [f32; 2],
+    upper: [f32; 5],
+    lower: [f32; 5],
+    high_pass: [f32; 4],
+}
+
+impl FilterBank {
+    /// 16 kHz to 8 kHz through the two all-pass branches.
+    fn downsample(&mut self, frame: &[i16]) -> Vec<f32> {
+        let upper = all_pass(
+            frame.iter().step_by(2).map(|&sample| sample as f32),
+            q(DOWNSAMPLING_COEFS_Q13[0], 13),
+            &mut self.downsampling[0],
+        );
+        let lower = all_pass(
+            frame.iter().skip(1).step_by(2).map(|&sample| sample as f32),
+            q(DOWNSAMPLING_COEFS_Q13[1], 13),
+            &mut self.downsampling[1],
+        );
+
+        upper.iter().zip(lower.iter()).map(|(u, l)| u + l).collect()
+    }
+
+    /// Log energies of 80-250, 250-500, 500-1000, 1000-2000, 2000-3000 and
+    /// 3000-4000 Hz, with the total energy of the frame.
+    fn features(&mut self, samples: &[f32]) -> ([f32; CHANNELS], f32) {
+        let mut features = [0.0; CHANNELS];
+        let mut total_energy = 0.0;
+
+        let (hp_2000, lp_2000) = self.split(0, samples);
+
+        let (hp_3000, lp_3000) = self.split(1, &hp_2000);
+        features[5] = log_energy(&hp_3000, OFFSET_VECTOR[5], &mut total_energy);
+        features[4] = log_energy(&lp_3000, OFFSET_VECTOR[4], &mut total_energy);
+
+        let (hp_1000, lp_1000) = self.split(2, &lp_2000);
+        features[3] = log_energy(&hp_1000, OFFSET_VECTOR[3], &mut total_energy);
+
+        let (hp_500, lp_500) = self.split(3, &lp_1000);
+        features[2] = log_energy(&hp_500, OFFSET_VECTOR[2], &mut total_energy);
+
+        let (hp_250, lp_250) = self.split(4, &lp_500);
+        features[1] = log_energy(&hp_250, OFFSET_VECTOR[1], &mut total_energy);
+
+        let band_80_250 = self.high_pass(&lp_250);
+        features[0] = log_energy(&band_80_250, OFFSET_VECTOR[0], &mut total_energy);
+
+        (features, total_energy)
+    }
+
+    /// Halves the band and the sample rate, returning the upper and lower halves.
+    fn split(&mut self, band: usize, samples: &[f32]) -> (Vec<f32>, Vec<f32>) {
+        let upper = all_pass(
+            samples.iter().step_by(2).copied(),
+            q(ALL_PASS_COEFS_Q15[0], 15),
+            &mut self.upper[band],
+        );
+        let lower = all_pass(
+            samples.iter().skip(1).step_by(2).copied(),
+            q(ALL_PASS_COEFS_Q15[1], 15),
+            &mut self.lower[band],
+        );
+
+        upper
+            .iter()
+            .zip(lower.iter())
+            .map(|(u, l)| (u - l, u + l))
+            .unzip()
+    }
+
+    fn high_pass(&mut self, samples: &[f32]) -> Vec<f32> {
+        let state = &mut self.high_pass;
+
+        samples
+            .iter()
+            .map(|&sample| {
+                let zeros = q(HP_ZERO_COEFS[0], 14) * sample
+                    + q(HP_ZERO_COEFS[1], 14) * state[0]
+                    + q(HP_ZERO_COEFS[2], 14) * state[1];
+                state[1] = state[0];
+                state[0] = sample;
+
+                let output =
+                    zeros - q(HP_POLE_COEFS[1], 14) * state[2] - q(HP_POLE_COEFS[2], 14) * state[3];
+                state[3] = state[2];
+                state[2] = output;
+
+                output
+            })
+            .collect()
+    }
+}
+
+/// First order all-pass section, with the 1/2 gain of the reference filters.
+fn all_pass(input: impl Iterator<Item = f32>, coefficient: f32, state: &mut f32) -> Vec<f32> {
+    input
+        .map(|sample| {
+            let output = (*state + coefficient * sample) / 2.0;
+            *state = sample - 2.0 * coefficient * output;
+            output
+        })
+        .collect()
+}
+
+/// Energy of the band in dB plus its offset, both in the units of the models.
+fn log_energy(samples: &[f32], offset: i16, total_energy: &mut f32) -> f32 {
+    let energy: f32 = samples.iter().map(|sample| sample * sample).sum();
+    *total_energy += energy;
+
+    if energy < 1.0 {
+        return q(offset, 4);
+    }
+
+    10.0 * energy.log10() + q(offset, 4)
+}
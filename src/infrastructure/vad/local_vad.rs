@@ -1,84 +1,90 @@
-use crate::domain::{
-    entities::audio_buffer::AudioBuffer,
-    ports::vad::{Vad, VadEvent},
-    utils::{Utils, convert::Convert},
+use crate::{
+    domain::{
+        entities::{
+            agent_profile::{AdaptiveThreshold, VadSettings},
+            audio_buffer::AudioBuffer,
+        },
+        ports::vad::{Vad, VadEvent},
+        utils::{
+            Utils,
+            units::{Millis, SampleRate},
+        },
+    },
+    infrastructure::vad::{noise_floor::NoiseFloor, speech_segmenter::SpeechSegmenter},
 };
-use std::cmp::max;
 
 #[derive(Debug, Clone)]
 pub struct LocalVadAdapter {
-    frame_size: u64,
+    segmenter: SpeechSegmenter,
     threshold: f32,
-    full_stop_bytes: u64,
-    min_speech_bytes: u64,
+    /// Set in adaptive mode, replaces the fixed `threshold`.
+    noise_floor: Option<NoiseFloor>,
 }
 
 impl LocalVadAdapter {
     pub fn new() -> Self {
         Self {
             threshold: 800.0,
-            frame_size: Convert::ms_to_int16(32),
-            full_stop_bytes: Convert::ms_to_int16(2000),
-            min_speech_bytes: Convert::ms_to_int16(200),
+            segmenter: SpeechSegmenter::new(SampleRate::PIPELINE.samples(Millis(32))),
+            noise_floor: None,
         }
     }
 }
 
-impl Vad for LocalVadAdapter {
-    fn process_audio<'a>(&mut self, audio_buffer: &'a mut AudioBuffer) -> VadEvent {
-        while audio_buffer.user.len() as u64 >= (audio_buffer.cursor + self.frame_size) {
-            let range = audio_buffer.cursor..audio_buffer.cursor + self.frame_size;
-            audio_buffer.cursor += self.frame_size;
-            let frame = &audio_buffer.user[range.start as usize..range.end as usize];
-
-            let is_speech = self.is_speech(frame);
-
-            match (is_speech, audio_buffer.start, audio_buffer.end) {
-                (true, None, None) => {
-                    // speech started for the first time this turn
-                    let start = max(audio_buffer.cursor - 3 * self.frame_size, 0);
-                    audio_buffer.start = Some(start);
-
-                    return VadEvent::SpeechStarted;
-                }
-                (true, Some(_), None) => {} // speech is continuing no pause yet
-                (true, Some(_), Some(_)) => {
-                    // speech has paused but the user resume speacking
-                    audio_buffer.end = None;
-                    return VadEvent::SpeechResumed;
-                }
-                (false, None, None) => {} // the user still did not talk this turn
-                (false, Some(start), None) => {
-                    // the user paused a pipeline shall start
-                    if audio_buffer.cursor - start >= self.min_speech_bytes {
-                        let end = audio_buffer.cursor;
-                        audio_buffer.end = Some(end);
+impl Default for LocalVadAdapter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
-                        //return VadEvent::SpeechPaused(start, end);
-                    }
-                }
-                (false, Some(start), Some(end)) => {
-                    // the user is still pausing it may be a full stop
-                    if audio_buffer.cursor - end > self.full_stop_bytes {
-                        audio_buffer.start = None;
-                        audio_buffer.end = None;
+impl Vad for LocalVadAdapter {
+    fn configure(&mut self, settings: &VadSettings) {
+        self.threshold = settings.threshold;
+        self.segmenter
+            .set_frame_size(SampleRate::PIPELINE.samples(settings.frame_ms));
+        self.segmenter.configure(settings);
 
-                        return VadEvent::SpeechFullStop;
-                    }
+        // an agent switch keeps what was learnt of the line
+        self.noise_floor = match (settings.adaptive, self.noise_floor.take()) {
+            (Some(adaptive), Some(mut noise_floor)) => {
+                noise_floor.configure(adaptive, window_frames(&adaptive, settings));
+                Some(noise_floor)
+            }
+            (Some(adaptive), None) => Some(NoiseFloor::new(
+                adaptive,
+                window_frames(&adaptive, settings),
+            )),
+            (None, _) => None,
+        };
+    }
 
-                    if (audio_buffer.cursor - end) >= 2 * self.frame_size {
-                        return VadEvent::SpeechPaused(start, end + 2 * self.frame_size);
-                    } // letting 3 trailling frames after a pause
-                }
-                _ => panic!("End cannot exists without start index"),
+    fn process_audio(&mut self, audio_buffer: &mut AudioBuffer) -> VadEvent {
+        match &mut self.noise_floor {
+            Some(noise_floor) => self.segmenter.process(audio_buffer, |frame| {
+                noise_floor.is_speech(Utils::rms_energy(frame))
+            }),
+            None => {
+                let threshold = self.threshold;
+                self.segmenter
+                    .process(audio_buffer, |frame| Utils::rms_energy(frame) > threshold)
             }
         }
-
-        return VadEvent::WaitingMoreChunks;
     }
 
     fn is_speech(&self, bytes: &[i16]) -> bool {
         let energy = Utils::rms_energy(bytes);
-        return energy > self.threshold;
+        match &self.noise_floor {
+            Some(noise_floor) => energy > noise_floor.onset(),
+            None => energy > self.threshold,
+        }
     }
+
+    fn noise_floor(&self) -> Option<f32> {
+        self.noise_floor.as_ref().map(NoiseFloor::floor)
+    }
+}
+
+/// Frames of `settings.frame_ms` in the adaptive window.
+fn window_frames(adaptive: &AdaptiveThreshold, settings: &VadSettings) -> usize {
+    (adaptive.window_ms.0 / settings.frame_ms.0) as usize
 }
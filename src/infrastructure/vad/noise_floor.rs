@@ -0,0 +1,146 @@
+use std::collections::VecDeque;
+
+use crate::domain::entities::agent_profile::AdaptiveThreshold;
+
+/// Continuous speech past this many windows re-estimates the floor even when
+/// its energy keeps moving.
+const LONG_RUN_WINDOWS: usize = 5;
+
+/// Spread of the frame energies, in dB, under which a speech run is taken for
+/// a steady noise rather than a voice, whose syllables swing far wider.
+const STATIONARY_DB: f32 = 2.0;
+
+/// Background noise estimate of a line, the median RMS energy of its recent
+/// non-speech frames, and the speech decision derived from it.
+///
+/// The floor starts at `min_floor`, so a noisy line reads as speech until a
+/// window of steady sound has gone by and the floor caught up with it.
+#[derive(Debug, Clone)]
+pub struct NoiseFloor {
+    settings: AdaptiveThreshold,
+    capacity: usize,
+    energies: VecDeque<f32>,
+    floor: f32,
+    speaking: bool,
+    /// Energies of the last window of the current speech run.
+    run: VecDeque<f32>,
+    /// Length of the current speech run in frames.
+    run_frames: usize,
+}
+
+impl NoiseFloor {
+    /// `capacity` is the number of frames in `settings.window_ms`.
+    pub fn new(settings: AdaptiveThreshold, capacity: usize) -> Self {
+        Self {
+            settings,
+            capacity: capacity.max(1),
+            energies: VecDeque::new(),
+            floor: settings.min_floor,
+            speaking: false,
+            run: VecDeque::new(),
+            run_frames: 0,
+        }
+    }
+
+    /// Applies new margins and window, keeping the estimate of the line.
+    pub fn configure(&mut self, settings: AdaptiveThreshold, capacity: usize) {
+        self.settings = settings;
+        self.capacity = capacity.max(1);
+        while self.energies.len() > self.capacity {
+            self.energies.pop_front();
+        }
+        while self.run.len() > self.capacity {
+            self.run.pop_front();
+        }
+        self.update_floor();
+    }
+
+    pub fn floor(&self) -> f32 {
+        self.floor
+    }
+
+    pub fn onset(&self) -> f32 {
+        self.floor * db_to_ratio(self.settings.onset_db)
+    }
+
+    pub fn offset(&self) -> f32 {
+        self.floor * db_to_ratio(self.settings.offset_db)
+    }
+
+    /// Classifies a frame by its RMS energy, feeding the estimate when it is not speech.
+    pub fn is_speech(&mut self, energy: f32) -> bool {
+        self.speaking = match self.speaking {
+            true => energy > self.offset(),
+            false => energy > self.onset(),
+        };
+
+        if !self.speaking {
+            self.run.clear();
+            self.run_frames = 0;
+            self.push(energy);
+            return false;
+        }
+
+        if self.run.len() == self.capacity {
+            self.run.pop_front();
+        }
+        self.run.push_back(energy);
+        self.run_frames += 1;
+
+        // a steady sound over a whole window is the noise getting louder than
+        // the onset, and so is a very long run without a single pause
+        let steady = self.run.len() == self.capacity && is_stationary(&self.run);
+        if steady || self.run_frames >= self.capacity * LONG_RUN_WINDOWS {
+            self.energies = match steady {
+                true => self.run.clone(),
+                false => self
+                    .run
+                    .iter()
+                    .copied()
+                    .min_by(f32::total_cmp)
+                    .into_iter()
+                    .collect(),
+            };
+            self.update_floor();
+            self.run.clear();
+            self.run_frames = 0;
+            self.speaking = false;
+        }
+
+        true
+    }
+
+    fn push(&mut self, energy: f32) {
+        if self.energies.len() == self.capacity {
+            self.energies.pop_front();
+        }
+        self.energies.push_back(energy);
+        self.update_floor();
+    }
+
+    fn update_floor(&mut self) {
+        let mut sorted: Vec<f32> = self.energies.iter().copied().collect();
+        sorted.sort_by(f32::total_cmp);
+        self.floor = sorted
+            .get(sorted.len() / 2)
+            .copied()
+            .unwrap_or(self.settings.min_floor)
+            .max(self.settings.min_floor);
+    }
+}
+
+/// Whether the standard deviation of `energies` in dB is under `STATIONARY_DB`.
+fn is_stationary(energies: &VecDeque<f32>) -> bool {
+    let db: Vec<f32> = energies
+        .iter()
+        .map(|energy| 20.0 * energy.max(1.0).log10())
+        .collect();
+    let mean = db.iter().sum::<f32>() / db.len() as f32;
+    let variance = db.iter().map(|value| (value - mean).powi(2)).sum::<f32>() / db.len() as f32;
+
+    variance.sqrt() < STATIONARY_DB
+}
+
+fn db_to_ratio(db: f32) -> f32 {
+    10f32.powf(db / 20.0)
+}
@@ -0,0 +1,208 @@
+use std::{
+    path::Path,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicUsize, Ordering},
+    },
+};
+
+use anywho::Error;
+use ort::{
+    session::{Session, builder::GraphOptimizationLevel},
+    value::Tensor,
+};
+use tokio::runtime::{Handle, RuntimeFlavor};
+use tracing::warn;
+
+use crate::{
+    domain::{
+        entities::{agent_profile::VadSettings, audio_buffer::AudioBuffer},
+        ports::vad::{Vad, VadEvent},
+        utils::units::{SampleRate, Samples},
+    },
+    infrastructure::vad::speech_segmenter::SpeechSegmenter,
+};
+
+/// 32 ms at 16 kHz, the only window the model accepts at this rate.
+const WINDOW: usize = 512;
+/// Tail of the previous window the model expects in front of each new one.
+const CONTEXT: usize = 64;
+const STATE: usize = 2 * 128;
+
+/// The Silero VAD ONNX model (v5), loaded once and shared by every session.
+///
+/// Inference runs on CPU with onnxruntime, loaded at runtime from
+/// `ORT_DYLIB_PATH` or the library search path. The model is loaded in a few
+/// onnxruntime sessions so calls can run their frames side by side.
+#[derive(Clone)]
+pub struct SileroModel {
+    sessions: Arc<Vec<Mutex<Session>>>,
+    /// Session the next inference tries first, spreading the calls over the pool.
+    next: Arc<AtomicUsize>,
+}
+
+impl SileroModel {
+    /// Loads `sessions` copies of the model, at least one.
+    pub fn load(path: &Path, sessions: usize) -> Result<Self, Error> {
+        let sessions = (0..sessions.max(1))
+            .map(|_| {
+                Ok(Mutex::new(
+                    Session::builder()?
+                        .with_optimization_level(GraphOptimizationLevel::Level3)?
+                        .with_intra_threads(1)?
+                        .commit_from_file(path)?,
+                ))
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        Ok(Self {
+            sessions: Arc::new(sessions),
+            next: Arc::new(AtomicUsize::new(0)),
+        })
+    }
+
+    /// Speech probability of `window` (`CONTEXT + WINDOW` samples in [-1, 1]),
+    /// updating the recurrent `state`.
+    fn probability(&self, window: Vec<f32>, state: &mut [f32]) -> Result<f32, Error> {
+        let input = Tensor::from_array(([1, CONTEXT + WINDOW], window))?;
+        let state_in = Tensor::from_array(([2, 1, 128], state.to_vec()))?;
+        let sample_rate = Tensor::from_array(((), vec![SampleRate::PIPELINE.hz() as i64]))?;
+
+        // the first idle session, or the turn of this call when all are busy
+        let first = self.next.fetch_add(1, Ordering::Relaxed);
+        let count = self.sessions.len();
+        let idle =
+            (0..count).find_map(|offset| self.sessions[(first + offset) % count].try_lock().ok());
+
+        // the VAD port is synchronous, this keeps the other tasks of the
+        // worker running while the frame is inferred
+        run_blocking(|| {
+            let mut session = match idle {
+                Some(session) => session,
+                None => self.sessions[first % count]
+                    .lock()
+                    .map_err(|_| Error::msg("Silero session poisoned"))?,
+            };
+            let outputs = session.run(ort::inputs![
+                "input" => input,
+                "state" => state_in,
+                "sr" => sample_rate,
+            ])?;
+
+            let (_, probability) = outputs["output"].try_extract_tensor::<f32>()?;
+            let (_, state_out) = outputs["stateN"].try_extract_tensor::<f32>()?;
+            state.copy_from_slice(state_out);
+
+            probability
+                .first()
+                .copied()
+                .ok_or_else(|| Error::msg("Silero returned no probability"))
+        })
+    }
+}
+
+/// Runs `inference` with `block_in_place` on a multi-threaded runtime, inline
+/// anywhere else.
+fn run_blocking<T>(inference: impl FnOnce() -> T) -> T {
+    match Handle::try_current().map(|handle| handle.runtime_flavor()) {
+        Ok(RuntimeFlavor::MultiThread) => tokio::task::block_in_place(inference),
+        _ => inference(),
+    }
+}
+
+impl std::fmt::Debug for SileroModel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SileroModel").finish_non_exhaustive()
+    }
+}
+
+/// Neural voice activity detection, far less sensitive to line noise and quiet
+/// callers than the energy threshold of `LocalVadAdapter`.
+///
+/// Speech starts once the probability of a frame reaches `threshold` and lasts
+/// until it drops below `threshold - hysteresis`.
+#[derive(Debug, Clone)]
+pub struct SileroVadAdapter {
+    segmenter: SpeechSegmenter,
+    stream: SileroStream,
+}
+
+/// Recurrent state carried from one frame of the session to the next.
+#[derive(Debug, Clone)]
+struct SileroStream {
+    model: SileroModel,
+    threshold: f32,
+    hysteresis: f32,
+    state: Vec<f32>,
+    context: Vec<f32>,
+    speaking: bool,
+}
+
+impl SileroVadAdapter {
+    pub fn new(model: SileroModel, threshold: f32, hysteresis: f32) -> Self {
+        Self {
+            segmenter: SpeechSegmenter::new(Samples(WINDOW as u64)),
+            stream: SileroStream {
+                model,
+                threshold,
+                hysteresis,
+                state: vec![0.0; STATE],
+                context: vec![0.0; CONTEXT],
+                speaking: false,
+            },
+        }
+    }
+}
+
+impl SileroStream {
+    fn is_speech(&mut self, frame: &[i16]) -> bool {
+        let mut window = Vec::with_capacity(CONTEXT + WINDOW);
+        window.extend_from_slice(&self.context);
+        window.extend(frame.iter().map(|&sample| sample as f32 / 32768.0));
+        self.context.copy_from_slice(&window[WINDOW..]);
+
+        let probability = match self.model.probability(window, &mut self.state) {
+            Ok(probability) => probability,
+            Err(err) => {
+                // keeping the previous decision rather than cutting the caller
+                warn!("Silero inference failed: {}", err);
+                return self.speaking;
+            }
+        };
+
+        self.speaking = match self.speaking {
+            true => probability >= self.threshold - self.hysteresis,
+            false => probability >= self.threshold,
+        };
+
+        self.speaking
+    }
+}
+
+impl Vad for SileroVadAdapter {
+    /// Only the turn timings apply, speech is decided by `SILERO_THRESHOLD`
+    /// on the model probability and the profile `threshold` is ignored.
+    fn configure(&mut self, settings: &VadSettings) {
+        self.segmenter.configure(settings);
+    }
+
+    fn process_audio(&mut self, audio_buffer: &mut AudioBuffer) -> VadEvent {
+        let stream = &mut self.stream;
+        self.segmenter
+            .process(audio_buffer, |frame| stream.is_speech(frame))
+    }
+
+    /// Classifies `bytes` on their own, from a fresh recurrent state.
+    fn is_speech(&self, bytes: &[i16]) -> bool {
+        let mut stream = SileroStream {
+            state: vec![0.0; STATE],
+            context: vec![0.0; CONTEXT],
+            speaking: false,
+            ..self.stream.clone()
+        };
+
+        bytes
+            .chunks_exact(WINDOW)
+            .any(|frame| stream.is_speech(frame))
+    }
+}
@@ -0,0 +1,110 @@
+use crate::domain::{
+    entities::{agent_profile::VadSettings, audio_buffer::AudioBuffer},
+    ports::vad::VadEvent,
+    utils::units::{SampleRate, Samples},
+};
+
+/// Turns per-frame speech decisions into the events of a turn.
+///
+/// Detectors only differ in how they classify a frame, they share this walk
+/// over the user audio and the bookkeeping of `AudioBuffer::start` and `end`.
+#[derive(Debug, Clone)]
+pub struct SpeechSegmenter {
+    frame_size: Samples,
+    full_stop: Samples,
+    min_speech: Samples,
+    pre_roll_frames: u64,
+    pause_trailer_frames: u64,
+}
+
+impl SpeechSegmenter {
+    pub fn new(frame_size: Samples) -> Self {
+        let settings = VadSettings::default();
+
+        Self {
+            frame_size,
+            full_stop: SampleRate::PIPELINE.samples(settings.full_stop_ms),
+            min_speech: SampleRate::PIPELINE.samples(settings.min_speech_ms),
+            pre_roll_frames: settings.pre_roll_frames,
+            pause_trailer_frames: settings.pause_trailer_frames,
+        }
+    }
+
+    /// Applies the turn settings, the frame size stays the one of the detector.
+    pub fn configure(&mut self, settings: &VadSettings) {
+        self.full_stop = SampleRate::PIPELINE.samples(settings.full_stop_ms);
+        self.min_speech = SampleRate::PIPELINE.samples(settings.min_speech_ms);
+        self.pre_roll_frames = settings.pre_roll_frames;
+        self.pause_trailer_frames = settings.pause_trailer_frames;
+    }
+
+    pub fn set_frame_size(&mut self, frame_size: Samples) {
+        self.frame_size = frame_size;
+    }
+
+    /// Classifies every complete frame past the cursor with `is_speech`, stopping
+    /// at the first one that changes the state of the turn.
+    pub fn process(
+        &mut self,
+        audio_buffer: &mut AudioBuffer,
+        mut is_speech: impl FnMut(&[i16]) -> bool,
+    ) -> VadEvent {
+        while audio_buffer.user.written() >= (audio_buffer.cursor + self.frame_size) {
+            let range = audio_buffer.cursor..audio_buffer.cursor + self.frame_size;
+            audio_buffer.cursor += self.frame_size;
+            let frame = audio_buffer
+                .user
+                .get(range)
+                .expect("Audio past the cursor is never dropped");
+
+            let is_speech = is_speech(&frame);
+
+            match (is_speech, audio_buffer.start, audio_buffer.end) {
+                (true, None, None) => {
+                    // speech started for the first time this turn
+                    let start = audio_buffer
+                        .cursor
+                        .saturating_sub(self.frame_size * self.pre_roll_frames);
+                    audio_buffer.start = Some(start);
+
+                    return VadEvent::SpeechStarted;
+                }
+                (true, Some(_), None) => {} // speech is continuing no pause yet
+                (true, Some(_), Some(_)) => {
+                    // speech has paused but the user resume speacking
+                    audio_buffer.end = None;
+                    return VadEvent::SpeechResumed;
+                }
+                (false, None, None) => {} // the user still did not talk this turn
+                (false, Some(start), None) => {
+                    // the user paused a pipeline shall start
+                    if audio_buffer.cursor - start >= self.min_speech {
+                        let end = audio_buffer.cursor;
+                        audio_buffer.end = Some(end);
+
+                        //return VadEvent::SpeechPaused(start, end);
+                    }
+                }
+                (false, Some(start), Some(end)) => {
+                    // the user is still pausing it may be a full stop
+                    if audio_buffer.cursor - end > self.full_stop {
+                        audio_buffer.start = None;
+                        audio_buffer.end = None;
+
+                        return VadEvent::SpeechFullStop;
+                    }
+
+                    let trailer = self.frame_size * self.pause_trailer_frames;
+                    // once, a pipeline restarted on every silent frame would
+                    // never get its transcript back before the full stop
+                    if (audio_buffer.cursor - end) == trailer {
+                        return VadEvent::SpeechPaused(start, end + trailer);
+                    } // letting the trailling frames after a pause
+                }
+                _ => panic!("End cannot exists without start index"),
+            }
+        }
+
+        VadEvent::WaitingMoreChunks
+    }
+}
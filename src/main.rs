@@ -8,24 +8,18 @@ use tower_http::trace::TraceLayer;
 use tracing::info_span;
 use voicehanler_rs::{
     application::{
-        audio_source::AudioSourceList,
-        env::Args,
+        cli,
+        env::{Args, command::Command},
         http::{
             app_state::AppState,
             handlers::{
-                incoming_local_handler::ws_local_handler,
+                client_handler::client_handler, incoming_local_handler::ws_local_handler,
                 incoming_twilio_handler::ws_twilio_handler,
             },
         },
-        llm::LlmList,
-        stt::SttList,
+        registry::ProviderRegistry,
     },
     domain::entities::pipeline::pool_manager::PoolManager,
-    infrastructure::{
-        audio_source::{local_source_adapter::LocalAdapter, twilio_source_adapter::TwilioAdapter},
-        llm::gemini_adapter::GeminiAdapter,
-        stt::scribe_adapter::ScribeAdapter,
-    },
 };
 
 #[tokio::main(flavor = "multi_thread")]
@@ -44,43 +38,85 @@ async fn main() {
         subscriber.init();
     }
 
-    let trace_layer =
-        TraceLayer::new_for_http().make_span_with(|request: &axum::extract::Request| {
-            let uri: String = request.uri().to_string();
-            info_span!("http_request", method = ?request.method(), uri)
-        });
-
-    let llms = vec![LlmList::Gemini(
-        GeminiAdapter::new(
-            args.llm.aistudio_api_key.clone(),
-            args.llm.aistudio_base_url.clone(),
-        )
-        .unwrap(),
-    )];
+    let command = args.command.clone().unwrap_or_default();
+    let missing = command.missing(&args.elevenlabs, &args.llm);
+    if !missing.is_empty() {
+        eprintln!(
+            "Missing {}, only `replay --mock` runs without them",
+            missing.join(", ")
+        );
+        std::process::exit(2);
+    }
 
-    let source_audio = vec![
-        AudioSourceList::Twilio(TwilioAdapter::new()),
-        AudioSourceList::Local(LocalAdapter::new()),
-    ];
+    let mut providers = ProviderRegistry::builtin(&args.elevenlabs, &args.llm);
+    providers.register_vad(
+        "gmm",
+        args.gmm_vad
+            .vad()
+            .expect("Invalid GMM_VAD_MODE or GMM_VAD_FRAME_MS"),
+    );
+    if let Some(silero) = args.silero.vad().expect("Unusable SILERO_MODEL") {
+        providers.register_vad("silero", silero);
+    }
+    // replaces the built-in semantic detector with the tuned one
+    providers.register_turn_detector(
+        "semantic",
+        args.turn_detector
+            .detector()
+            .expect("Invalid TURN_* settings or unreadable TURN_CLASSIFIER_FILE"),
+    );
 
     let pool_manager = PoolManager::new(10);
     let state = Arc::new(AppState::new(
         pool_manager,
-        SttList::Scribe(ScribeAdapter::new(
-            args.elevenlabs.elevenlabs_api_key.clone(),
-        )),
-        source_audio,
-        llms,
+        providers,
+        args.tools.tools().expect("Unreadable TOOLS_FILE"),
+        args.agent
+            .agents(args.vad.settings().expect("Invalid VAD settings"))
+            .expect("Unreadable AGENT_PROFILES_FILE"),
+        args.capture.clone(),
+        args.audio.retention(),
     ));
 
+    let result = match command {
+        Command::Serve => {
+            serve(state).await;
+            Ok(())
+        }
+        Command::Transcribe { wav } => cli::transcribe(&state, &wav).await,
+        Command::Simulate { wav, speed, agent } => {
+            cli::simulate(&state, &wav, speed, agent.as_deref()).await
+        }
+        Command::Replay {
+            capture,
+            speed,
+            mock,
+        } => cli::replay(&state, &capture, speed, mock).await,
+    };
+
+    if let Err(err) = result {
+        eprintln!("{:?}", err);
+        std::process::exit(1);
+    }
+}
+
+async fn serve(state: Arc<AppState>) {
+    let trace_layer =
+        TraceLayer::new_for_http().make_span_with(|request: &axum::extract::Request| {
+            let uri: String = request.uri().to_string();
+            info_span!("http_request", method = ?request.method(), uri)
+        });
+
     let app = Router::new()
         .route("/", get(ws_twilio_handler))
         .route("/local", get(ws_local_handler))
+        .route("/client", get(client_handler))
         .layer(trace_layer)
         .with_state(state);
 
     let addr = SocketAddr::from(([127, 0, 0, 1], 5050));
     println!("Listening on ws://{}", addr);
+    println!("Test client on http://{}/client", addr);
 
     bind(addr)
         .serve(app.into_make_service())
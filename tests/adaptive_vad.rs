@@ -0,0 +1,158 @@
+//! The energy VAD in adaptive mode, on lines quieter and noisier than the fixed
+//! threshold is tuned for.
+
+mod common;
+
+use std::f32::consts::PI;
+
+use voicehanler_rs::{
+    domain::{
+        entities::agent_profile::{AdaptiveThreshold, VadSettings},
+        ports::vad::{Vad, VadEvent},
+    },
+    infrastructure::vad::local_vad::LocalVadAdapter,
+};
+
+use common::{SAMPLE_RATE, noise};
+
+/// A 200 Hz voice over the line noise.
+fn voice(ms: usize, amplitude: f32, line: f32) -> Vec<i16> {
+    noise(ms, line, 9)
+        .iter()
+        .enumerate()
+        .map(|(n, sample)| {
+            let t = n as f32 / SAMPLE_RATE as f32;
+            (amplitude * (2.0 * PI * 200.0 * t).sin()) as i16 + sample
+        })
+        .collect()
+}
+
+/// A 200 Hz voice whose loudness swings with four syllables a second, never
+/// quite silent.
+fn syllables(ms: usize, amplitude: f32, line: f32) -> Vec<i16> {
+    noise(ms, line, 11)
+        .iter()
+        .enumerate()
+        .map(|(n, sample)| {
+            let t = n as f32 / SAMPLE_RATE as f32;
+            let envelope = 0.55 + 0.45 * (2.0 * PI * 4.0 * t).sin();
+            (amplitude * envelope * (2.0 * PI * 200.0 * t).sin()) as i16 + sample
+        })
+        .collect()
+}
+
+fn adaptive() -> LocalVadAdapter {
+    let mut vad = LocalVadAdapter::new();
+    vad.configure(&VadSettings {
+        adaptive: Some(AdaptiveThreshold::default()),
+        ..VadSettings::default()
+    });
+    vad
+}
+
+/// Every event but the pauses, with the cursor it was raised at in ms.
+fn events(vad: &mut LocalVadAdapter, audio: &[i16]) -> Vec<(&'static str, usize)> {
+    common::events(vad, audio)
+        .iter()
+        .filter(|raised| !matches!(raised.event, VadEvent::SpeechPaused(_, _)))
+        .map(|raised| (raised.event.name(), raised.cursor_ms()))
+        .collect()
+}
+
+#[test]
+fn hears_a_quiet_caller_the_fixed_threshold_misses() {
+    let office = [
+        noise(3000, 50.0, 1),
+        voice(1000, 400.0, 50.0),
+        noise(6000, 50.0, 2),
+    ]
+    .concat();
+
+    assert!(events(&mut LocalVadAdapter::new(), &office).is_empty());
+
+    let events = events(&mut adaptive(), &office);
+    assert_eq!(events.len(), 2, "{:?}", events);
+    assert_eq!(events[0].0, "speech_started");
+    assert!((3000..3200).contains(&events[0].1), "{:?}", events);
+    assert_eq!(events[1].0, "speech_full_stop");
+}
+
+#[test]
+fn settles_on_a_street_line() {
+    let street = [
+        noise(6000, 2500.0, 3),
+        voice(1000, 8000.0, 2500.0),
+        noise(6000, 2500.0, 4),
+    ]
+    .concat();
+
+    // the fixed threshold takes the street for one endless sentence
+    let fixed = events(&mut LocalVadAdapter::new(), &street);
+    assert_eq!(fixed.len(), 1, "{:?}", fixed);
+    assert_eq!(fixed[0].0, "speech_started");
+
+    let mut vad = adaptive();
+    let events = events(&mut vad, &street);
+    assert!(
+        events
+            .iter()
+            .any(|(name, at_ms)| *name != "speech_full_stop" && (6000..6200).contains(at_ms)),
+        "{:?}",
+        events
+    );
+    assert_eq!(events.last().unwrap().0, "speech_full_stop", "{:?}", events);
+
+    // uniform noise has an RMS of amplitude / sqrt(3)
+    let floor = vad.noise_floor().unwrap();
+    assert!((floor - 1443.0).abs() < 100.0, "floor {}", floor);
+}
+
+#[test]
+fn fixed_threshold_reports_no_noise_floor() {
+    let mut vad = LocalVadAdapter::new();
+    events(&mut vad, &noise(1000, 100.0, 5));
+
+    assert_eq!(vad.noise_floor(), None);
+}
+
+#[test]
+fn keeps_the_floor_through_a_long_monologue() {
+    let monologue = [
+        noise(3000, 50.0, 6),
+        syllables(8000, 4000.0, 50.0),
+        noise(3000, 50.0, 7),
+    ]
+    .concat();
+
+    // the voice never stops for a whole window, but it is no steady noise
+    let mut vad = adaptive();
+    let events = events(&mut vad, &monologue);
+    assert_eq!(events.len(), 2, "{:?}", events);
+    assert_eq!(events[0].0, "speech_started");
+    assert!((3000..3100).contains(&events[0].1), "{:?}", events);
+    assert_eq!(events[1].0, "speech_full_stop");
+
+    let floor = vad.noise_floor().unwrap();
+    assert!(floor < 60.0, "floor {}", floor);
+}
+
+#[test]
+fn keeps_the_floor_when_reconfigured() {
+    let mut vad = adaptive();
+    events(&mut vad, &noise(3000, 2500.0, 8));
+    let floor = vad.noise_floor().unwrap();
+    assert!((floor - 1443.0).abs() < 100.0, "floor {}", floor);
+
+    // what an agent switch does mid-call
+    vad.configure(&VadSettings {
+        adaptive: Some(AdaptiveThreshold {
+            onset_db: 12.0,
+            ..AdaptiveThreshold::default()
+        }),
+        ..VadSettings::default()
+    });
+    assert_eq!(vad.noise_floor(), Some(floor));
+
+    vad.configure(&VadSettings::default());
+    assert_eq!(vad.noise_floor(), None);
+}
@@ -0,0 +1,145 @@
+mod common;
+
+use std::{path::Path, time::Duration};
+
+use tokio::{task::yield_now, time::sleep};
+use uuid::Uuid;
+use voicehanler_rs::{
+    application::{
+        cli, env::capture::CaptureEnv, http::app_state::AppState, registry::ProviderRegistry,
+    },
+    domain::{
+        entities::{
+            agent_profile::{VadOverrides, VadSettings},
+            agent_registry::AgentRegistry,
+            audio_buffer::DEFAULT_RETENTION,
+            barge_in::BargeInMode,
+            pipeline::pool_manager::PoolManager,
+            tool_registry::ToolRegistry,
+        },
+        ports::audio_source::{InboundFrame, OutboundFrame},
+        utils::{
+            audio::codec::{Codec, l16::L16Le},
+            units::Millis,
+        },
+    },
+    infrastructure::{
+        audio_source::local_source_adapter::LocalAdapter,
+        capture::{
+            capture_file::{CaptureFile, Direction},
+            session_recorder::SessionRecorder,
+        },
+    },
+};
+
+use common::{FRAME_MS, SAMPLE_RATE, silence, utterance};
+
+/// The capture once the recorder wrote its `frames`, it flushes in the background.
+async fn read_capture(path: &Path, frames: usize) -> CaptureFile {
+    for _ in 0..1000 {
+        if let Ok(capture) = CaptureFile::read(path)
+            && capture.frames.len() == frames
+        {
+            return capture;
+        }
+
+        std::thread::sleep(Duration::from_millis(1));
+        yield_now().await;
+    }
+
+    panic!("{} was not fully written", path.display());
+}
+
+#[tokio::test(start_paused = true)]
+async fn replays_a_recorded_local_session_with_mocks() {
+    let dir = std::env::temp_dir().join(format!("capture-replay-{}", std::process::id()));
+    let session = Uuid::now_v7();
+
+    // two utterances 5 s apart, one turn for the full stop the session asked for
+    let mut audio = utterance(800);
+    audio.extend(utterance(600));
+    audio.extend(silence(2000));
+    let vad = VadOverrides {
+        full_stop_ms: Some(Millis(6000)),
+        ..VadOverrides::default()
+    };
+
+    let mut inbound = vec![InboundFrame::Text(r#"{"event": "start"}"#.to_string())];
+    inbound.extend(
+        audio
+            .chunks(FRAME_MS * SAMPLE_RATE / 1000)
+            .map(|frame| InboundFrame::Binary(L16Le.encode(frame).unwrap())),
+    );
+    inbound.push(InboundFrame::Text(r#"{"event": "stop"}"#.to_string()));
+
+    let (recorder, path) = SessionRecorder::create(&dir, "local", session, None, vad)
+        .await
+        .unwrap();
+    for frame in inbound.iter() {
+        recorder.inbound(frame);
+        sleep(Duration::from_millis(FRAME_MS as u64)).await;
+    }
+    recorder.outbound(&OutboundFrame::Binary(vec![1, 2, 3, 4]));
+    drop(recorder);
+
+    let capture = read_capture(&path, inbound.len() + 1).await;
+    assert_eq!(capture.header.protocol, "local");
+    assert_eq!(capture.header.session, session);
+    assert_eq!(capture.header.vad.full_stop_ms, Some(Millis(6000)));
+    let replayed: Vec<InboundFrame> = capture
+        .inbound()
+        .map(|frame| frame.payload.inbound_frame().unwrap())
+        .collect();
+    assert_eq!(replayed, inbound);
+    assert_eq!(
+        capture.frames.last().map(|frame| frame.direction),
+        Some(Direction::Outbound)
+    );
+    // frames keep the pace they were received at
+    let last_inbound = capture.inbound().last().unwrap();
+    assert_eq!(
+        last_inbound.at_us,
+        (inbound.len() as u64 - 1) * FRAME_MS as u64 * 1000
+    );
+
+    let mut providers = ProviderRegistry::new();
+    providers.register_audio_source("local", LocalAdapter::new());
+    let state = AppState::new(
+        PoolManager::new(1),
+        providers,
+        ToolRegistry::new(Duration::from_secs(1)),
+        AgentRegistry::new(
+            Vec::new(),
+            None,
+            VadSettings::default(),
+            BargeInMode::Immediate,
+        )
+        .unwrap(),
+        CaptureEnv {
+            capture_dir: None,
+            capture_audio: false,
+        },
+        DEFAULT_RETENTION,
+    );
+
+    let outcome = cli::replay_session(&state, &path, 1.0, true).await.unwrap();
+    let _ = std::fs::remove_dir_all(&dir);
+
+    let transcript: Vec<String> = outcome
+        .history
+        .events
+        .iter()
+        .map(|event| {
+            format!(
+                "{}: {}",
+                event.member,
+                event.content.as_deref().unwrap_or_default()
+            )
+        })
+        .collect();
+    assert_eq!(
+        transcript,
+        vec!["user: (transcription simulée)", "agent: Réponse simulée.",]
+    );
+    assert_eq!(outcome.recorded, 1);
+}
@@ -0,0 +1,136 @@
+//! G.711, L16 and WAV codecs. The G.711 vectors are those of the Sun reference
+//! implementation, which the CPython `audioop` module also ships.
+
+use voicehanler_rs::domain::utils::{
+    audio::codec::{
+        self, Codec,
+        alaw::ALaw,
+        l16::{L16, L16Le},
+        mulaw::MuLaw,
+        wav::Wav,
+    },
+    units::SampleRate,
+};
+
+#[test]
+fn mulaw_matches_the_reference_vectors() {
+    let vectors: [(i16, u8); 8] = [
+        (0, 0xFF),
+        (-4, 0x7E),
+        (8, 0xFE),
+        (-100, 0x72),
+        (1000, 0xCE),
+        (-8160, 0x1F),
+        (32767, 0x80),
+        (-32768, 0x00),
+    ];
+    for (sample, byte) in vectors {
+        assert_eq!(MuLaw::encode_sample(sample), byte, "{}", sample);
+    }
+
+    assert_eq!(MuLaw::decode_sample(0xFF), 0);
+    assert_eq!(MuLaw::decode_sample(0x7F), 0);
+    assert_eq!(MuLaw::decode_sample(0xFE), 8);
+    assert_eq!(MuLaw::decode_sample(0xCE), 988);
+    assert_eq!(MuLaw::decode_sample(0x80), 32124);
+    assert_eq!(MuLaw::decode_sample(0x00), -32124);
+}
+
+#[test]
+fn alaw_matches_the_reference_vectors() {
+    let vectors: [(i16, u8); 8] = [
+        (0, 0xD5),
+        (-1, 0x55),
+        (100, 0xD3),
+        (-100, 0x53),
+        (1000, 0xFA),
+        (-8000, 0x0A),
+        (32767, 0xAA),
+        (-32768, 0x2A),
+    ];
+    for (sample, byte) in vectors {
+        assert_eq!(ALaw::encode_sample(sample), byte, "{}", sample);
+    }
+
+    assert_eq!(ALaw::decode_sample(0xD5), 8);
+    assert_eq!(ALaw::decode_sample(0x55), -8);
+    assert_eq!(ALaw::decode_sample(0xFA), 1008);
+    assert_eq!(ALaw::decode_sample(0xAA), 32256);
+    assert_eq!(ALaw::decode_sample(0x2A), -32256);
+}
+
+#[test]
+fn g711_round_trips_every_byte_and_bounds_the_error() {
+    for byte in 0..=255u8 {
+        assert_eq!(ALaw::encode_sample(ALaw::decode_sample(byte)), byte);
+        // μ-law has two zeros, the negative one encodes back as the positive one
+        if byte != 0x7F {
+            assert_eq!(MuLaw::encode_sample(MuLaw::decode_sample(byte)), byte);
+        }
+    }
+
+    for sample in -32000..=32000i16 {
+        // the quantization step doubles with each segment, 1/16 of the value at most
+        let bound = (sample as i32).abs() / 16 + 16;
+        let mulaw = MuLaw::decode_sample(MuLaw::encode_sample(sample)) as i32;
+        let alaw = ALaw::decode_sample(ALaw::encode_sample(sample)) as i32;
+
+        assert!((mulaw - sample as i32).abs() <= bound, "μ-law {}", sample);
+        assert!((alaw - sample as i32).abs() <= bound, "A-law {}", sample);
+    }
+}
+
+#[test]
+fn l16_is_big_endian_and_the_internal_variant_little_endian() {
+    let samples = [0, 1, -2, i16::MAX, i16::MIN];
+
+    let bytes = L16.encode(&samples).unwrap();
+    assert_eq!(&bytes[..6], &[0x00, 0x00, 0x00, 0x01, 0xFF, 0xFE]);
+    assert_eq!(L16.decode(&bytes).unwrap(), samples);
+
+    let bytes = L16Le.encode(&samples).unwrap();
+    assert_eq!(&bytes[..6], &[0x00, 0x00, 0x01, 0x00, 0xFE, 0xFF]);
+    assert_eq!(L16Le.decode(&bytes).unwrap(), samples);
+
+    // a trailing odd byte is not a sample
+    assert_eq!(L16.decode(&[0x00, 0x01, 0x07]).unwrap(), [1]);
+    assert_eq!(L16Le.decode(&[0x01, 0x00, 0x07]).unwrap(), [1]);
+}
+
+#[test]
+fn wav_round_trips_and_checks_its_rate() {
+    let samples: Vec<i16> = (0..800).map(|n| (n * 40 - 16000) as i16).collect();
+    let bytes = Wav::new(SampleRate::TELEPHONY).encode(&samples).unwrap();
+
+    assert_eq!(&bytes[..4], b"RIFF");
+    assert_eq!(&bytes[8..12], b"WAVE");
+    assert_eq!(bytes.len(), 44 + samples.len() * 2);
+
+    assert_eq!(
+        Wav::read(&bytes).unwrap(),
+        (SampleRate::TELEPHONY, samples.clone())
+    );
+    assert_eq!(
+        Wav::new(SampleRate::TELEPHONY).decode(&bytes).unwrap(),
+        samples
+    );
+    assert!(Wav::default().decode(&bytes).is_err());
+}
+
+#[test]
+fn selects_codecs_by_name() {
+    let names = [
+        ("audio/x-mulaw", "audio/x-mulaw"),
+        ("audio/PCMU", "audio/x-mulaw"),
+        ("audio/x-alaw", "audio/x-alaw"),
+        ("audio/L16; rate=16000", "audio/l16"),
+        ("audio/x-l16-le", "audio/x-l16-le"),
+        ("audio/x-wav", "audio/wav"),
+    ];
+    for (announced, name) in names {
+        let codec = codec::by_name(announced, SampleRate::PIPELINE).unwrap();
+        assert_eq!(codec.name(), name);
+    }
+
+    assert!(codec::by_name("audio/opus", SampleRate::PIPELINE).is_none());
+}
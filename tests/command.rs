@@ -0,0 +1,69 @@
+//! Credentials each command needs before it starts.
+
+use std::path::PathBuf;
+
+use voicehanler_rs::application::env::{
+    aistudio::AiStudioEnv, command::Command, elevenlabs::ElevenLabsEnv,
+};
+
+fn elevenlabs(api_key: Option<&str>) -> ElevenLabsEnv {
+    ElevenLabsEnv {
+        elevenlabs_api_key: api_key.map(str::to_string),
+        elevenlabs_voice_id: "21m00Tcm4TlvDq8ikWAM".to_string(),
+        elevenlabs_tts_model: "eleven_flash_v2_5".to_string(),
+        elevenlabs_tts_sample_rate: 16000,
+    }
+}
+
+fn no_llm() -> AiStudioEnv {
+    AiStudioEnv {
+        aistudio_api_key: None,
+        aistudio_base_url: None,
+    }
+}
+
+#[test]
+fn transcribing_needs_only_the_stt_key() {
+    let transcribe = Command::Transcribe {
+        wav: PathBuf::from("call.wav"),
+    };
+
+    assert!(
+        transcribe
+            .missing(&elevenlabs(Some("key")), &no_llm())
+            .is_empty()
+    );
+    assert_eq!(
+        transcribe.missing(&elevenlabs(None), &no_llm()),
+        ["ELEVENLABS_API_KEY"]
+    );
+}
+
+#[test]
+fn replies_need_the_llm_too_unless_mocked() {
+    let simulate = Command::Simulate {
+        wav: PathBuf::from("call.wav"),
+        speed: 1.0,
+        agent: None,
+    };
+    assert_eq!(
+        simulate.missing(&elevenlabs(Some("key")), &no_llm()),
+        ["LLM_AISTUDIO_GOOGLE_API_KEY", "LLM_AISTUDIO_BASE_URL"]
+    );
+    assert_eq!(
+        Command::Serve.missing(&elevenlabs(None), &no_llm()).len(),
+        3
+    );
+
+    let replay = |mock| Command::Replay {
+        capture: PathBuf::from("session.jsonl"),
+        speed: 0.0,
+        mock,
+    };
+    assert!(
+        replay(true)
+            .missing(&elevenlabs(None), &no_llm())
+            .is_empty()
+    );
+    assert_eq!(replay(false).missing(&elevenlabs(None), &no_llm()).len(), 3);
+}
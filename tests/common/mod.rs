@@ -0,0 +1,338 @@
+//! End-to-end harness: a session fed with synthetic audio through the real VAD
+//! and pipeline, with scripted providers on the other side.
+
+#![allow(dead_code)]
+
+use std::{sync::Arc, time::Duration};
+
+use tokio::{sync::Mutex, time::sleep};
+use uuid::Uuid;
+use voicehanler_rs::{
+    application::{
+        env::capture::CaptureEnv, http::app_state::AppState, registry::ProviderRegistry,
+        turn_detector::TurnDetectorList, vad::VadList,
+    },
+    domain::{
+        entities::{
+            agent_profile::{AgentProfile, VadSettings},
+            agent_registry::AgentRegistry,
+            audio_buffer::{AudioBuffer, DEFAULT_RETENTION},
+            audio_source_layer::{
+                AudioSourceLayer, ClearAudioCallback, SendAudioCallback, SessionCallbacks,
+                SessionEventCallback,
+            },
+            barge_in::BargeInMode,
+            call_metadata::CallMetadata,
+            history::{history::History, history_member::HistoryMember},
+            pipeline::pool_manager::PoolManager,
+            playback::Playback,
+            tool_registry::ToolRegistry,
+        },
+        ports::{
+            audio_source::{AudioSource, InboundFrame, SessionEvent},
+            vad::{Vad, VadEvent},
+        },
+        utils::{Utils, units::Samples},
+    },
+    infrastructure::{
+        llm::mock_llm::MockLlm, stt::mock_stt::MockStt, tts::mock_tts::MockTts,
+        vad::local_vad::LocalVadAdapter,
+    },
+};
+
+pub const SAMPLE_RATE: usize = 16_000;
+/// Audio is pushed in 20 ms frames, paced like a caller would.
+pub const FRAME_MS: usize = 20;
+
+/// `ms` of a 200 Hz tone loud enough for the VAD.
+pub fn tone(ms: usize) -> Vec<i16> {
+    (0..ms * SAMPLE_RATE / 1000)
+        .map(|idx| {
+            let phase = idx as f32 * 200.0 * std::f32::consts::TAU / SAMPLE_RATE as f32;
+            (phase.sin() * 8000.0) as i16
+        })
+        .collect()
+}
+
+pub fn silence(ms: usize) -> Vec<i16> {
+    vec![0; ms * SAMPLE_RATE / 1000]
+}
+
+/// An utterance followed by enough silence for the VAD to close the turn.
+pub fn utterance(ms: usize) -> Vec<i16> {
+    let mut audio = tone(ms);
+    audio.extend(silence(5000));
+    audio
+}
+
+/// `ms` of white noise at `rate`, from a small LCG so runs are reproducible.
+pub fn noise_at(rate: usize, ms: usize, amplitude: f32, seed: u32) -> Vec<i16> {
+    let mut state = seed;
+    (0..ms * rate / 1000)
+        .map(|_| {
+            state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+            let uniform = (state >> 8) as f32 / (1 << 24) as f32 * 2.0 - 1.0;
+            (uniform * amplitude) as i16
+        })
+        .collect()
+}
+
+/// `ms` of white noise at the pipeline rate.
+pub fn noise(ms: usize, amplitude: f32, seed: u32) -> Vec<i16> {
+    noise_at(SAMPLE_RATE, ms, amplitude, seed)
+}
+
+/// A VAD event with the cursor and the start of the turn right after it.
+#[derive(Debug, PartialEq)]
+pub struct Raised {
+    pub event: VadEvent,
+    pub cursor: Samples,
+    pub start: Option<Samples>,
+}
+
+impl Raised {
+    pub fn cursor_ms(&self) -> usize {
+        self.cursor.as_usize() * 1000 / SAMPLE_RATE
+    }
+}
+
+/// Pushes `audio` `chunk` samples at a time and collects every event `process`
+/// raises, until it waits for more audio.
+pub fn raise(
+    audio: &[i16],
+    chunk: usize,
+    mut process: impl FnMut(&mut AudioBuffer) -> VadEvent,
+) -> Vec<Raised> {
+    let mut audio_buffer = AudioBuffer::new();
+    let mut events = Vec::new();
+
+    for chunk in audio.chunks(chunk) {
+        audio_buffer.push_user(chunk);
+
+        loop {
+            match process(&mut audio_buffer) {
+                VadEvent::WaitingMoreChunks => break,
+                event => events.push(Raised {
+                    event,
+                    cursor: audio_buffer.cursor,
+                    start: audio_buffer.start,
+                }),
+            }
+        }
+    }
+
+    events
+}
+
+/// Every event `vad` raises over `audio`, fed in 20 ms frames.
+pub fn events(vad: &mut impl Vad, audio: &[i16]) -> Vec<Raised> {
+    raise(audio, FRAME_MS * SAMPLE_RATE / 1000, |audio_buffer| {
+        vad.process_audio(audio_buffer)
+    })
+}
+
+pub struct Harness {
+    pub id: Uuid,
+    pub agent: AgentProfile,
+    pub stt: MockStt,
+    pub llm: MockLlm,
+    pub tts: MockTts,
+    pub tools: ToolRegistry,
+    pub barge_in: BargeInMode,
+    pub pool_manager: PoolManager,
+    pub playback: Playback,
+    pub history: History,
+    pub audio_buffer: AudioBuffer,
+    /// What the audio source announced when it started, and whether it stopped.
+    pub metadata: CallMetadata,
+    pub ended: bool,
+    vad: VadList,
+    providers: ProviderRegistry,
+    outbound: Arc<Mutex<Vec<i16>>>,
+    clears: Arc<Mutex<usize>>,
+    events: Arc<Mutex<Vec<SessionEvent>>>,
+}
+
+impl Harness {
+    pub fn new(stt: MockStt, llm: MockLlm, tts: MockTts) -> Self {
+        Self {
+            id: Utils::generate_uuid(),
+            agent: AgentProfile::default(),
+            stt,
+            llm,
+            tts,
+            tools: ToolRegistry::default(),
+            barge_in: BargeInMode::Immediate,
+            pool_manager: PoolManager::new(4),
+            playback: Playback::new(),
+            history: History::new(),
+            audio_buffer: AudioBuffer::new(),
+            metadata: CallMetadata::new(),
+            ended: false,
+            vad: LocalVadAdapter::new().into(),
+            providers: ProviderRegistry::new(),
+            outbound: Arc::new(Mutex::new(Vec::new())),
+            clears: Arc::new(Mutex::new(0)),
+            events: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    /// Ends the turns with `detector` rather than the fixed VAD full stop.
+    pub fn with_turn_detector<T>(mut self, detector: T) -> Self
+    where
+        T: Into<TurnDetectorList> + Clone + Send + Sync + 'static,
+    {
+        self.providers
+            .register_turn_detector("test", move || detector.clone());
+        self
+    }
+
+    /// Plays `audio` as the caller, one frame every 20 ms.
+    pub async fn speak(&mut self, audio: &[i16]) {
+        let (state, providers, callbacks) = self.session();
+        let mut layer = AudioSourceLayer::new(
+            self.id,
+            &state,
+            &providers,
+            callbacks,
+            &mut self.vad,
+            &mut self.history,
+            &mut self.audio_buffer,
+        )
+        .unwrap();
+        layer.playback = self.playback.clone();
+
+        layer.apply_agent(self.agent.clone());
+
+        for frame in audio.chunks(FRAME_MS * SAMPLE_RATE / 1000) {
+            layer.process(frame).await;
+            sleep(Duration::from_millis(FRAME_MS as u64)).await;
+        }
+    }
+
+    /// Hands `frames` to `source` as its socket would, one every 20 ms.
+    pub async fn stream(&mut self, source: &dyn AudioSource, frames: Vec<InboundFrame>) {
+        let (state, providers, callbacks) = self.session();
+        let mut layer = AudioSourceLayer::new(
+            self.id,
+            &state,
+            &providers,
+            callbacks,
+            &mut self.vad,
+            &mut self.history,
+            &mut self.audio_buffer,
+        )
+        .unwrap();
+        layer.playback = self.playback.clone();
+
+        layer.apply_agent(self.agent.clone());
+
+        for frame in frames {
+            layer.audio_buffer.override_streamed_buffer(frame);
+            source.handle(&mut layer).await.unwrap();
+            sleep(Duration::from_millis(FRAME_MS as u64)).await;
+        }
+
+        self.metadata = layer.metadata.clone();
+        self.ended = layer.ended;
+    }
+
+    /// What a session layer is built from, wired to the mocks and recorders.
+    fn session(&self) -> (AppState, ProviderRegistry, SessionCallbacks) {
+        let outbound = self.outbound.clone();
+        let clears = self.clears.clone();
+        let events = self.events.clone();
+
+        // the VAD is not registered, its state must outlive each call
+        let mut providers = self.providers.clone();
+        providers
+            .register_stt("mock", self.stt.clone())
+            .register_llm("mock", self.llm.clone())
+            .register_tts("mock", self.tts.clone());
+        let state = AppState::new(
+            self.pool_manager.clone(),
+            providers.clone(),
+            self.tools.clone(),
+            AgentRegistry::new(Vec::new(), None, VadSettings::default(), self.barge_in).unwrap(),
+            CaptureEnv {
+                capture_dir: None,
+                capture_audio: false,
+            },
+            DEFAULT_RETENTION,
+        );
+
+        let callbacks = SessionCallbacks {
+            send_audio: SendAudioCallback::new(move |bytes| {
+                let outbound = outbound.clone();
+                let bytes = bytes.to_vec();
+                async move {
+                    outbound.lock().await.extend(bytes);
+                    Ok(())
+                }
+            }),
+            clear_audio: ClearAudioCallback::new(move || {
+                let clears = clears.clone();
+                async move {
+                    *clears.lock().await += 1;
+                    Ok(())
+                }
+            }),
+            send_event: SessionEventCallback::new(move |event| {
+                let events = events.clone();
+                async move {
+                    events.lock().await.push(event);
+                    Ok(())
+                }
+            }),
+        };
+
+        (state, providers, callbacks)
+    }
+
+    /// Waits for the running pipeline, if any, and applies what it recorded.
+    pub async fn settle(&mut self) {
+        for _ in 0..600 {
+            if !self.pool_manager.is_running(&self.id).await {
+                break;
+            }
+            sleep(Duration::from_millis(50)).await;
+        }
+
+        self.history.sync();
+    }
+
+    /// The history as `member: content` lines, tool calls as `name(args) -> result`.
+    /// Tool rounds where the agent said nothing are left out.
+    pub fn transcript(&self) -> Vec<String> {
+        self.history
+            .events
+            .iter()
+            .filter(|event| {
+                !matches!(event.member, HistoryMember::Agent) || event.content.is_some()
+            })
+            .map(|event| match &event.tool_call {
+                Some(call) => format!(
+                    "{}: {}({}) -> {}",
+                    event.member, call.name, call.arguments, call.result
+                ),
+                None => format!(
+                    "{}: {}",
+                    event.member,
+                    event.content.as_deref().unwrap_or_default()
+                ),
+            })
+            .collect()
+    }
+
+    pub async fn outbound_audio(&self) -> Vec<i16> {
+        self.outbound.lock().await.clone()
+    }
+
+    pub async fn clears(&self) -> usize {
+        *self.clears.lock().await
+    }
+
+    pub async fn events(&self) -> Vec<SessionEvent> {
+        self.events.lock().await.clone()
+    }
+}
@@ -0,0 +1,147 @@
+//! The GMM VAD on 8 kHz telephony audio, upsampled like the Twilio source does.
+
+mod common;
+
+use std::f32::consts::PI;
+
+use voicehanler_rs::{
+    domain::{
+        ports::vad::Vad,
+        utils::{
+            audio::resampler::Resampler,
+            units::{Millis, SampleRate},
+        },
+    },
+    infrastructure::vad::gmm_vad::GmmVadAdapter,
+};
+
+use common::noise_at;
+
+const TELEPHONY_RATE: usize = 8_000;
+
+/// Line hiss, at the rate of a phone line.
+fn hiss(ms: usize, amplitude: f32, seed: u32) -> Vec<i16> {
+    noise_at(TELEPHONY_RATE, ms, amplitude, seed)
+}
+
+/// A voiced vowel: harmonics of a 130 Hz pitch shaped by three formants,
+/// with a syllabic envelope, over the same line hiss.
+fn vowel(ms: usize) -> Vec<i16> {
+    let formants: [(f32, f32); 3] = [(700.0, 130.0), (1220.0, 70.0), (2600.0, 160.0)];
+    let pitch = 130.0;
+    let noise = hiss(ms, 60.0, 7);
+
+    (0..ms * TELEPHONY_RATE / 1000)
+        .map(|n| {
+            let t = n as f32 / TELEPHONY_RATE as f32;
+            let mut sample = 0.0;
+            let mut harmonic = pitch;
+            while harmonic < 3400.0 {
+                let gain: f32 = formants
+                    .iter()
+                    .map(|(frequency, bandwidth)| {
+                        1.0 / (1.0 + ((harmonic - frequency) / bandwidth).powi(2))
+                    })
+                    .sum();
+                sample += gain * (2.0 * PI * harmonic * t).sin();
+                harmonic += pitch;
+            }
+
+            let envelope = 0.6 + 0.4 * (2.0 * PI * 3.0 * t).sin();
+            (sample * envelope * 2500.0) as i16 + noise[n]
+        })
+        .collect()
+}
+
+fn telephony(parts: &[Vec<i16>]) -> Vec<i16> {
+    Resampler::new(SampleRate::TELEPHONY, SampleRate::PIPELINE)
+        .unwrap()
+        .resample(&parts.concat())
+}
+
+/// The events over the whole call as their name, the cursor and the start of
+/// the turn, in ms.
+fn events(vad: &mut GmmVadAdapter, audio: &[i16]) -> Vec<(&'static str, usize, Option<usize>)> {
+    common::events(vad, audio)
+        .iter()
+        .map(|raised| {
+            (
+                raised.event.name(),
+                raised.cursor_ms(),
+                raised.start.map(|start| start.as_usize() / 16),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn detects_a_caller_once_the_line_settled() {
+    let audio = telephony(&[hiss(6000, 60.0, 1), vowel(1500), hiss(6000, 60.0, 2)]);
+
+    for mode in 0..=3 {
+        let mut vad = GmmVadAdapter::new(mode, Millis(30)).unwrap();
+        // the noise model adapts to the line within the first second
+        let events: Vec<_> = events(&mut vad, &audio)
+            .into_iter()
+            .filter(|(_, at_ms, _)| *at_ms >= 5000)
+            .collect();
+
+        let (name, at_ms, start_ms) = events[0];
+        assert_eq!(name, "speech_started", "mode {}: {:?}", mode, events);
+        assert!((6000..6100).contains(&at_ms), "mode {}: {:?}", mode, events);
+        assert!(start_ms.unwrap() <= 6000, "mode {}: {:?}", mode, events);
+
+        assert_eq!(
+            events.last().map(|(name, _, _)| *name),
+            Some("speech_full_stop"),
+            "mode {}: {:?}",
+            mode,
+            events
+        );
+        assert_eq!(
+            events
+                .iter()
+                .filter(|(name, _, _)| *name == "speech_started")
+                .count(),
+            1,
+            "mode {}: {:?}",
+            mode,
+            events
+        );
+    }
+}
+
+#[test]
+fn aggressive_modes_ignore_a_fresh_hiss() {
+    let audio = telephony(&[hiss(3000, 60.0, 3)]);
+
+    for mode in 0..=3 {
+        let mut vad = GmmVadAdapter::new(mode, Millis(30)).unwrap();
+        let started = events(&mut vad, &audio)
+            .iter()
+            .any(|(name, _, _)| *name == "speech_started");
+
+        // quality modes take the hiss for speech until the noise model adapts
+        assert_eq!(started, mode < 2, "mode {}", mode);
+    }
+}
+
+#[test]
+fn every_frame_length_detects_speech() {
+    for frame_ms in [10, 20, 30] {
+        let vad = GmmVadAdapter::new(2, Millis(frame_ms)).unwrap();
+
+        assert!(vad.is_speech(&telephony(&[vowel(300)])), "{} ms", frame_ms);
+        assert!(
+            !vad.is_speech(&telephony(&[hiss(300, 60.0, 4)])),
+            "{} ms",
+            frame_ms
+        );
+    }
+}
+
+#[test]
+fn rejects_unknown_modes_and_frames() {
+    assert!(GmmVadAdapter::new(4, Millis(30)).is_err());
+    assert!(GmmVadAdapter::new(2, Millis(32)).is_err());
+}
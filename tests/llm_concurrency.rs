@@ -0,0 +1,108 @@
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+    time::{Duration, Instant},
+};
+
+use axum::{Json, Router, extract::State, routing::post};
+use serde_json::{Value, json};
+use tokio::{net::TcpListener, spawn, time::sleep};
+use voicehanler_rs::{
+    application::llm::LlmList,
+    domain::{
+        entities::{
+            agent_profile::SamplingParams,
+            history::{
+                history_event::{HistoryEvent, HistoryEventPayload},
+                history_member::HistoryMember,
+            },
+        },
+        ports::llm::{Llm, LlmRequest},
+    },
+    infrastructure::llm::gemini_adapter::GeminiAdapter,
+};
+
+const CALLS: usize = 4;
+const PROVIDER_LATENCY: Duration = Duration::from_millis(300);
+
+#[derive(Default)]
+struct Provider {
+    in_flight: AtomicUsize,
+    max_in_flight: AtomicUsize,
+}
+
+async fn chat_completions(State(provider): State<Arc<Provider>>) -> Json<Value> {
+    let in_flight = provider.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+    provider
+        .max_in_flight
+        .fetch_max(in_flight, Ordering::SeqCst);
+
+    sleep(PROVIDER_LATENCY).await;
+    provider.in_flight.fetch_sub(1, Ordering::SeqCst);
+
+    Json(json!({
+        "id": "chatcmpl-test",
+        "object": "chat.completion",
+        "created": 0,
+        "model": "gemini-2.0-flash",
+        "choices": [{
+            "index": 0,
+            "message": { "role": "assistant", "content": "Bonjour" },
+            "finish_reason": "stop"
+        }],
+        "usage": { "prompt_tokens": 1, "completion_tokens": 1, "total_tokens": 2 }
+    }))
+}
+
+fn request() -> LlmRequest {
+    LlmRequest {
+        model: "gemini-2.0-flash".to_string(),
+        sampling: SamplingParams::default(),
+        history_events: vec![HistoryEvent::new(HistoryEventPayload {
+            member: HistoryMember::User,
+            content: Some("Bonjour".to_string()),
+            created_at: chrono::Utc::now(),
+            tool_call: None,
+        })],
+        tools: Vec::new(),
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn concurrent_sessions_do_not_serialize_llm_calls() {
+    let provider = Arc::new(Provider::default());
+    let app = Router::new()
+        .route("/chat/completions", post(chat_completions))
+        .with_state(Arc::clone(&provider));
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let endpoint = format!("http://{}", listener.local_addr().unwrap());
+    spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+    // every session clones the shared adapter, as the WebSocket handlers do
+    let llm = LlmList::Gemini(GeminiAdapter::new("test-key".to_string(), endpoint));
+
+    let started = Instant::now();
+    let calls: Vec<_> = (0..CALLS)
+        .map(|_| {
+            let llm = llm.clone();
+            spawn(async move { llm.process(request()).await })
+        })
+        .collect();
+
+    for call in calls {
+        let response = call.await.unwrap().unwrap();
+        assert_eq!(response.text.as_deref(), Some("Bonjour"));
+    }
+
+    let elapsed = started.elapsed();
+    assert_eq!(provider.max_in_flight.load(Ordering::SeqCst), CALLS);
+    assert!(
+        elapsed < PROVIDER_LATENCY * 2,
+        "{} calls took {:?}, they were serialized",
+        CALLS,
+        elapsed
+    );
+}
@@ -0,0 +1,157 @@
+//! Streamed replies of the AI Studio adapter, read from server-sent events cut
+//! anywhere by the network.
+
+use std::{convert::Infallible, time::Duration};
+
+use axum::{Router, body::Body, response::Response, routing::post};
+use futures::{StreamExt, stream};
+use serde_json::{Value, json};
+use tokio::{net::TcpListener, spawn, time::sleep};
+use voicehanler_rs::{
+    domain::{
+        entities::{
+            agent_profile::SamplingParams,
+            history::{
+                history_event::{HistoryEvent, HistoryEventPayload},
+                history_member::HistoryMember,
+            },
+        },
+        ports::llm::{Llm, LlmProcessResponse, LlmRequest, LlmStreamEvent},
+    },
+    infrastructure::llm::gemini_adapter::GeminiAdapter,
+};
+
+fn chunk(delta: Value) -> Value {
+    json!({"choices": [{"index": 0, "delta": delta, "finish_reason": null}]})
+}
+
+/// The SSE body of a reply that speaks, then calls two tools whose arguments
+/// arrive in pieces.
+fn events() -> String {
+    let chunks = [
+        chunk(json!({"role": "assistant", "content": "Je regarde "})),
+        chunk(json!({"content": "ça, un instant."})),
+        chunk(json!({"tool_calls": [{
+            "index": 0,
+            "id": "call_lundi",
+            "type": "function",
+            "function": {"name": "opening_hours", "arguments": "{\"da"}
+        }]})),
+        chunk(json!({"tool_calls": [{
+            "index": 1,
+            "id": "call_mardi",
+            "type": "function",
+            "function": {"name": "opening_hours", "arguments": "{\"day\":"}
+        }]})),
+        chunk(json!({"tool_calls": [{"index": 0, "function": {"arguments": "y\":\"lundi\"}"}}]})),
+        chunk(json!({"tool_calls": [{"index": 1, "function": {"arguments": "\"mardi\"}"}}]})),
+        json!({
+            "choices": [{"index": 0, "delta": {}, "finish_reason": "tool_calls"}],
+            "usage": {"prompt_tokens": 12, "completion_tokens": 8, "total_tokens": 20}
+        }),
+    ];
+
+    let mut body: String = chunks
+        .iter()
+        .map(|chunk| format!("data: {}\n\n", chunk))
+        .collect();
+    body.push_str("data: [DONE]\n\n");
+    // nothing after the end of the stream is read
+    body.push_str(&format!(
+        "data: {}\n\n",
+        chunk(json!({"content": "Encore"}))
+    ));
+    body
+}
+
+/// Sends `events` in 7 byte pieces, cutting lines and JSON apart, and the `ç`
+/// in the middle of its two bytes.
+async fn chat_completions() -> Response {
+    let events = events().into_bytes();
+    let cedilla = events
+        .windows(2)
+        .position(|pair| pair == "ç".as_bytes())
+        .unwrap();
+
+    let mut cuts: Vec<usize> = (0..events.len()).step_by(7).collect();
+    cuts.push(cedilla + 1);
+    cuts.push(events.len());
+    cuts.sort();
+    let pieces: Vec<Vec<u8>> = cuts
+        .windows(2)
+        .map(|cut| events[cut[0]..cut[1]].to_vec())
+        .collect();
+    let body = stream::iter(pieces).then(|piece| async move {
+        sleep(Duration::from_millis(1)).await;
+        Ok::<_, Infallible>(piece)
+    });
+
+    Response::builder()
+        .header("content-type", "text/event-stream")
+        .body(Body::from_stream(body))
+        .unwrap()
+}
+
+fn request() -> LlmRequest {
+    LlmRequest {
+        model: "gemini-2.0-flash".to_string(),
+        sampling: SamplingParams::default(),
+        history_events: vec![HistoryEvent::new(HistoryEventPayload {
+            member: HistoryMember::User,
+            content: Some("Vous ouvrez lundi et mardi ?".to_string()),
+            created_at: chrono::Utc::now(),
+            tool_call: None,
+        })],
+        tools: Vec::new(),
+    }
+}
+
+#[tokio::test]
+async fn assembles_a_reply_streamed_in_pieces() {
+    let app = Router::new().route("/chat/completions", post(chat_completions));
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let endpoint = format!("http://{}", listener.local_addr().unwrap());
+    spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+    let llm = GeminiAdapter::new("test-key".to_string(), endpoint);
+    let mut stream = llm.process_stream(request()).await.unwrap();
+
+    let mut deltas = Vec::new();
+    let mut finished = Vec::<LlmProcessResponse>::new();
+    while let Some(event) = stream.next().await {
+        match event.unwrap() {
+            LlmStreamEvent::Delta(delta) => deltas.push(delta),
+            LlmStreamEvent::Finished(response) => finished.push(response),
+        }
+    }
+
+    assert_eq!(deltas, ["Je regarde ", "ça, un instant."]);
+    assert_eq!(finished.len(), 1);
+
+    let response = &finished[0];
+    assert_eq!(response.text.as_deref(), Some("Je regarde ça, un instant."));
+    assert_eq!(response.finish_reason.as_deref(), Some("tool_calls"));
+    assert_eq!(
+        response.usage.as_ref().map(|usage| usage.total_tokens),
+        Some(20)
+    );
+
+    let calls: Vec<(&str, &str, &str)> = response
+        .tool_calls
+        .iter()
+        .map(|call| {
+            (
+                call.id.as_str(),
+                call.name.as_str(),
+                call.arguments.as_str(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        calls,
+        [
+            ("call_lundi", "opening_hours", r#"{"day":"lundi"}"#),
+            ("call_mardi", "opening_hours", r#"{"day":"mardi"}"#),
+        ]
+    );
+}
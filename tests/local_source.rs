@@ -0,0 +1,63 @@
+mod common;
+
+use tokio::sync::mpsc::channel;
+use voicehanler_rs::{
+    domain::{
+        ports::audio_source::{AudioSource, InboundFrame, OutboundFrame},
+        utils::{
+            audio::codec::{Codec, l16::L16Le},
+            units::Samples,
+        },
+    },
+    infrastructure::{
+        audio_source::local_source_adapter::LocalAdapter, llm::mock_llm::MockLlm,
+        stt::mock_stt::MockStt, tts::mock_tts::MockTts,
+    },
+};
+
+use common::{Harness, tone};
+
+#[tokio::test(start_paused = true)]
+async fn keeps_samples_cut_across_binary_frames() {
+    let mut harness = Harness::new(MockStt::new(vec![]), MockLlm::new(vec![]), MockTts::new());
+    let audio = tone(200);
+
+    // an odd frame size cuts a sample every other frame
+    let frames = L16Le
+        .encode(&audio)
+        .unwrap()
+        .chunks(641)
+        .map(|chunk| InboundFrame::Binary(chunk.to_vec()))
+        .collect();
+    harness.stream(&LocalAdapter::new(), frames).await;
+
+    let received = harness
+        .audio_buffer
+        .user_audio(Samples::ZERO..Samples::of(&audio))
+        .expect("the whole tone was received");
+    assert_eq!(received, audio);
+}
+
+#[tokio::test(start_paused = true)]
+async fn sends_the_whole_clause_at_the_client_rate() {
+    let mut harness = Harness::new(MockStt::new(vec![]), MockLlm::new(vec![]), MockTts::new());
+    let (outbound, mut client) = channel(1024);
+    let source = LocalAdapter::new().connect(outbound);
+
+    let start = r#"{"event": "start", "sample_rate": 8000}"#;
+    harness
+        .stream(source.as_ref(), vec![InboundFrame::Text(start.to_string())])
+        .await;
+
+    // the resampler holds back the end of a stream until it is flushed
+    let clause = tone(300);
+    source.send_audio(&clause).await.unwrap();
+
+    let mut sent = 0;
+    while let Ok(frame) = client.try_recv() {
+        if let OutboundFrame::Binary(bytes) = frame {
+            sent += L16Le.decode(&bytes).unwrap().len();
+        }
+    }
+    assert_eq!(sent, clause.len() / 2);
+}
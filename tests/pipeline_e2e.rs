@@ -0,0 +1,388 @@
+mod common;
+
+use std::{sync::Arc, time::Duration};
+
+use serde_json::{Value, json};
+use voicehanler_rs::{
+    domain::{
+        entities::{barge_in::BargeInMode, tool_registry::ToolRegistry},
+        ports::{
+            llm::LlmToolCall,
+            tool::{Tool, ToolFuture},
+        },
+        utils::units::Millis,
+    },
+    infrastructure::{llm::mock_llm::MockLlm, stt::mock_stt::MockStt, tts::mock_tts::MockTts},
+};
+
+use common::{Harness, silence, tone, utterance};
+
+struct OpeningHours;
+
+impl Tool for OpeningHours {
+    fn name(&self) -> &str {
+        "opening_hours"
+    }
+
+    fn description(&self) -> &str {
+        "Horaires d'ouverture du magasin"
+    }
+
+    fn parameters(&self) -> Value {
+        json!({"type": "object", "properties": {"day": {"type": "string"}}})
+    }
+
+    fn call(&self, arguments: Value) -> ToolFuture {
+        Box::pin(async move { Ok(json!({"day": arguments["day"], "hours": "9h-19h"})) })
+    }
+}
+
+fn rendered_len(texts: &[String]) -> usize {
+    texts.iter().map(|text| MockTts::render(text).len()).sum()
+}
+
+#[tokio::test(start_paused = true)]
+async fn answers_a_single_turn() {
+    let mut harness = Harness::new(
+        MockStt::new(vec![Ok("Bonjour".to_string())]),
+        MockLlm::new(vec![Ok(MockLlm::text(
+            "Bonjour ! Que puis-je faire pour vous ?",
+        ))]),
+        MockTts::new(),
+    );
+
+    harness.speak(&utterance(800)).await;
+    harness.settle().await;
+
+    assert_eq!(
+        harness.transcript(),
+        vec![
+            "user: Bonjour",
+            "agent: Bonjour ! Que puis-je faire pour vous ?"
+        ]
+    );
+
+    let texts = harness.tts.texts().await;
+    assert_eq!(
+        texts.last().map(String::as_str),
+        Some("Que puis-je faire pour vous ?")
+    );
+    assert_eq!(
+        harness.outbound_audio().await.len(),
+        rendered_len(&texts[texts.len() - 2..])
+    );
+    assert_eq!(harness.stt.script().consumed().await, 1);
+}
+
+#[tokio::test(start_paused = true)]
+async fn runs_a_tool_before_answering() {
+    let mut tools = ToolRegistry::new(Duration::from_secs(1));
+    tools.register(Arc::new(OpeningHours));
+
+    let mut harness = Harness::new(
+        MockStt::new(vec![Ok("Vous ouvrez à quelle heure lundi ?".to_string())]),
+        MockLlm::new(vec![
+            Ok(MockLlm::tool_call("opening_hours", r#"{"day":"lundi"}"#)),
+            Ok(MockLlm::text("Le lundi nous ouvrons de 9h à 19h.")),
+        ]),
+        MockTts::new(),
+    );
+    harness.tools = tools;
+
+    harness.speak(&utterance(1200)).await;
+    harness.settle().await;
+
+    assert_eq!(
+        harness.transcript(),
+        vec![
+            "user: Vous ouvrez à quelle heure lundi ?",
+            r#"tool_call: opening_hours({"day":"lundi"}) -> {"day":"lundi","hours":"9h-19h"}"#,
+            "agent: Le lundi nous ouvrons de 9h à 19h.",
+        ]
+    );
+
+    let requests = harness.llm.requests().await;
+    let last = requests.last().expect("the LLM was called");
+    assert_eq!(last.tools.len(), 1);
+    assert!(
+        last.history_events
+            .iter()
+            .any(|event| event.tool_call.is_some())
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn tells_the_model_what_it_said_before_its_tool_calls() {
+    let mut tools = ToolRegistry::new(Duration::from_secs(1));
+    tools.register(Arc::new(OpeningHours));
+
+    let mut round = MockLlm::tool_call("opening_hours", r#"{"day":"lundi"}"#);
+    round.text = Some("Je regarde les horaires.".to_string());
+    round.tool_calls.push(LlmToolCall {
+        id: "call_mardi".to_string(),
+        name: "opening_hours".to_string(),
+        arguments: r#"{"day":"mardi"}"#.to_string(),
+    });
+
+    let mut harness = Harness::new(
+        MockStt::new(vec![Ok("Vous ouvrez lundi et mardi ?".to_string())]),
+        MockLlm::new(vec![
+            Ok(round),
+            Ok(MockLlm::text("Nous ouvrons de 9h à 19h ces deux jours.")),
+        ]),
+        MockTts::new(),
+    );
+    harness.tools = tools;
+
+    harness.speak(&utterance(1200)).await;
+    harness.settle().await;
+
+    assert_eq!(
+        harness.transcript(),
+        vec![
+            "user: Vous ouvrez lundi et mardi ?",
+            "agent: Je regarde les horaires.",
+            r#"tool_call: opening_hours({"day":"lundi"}) -> {"day":"lundi","hours":"9h-19h"}"#,
+            r#"tool_call: opening_hours({"day":"mardi"}) -> {"day":"mardi","hours":"9h-19h"}"#,
+            "agent: Nous ouvrons de 9h à 19h ces deux jours.",
+        ]
+    );
+
+    // one agent event for the round, before both results
+    let requests = harness.llm.requests().await;
+    let members: Vec<String> = requests[1]
+        .history_events
+        .iter()
+        .map(|event| match &event.content {
+            Some(content) => format!("{}: {}", event.member, content),
+            None => event.member.to_string(),
+        })
+        .collect();
+    assert_eq!(
+        members,
+        vec![
+            "user: Vous ouvrez lundi et mardi ?",
+            "agent: Je regarde les horaires.",
+            "tool_call",
+            "tool_call",
+        ]
+    );
+    assert_eq!(
+        harness.tts.texts().await,
+        vec![
+            "Je regarde les horaires.",
+            "Nous ouvrons de 9h à 19h ces deux jours."
+        ]
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn keeps_the_conversation_across_turns() {
+    let mut harness = Harness::new(
+        MockStt::new(vec![
+            Ok("Je voudrais un rendez-vous".to_string()),
+            Ok("Demain matin".to_string()),
+        ]),
+        MockLlm::new(vec![
+            Ok(MockLlm::text("Pour quand ?")),
+            Ok(MockLlm::text("C'est noté pour demain matin.")),
+        ]),
+        MockTts::new(),
+    );
+
+    harness.speak(&utterance(1000)).await;
+    harness.speak(&utterance(600)).await;
+    harness.settle().await;
+
+    assert_eq!(
+        harness.transcript(),
+        vec![
+            "user: Je voudrais un rendez-vous",
+            "agent: Pour quand ?",
+            "user: Demain matin",
+            "agent: C'est noté pour demain matin.",
+        ]
+    );
+
+    let requests = harness.llm.requests().await;
+    let last = requests.last().expect("the LLM was called");
+    let contents: Vec<_> = last
+        .history_events
+        .iter()
+        .filter_map(|event| event.content.as_deref())
+        .collect();
+    assert_eq!(
+        contents,
+        vec!["Je voudrais un rendez-vous", "Pour quand ?", "Demain matin"]
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn answers_after_a_long_full_stop() {
+    let mut harness = Harness::new(
+        MockStt::new(vec![Ok("12 rue des Lilas".to_string())]),
+        MockLlm::new(vec![Ok(MockLlm::text("C'est noté."))]),
+        MockTts::new(),
+    );
+    // slow dictation, the answer waits 8 s of silence before it is released
+    harness.agent.vad.full_stop_ms = Some(Millis(8000));
+
+    harness.speak(&[tone(1000), silence(8500)].concat()).await;
+    harness.settle().await;
+
+    assert_eq!(
+        harness.transcript(),
+        vec!["user: 12 rue des Lilas", "agent: C'est noté."]
+    );
+    assert_eq!(
+        harness.outbound_audio().await.len(),
+        rendered_len(&harness.tts.texts().await)
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn stays_silent_when_transcription_fails() {
+    let mut harness = Harness::new(
+        MockStt::new(vec![Err("provider unavailable".to_string())]),
+        MockLlm::new(vec![Ok(MockLlm::text("Jamais prononcé."))]),
+        MockTts::new(),
+    );
+
+    harness.speak(&utterance(800)).await;
+    harness.settle().await;
+
+    assert!(harness.transcript().is_empty());
+    assert!(harness.outbound_audio().await.is_empty());
+    assert!(harness.llm.requests().await.is_empty());
+}
+
+#[tokio::test(start_paused = true)]
+async fn drops_the_reply_when_synthesis_fails() {
+    let mut harness = Harness::new(
+        MockStt::new(vec![Ok("Allô ?".to_string())]),
+        MockLlm::new(vec![Ok(MockLlm::text("Oui, je vous écoute."))]),
+        MockTts::new().failing_on("Oui, je vous écoute."),
+    );
+
+    harness.speak(&utterance(500)).await;
+    harness.settle().await;
+
+    assert!(harness.outbound_audio().await.is_empty());
+    assert!(
+        !harness
+            .transcript()
+            .iter()
+            .any(|line| line.starts_with("agent:"))
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn caller_interrupts_a_long_answer() {
+    let answer = "Nous proposons trois formules. La première est mensuelle. \
+                  La deuxième est annuelle. La troisième est sans engagement.";
+
+    let mut harness = Harness::new(
+        MockStt::new(vec![
+            Ok("Quelles sont vos offres ?".to_string()),
+            Ok("Stop".to_string()),
+        ]),
+        MockLlm::new(vec![
+            Ok(MockLlm::text(answer)),
+            Ok(MockLlm::text("D'accord.")),
+        ]),
+        MockTts::new(),
+    );
+    harness.barge_in = BargeInMode::Immediate;
+
+    // the caller speaks again one second into the answer
+    let mut audio = tone(800);
+    audio.extend(silence(3000));
+    harness.speak(&audio).await;
+    harness.speak(&tone(400)).await;
+    harness.speak(&silence(5000)).await;
+    harness.settle().await;
+
+    assert_eq!(harness.clears().await, 1);
+
+    let transcript = harness.transcript();
+    assert_eq!(transcript[0], "user: Quelles sont vos offres ?");
+    assert!(transcript[1].starts_with("agent: Nous"));
+    assert_ne!(transcript[1], format!("agent: {}", answer));
+    assert_eq!(transcript[2..], ["user: Stop", "agent: D'accord."]);
+}
+
+#[tokio::test(start_paused = true)]
+async fn caller_interrupts_the_answer_after_a_tool_round() {
+    let mut tools = ToolRegistry::new(Duration::from_secs(1));
+    tools.register(Arc::new(OpeningHours));
+
+    let mut round = MockLlm::tool_call("opening_hours", r#"{"day":"lundi"}"#);
+    round.text = Some("Je regarde les horaires.".to_string());
+    let answer = "Nous ouvrons de 9h à 19h le lundi. Le mardi nous ouvrons de 10h à 18h. \
+                  Le mercredi nous sommes fermés toute la journée.";
+
+    let mut harness = Harness::new(
+        MockStt::new(vec![
+            Ok("Vous ouvrez quand ?".to_string()),
+            Ok("Stop".to_string()),
+        ]),
+        MockLlm::new(vec![
+            Ok(round),
+            Ok(MockLlm::text(answer)),
+            Ok(MockLlm::text("D'accord.")),
+        ]),
+        MockTts::new(),
+    );
+    harness.tools = tools;
+    harness.barge_in = BargeInMode::Immediate;
+
+    // the round is heard, the caller speaks again during the answer
+    let mut audio = tone(800);
+    audio.extend(silence(3000));
+    harness.speak(&audio).await;
+    harness.speak(&tone(400)).await;
+    harness.speak(&silence(5000)).await;
+    harness.settle().await;
+
+    assert_eq!(harness.clears().await, 1);
+
+    let transcript = harness.transcript();
+    assert_eq!(
+        transcript[..3],
+        [
+            "user: Vous ouvrez quand ?",
+            "agent: Je regarde les horaires.",
+            r#"tool_call: opening_hours({"day":"lundi"}) -> {"day":"lundi","hours":"9h-19h"}"#,
+        ]
+    );
+    // the answer is cut where the caller stopped listening, and kept once
+    assert!(transcript[3].starts_with("agent: Nous ouvrons"));
+    assert!(answer.starts_with(&transcript[3]["agent: ".len()..]));
+    assert_ne!(transcript[3], format!("agent: {}", answer));
+    assert_eq!(transcript[4..], ["user: Stop", "agent: D'accord."]);
+}
+
+#[tokio::test(start_paused = true)]
+async fn agent_profile_overrides_the_barge_in_mode() {
+    let answer = "Nous proposons trois formules. La première est mensuelle. \
+                  La deuxième est annuelle. La troisième est sans engagement.";
+
+    let mut harness = Harness::new(
+        MockStt::new(vec![Ok("Quelles sont vos offres ?".to_string())]),
+        MockLlm::new(vec![Ok(MockLlm::text(answer))]),
+        MockTts::new(),
+    );
+    harness.barge_in = BargeInMode::Immediate;
+    let profile = r#"{"name": "dictation", "barge_in": "off"}"#;
+    harness.agent = serde_json::from_str(profile).unwrap();
+
+    // the caller talks over the answer, which goes on
+    let mut audio = tone(800);
+    audio.extend(silence(3000));
+    harness.speak(&audio).await;
+    harness.speak(&tone(400)).await;
+    harness.settle().await;
+
+    assert_eq!(harness.clears().await, 0);
+    assert_eq!(harness.transcript()[1], format!("agent: {}", answer));
+}
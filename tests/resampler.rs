@@ -0,0 +1,140 @@
+//! Streaming resampler between the rates of the sources and the TTS.
+
+use std::f64::consts::PI;
+
+use voicehanler_rs::domain::utils::{
+    audio::resampler::{Resampler, SUPPORTED_RATES},
+    units::SampleRate,
+};
+
+fn tone(rate: SampleRate, frequency: f64, amplitude: f64, samples: usize) -> Vec<i16> {
+    (0..samples)
+        .map(|n| (amplitude * (2.0 * PI * frequency * n as f64 / rate.0 as f64).sin()) as i16)
+        .collect()
+}
+
+/// Root mean square of the middle of `samples`, away from the filter's edges.
+fn rms(samples: &[i16]) -> f64 {
+    let middle = &samples[samples.len() / 4..samples.len() * 3 / 4];
+    let power: f64 = middle.iter().map(|&s| (s as f64).powi(2)).sum();
+    (power / middle.len() as f64).sqrt()
+}
+
+/// Amplitude of the `frequency` component of the middle of `samples`.
+fn amplitude_at(samples: &[i16], rate: SampleRate, frequency: f64) -> f64 {
+    let middle = &samples[samples.len() / 4..samples.len() * 3 / 4];
+    let (mut re, mut im) = (0.0, 0.0);
+    for (n, &sample) in middle.iter().enumerate() {
+        let angle = 2.0 * PI * frequency * n as f64 / rate.0 as f64;
+        re += sample as f64 * angle.cos();
+        im += sample as f64 * angle.sin();
+    }
+    2.0 * (re * re + im * im).sqrt() / middle.len() as f64
+}
+
+#[test]
+fn outputs_exactly_the_duration_of_the_input() {
+    for from in SUPPORTED_RATES {
+        for to in SUPPORTED_RATES {
+            let input = tone(from, 440.0, 8000.0, from.0 as usize / 10 + 7);
+            let output = Resampler::new(from, to).unwrap().resample(&input);
+
+            let expected = (input.len() as u64 * to.hz()).div_ceil(from.hz());
+            assert_eq!(output.len() as u64, expected, "{} to {}", from, to);
+        }
+    }
+}
+
+#[test]
+fn chunked_streams_match_the_whole_signal() {
+    let pairs = [
+        (SampleRate::TELEPHONY, SampleRate::PIPELINE),
+        (SampleRate::PIPELINE, SampleRate::TELEPHONY),
+        (SampleRate(44_100), SampleRate::PIPELINE),
+        (SampleRate(22_050), SampleRate(48_000)),
+    ];
+
+    for (from, to) in pairs {
+        let input = tone(from, 1000.0, 12000.0, from.0 as usize / 2);
+        let whole = Resampler::new(from, to).unwrap().resample(&input);
+
+        let mut resampler = Resampler::new(from, to).unwrap();
+        let mut chunked = Vec::new();
+        for chunk in input.chunks(from.0 as usize / 50) {
+            chunked.extend(resampler.process(chunk));
+        }
+        chunked.extend(resampler.flush());
+        assert_eq!(chunked, whole, "{} to {}", from, to);
+
+        // odd sizes cut the frames across the phases
+        let mut chunked = Vec::new();
+        for chunk in input.chunks(37) {
+            chunked.extend(resampler.process(chunk));
+        }
+        chunked.extend(resampler.flush());
+        assert_eq!(chunked, whole, "{} to {} after a flush", from, to);
+    }
+}
+
+#[test]
+fn keeps_the_tones_of_the_voice_band() {
+    for from in SUPPORTED_RATES {
+        for to in SUPPORTED_RATES {
+            let input = tone(from, 1000.0, 10000.0, from.0 as usize / 2);
+            let output = Resampler::new(from, to).unwrap().resample(&input);
+
+            let amplitude = amplitude_at(&output, to, 1000.0);
+            assert!(
+                (amplitude - 10000.0).abs() < 100.0,
+                "{} to {}: {}",
+                from,
+                to,
+                amplitude
+            );
+        }
+    }
+}
+
+#[test]
+fn filters_out_what_the_slower_rate_cannot_carry() {
+    // 6 kHz is above the 4 kHz Nyquist frequency of a phone line, it would
+    // fold back as a 2 kHz tone
+    let input = tone(SampleRate(48_000), 6000.0, 16000.0, 24_000);
+    let output = Resampler::new(SampleRate(48_000), SampleRate::TELEPHONY)
+        .unwrap()
+        .resample(&input);
+
+    assert!(rms(&output) < 16000.0 / 100.0, "{}", rms(&output));
+}
+
+#[test]
+fn round_trips_with_little_distortion() {
+    let input: Vec<i16> = tone(SampleRate::PIPELINE, 440.0, 8000.0, 16_000)
+        .iter()
+        .zip(tone(SampleRate::PIPELINE, 2500.0, 4000.0, 16_000))
+        .map(|(a, b)| a + b)
+        .collect();
+
+    let up = Resampler::new(SampleRate::PIPELINE, SampleRate(44_100))
+        .unwrap()
+        .resample(&input);
+    let back = Resampler::new(SampleRate(44_100), SampleRate::PIPELINE)
+        .unwrap()
+        .resample(&up);
+    assert_eq!(back.len(), input.len());
+
+    let error: Vec<i16> = input.iter().zip(&back).map(|(a, b)| a - b).collect();
+    let snr = 20.0 * (rms(&input) / rms(&error)).log10();
+    assert!(snr > 40.0, "{} dB", snr);
+}
+
+#[test]
+fn passes_the_pipeline_rate_through_and_rejects_other_rates() {
+    let input = tone(SampleRate::PIPELINE, 440.0, 8000.0, 320);
+    let mut resampler = Resampler::new(SampleRate::PIPELINE, SampleRate::PIPELINE).unwrap();
+    assert_eq!(resampler.process(&input), input);
+    assert!(resampler.flush().is_empty());
+
+    assert!(Resampler::new(SampleRate(11_025), SampleRate::PIPELINE).is_err());
+    assert!(Resampler::new(SampleRate::PIPELINE, SampleRate(96_000)).is_err());
+}
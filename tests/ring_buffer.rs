@@ -0,0 +1,135 @@
+//! The session audio buffer keeps the active turn only, the whole call goes
+//! through its sink.
+
+use std::{
+    f32::consts::PI,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use voicehanler_rs::{
+    domain::{
+        entities::audio_buffer::AudioBuffer,
+        ports::{
+            audio_sink::AudioSink,
+            vad::{Vad, VadEvent},
+        },
+        utils::{
+            ring_buffer::RingBuffer,
+            units::{Millis, SampleRate, Samples},
+        },
+    },
+    infrastructure::{capture::wav_sink::WavSink, vad::local_vad::LocalVadAdapter},
+};
+
+#[derive(Clone, Default)]
+struct CollectingSink(Arc<Mutex<Vec<i16>>>);
+
+impl AudioSink for CollectingSink {
+    fn write(&mut self, samples: &[i16]) {
+        self.0.lock().unwrap().extend_from_slice(samples);
+    }
+}
+
+fn tone(ms: usize) -> Vec<i16> {
+    (0..ms * 16)
+        .map(|n| (4000.0 * (2.0 * PI * 200.0 * n as f32 / 16_000.0).sin()) as i16)
+        .collect()
+}
+
+#[test]
+fn offsets_survive_drops_and_wraps() {
+    let mut ring = RingBuffer::new();
+    ring.push(&(0..100).collect::<Vec<i16>>());
+    ring.discard_before(Samples(40));
+
+    assert_eq!(ring.oldest(), Samples(40));
+    assert_eq!(ring.written(), Samples(100));
+    assert_eq!(ring.retained(), 60);
+    assert_eq!(
+        ring.get(Samples(40)..Samples(43)).unwrap().as_ref(),
+        &[40, 41, 42]
+    );
+    assert!(ring.get(Samples(30)..Samples(50)).is_none());
+    assert!(ring.get(Samples(90)..Samples(110)).is_none());
+
+    // the freed slots are reused, so the next samples wrap around the ring
+    ring.push(&(100..130).collect::<Vec<i16>>());
+    let wrapped: Vec<i16> = (85..125).collect();
+    assert_eq!(
+        ring.get(Samples(85)..Samples(125)).unwrap().as_ref(),
+        wrapped.as_slice()
+    );
+
+    ring.discard_before(Samples(1000));
+    assert_eq!(ring.oldest(), Samples(130));
+    assert_eq!(ring.retained(), 0);
+}
+
+#[test]
+fn a_long_call_keeps_only_the_active_turn() {
+    let sink = CollectingSink::default();
+    let mut audio_buffer = AudioBuffer::new()
+        .with_retention(Millis(1000))
+        .with_sink(sink.clone());
+    let mut vad = LocalVadAdapter::new();
+
+    // ten minutes of one second sentences every seven seconds
+    let sentence = [tone(1000), vec![0; 6000 * 16]].concat();
+    let mut most_retained = 0;
+    let mut turns = 0;
+    for _ in 0..86 {
+        for chunk in sentence.chunks(320) {
+            audio_buffer.push_user(chunk);
+
+            loop {
+                match vad.process_audio(&mut audio_buffer) {
+                    VadEvent::WaitingMoreChunks => break,
+                    VadEvent::SpeechPaused(start, end) => {
+                        assert!(audio_buffer.user_audio(start..end).is_some());
+                        turns += 1;
+                    }
+                    _ => {}
+                }
+            }
+            most_retained = most_retained.max(audio_buffer.user.retained());
+        }
+    }
+
+    assert_eq!(turns, 86);
+    assert_eq!(sink.0.lock().unwrap().len(), 86 * sentence.len());
+    // the retention plus the longest turn, far from the whole call
+    let bound = SampleRate::PIPELINE
+        .samples(Millis(1000 + 1000 + 2500))
+        .as_usize();
+    assert!(most_retained < bound, "{} >= {}", most_retained, bound);
+}
+
+#[tokio::test]
+async fn the_wav_sink_writes_the_whole_call() {
+    let dir = std::env::temp_dir().join(format!("wav-sink-{}", std::process::id()));
+    let (mut sink, path) = WavSink::create(&dir, "local", uuid::Uuid::nil())
+        .await
+        .unwrap();
+
+    let audio = tone(500);
+    for chunk in audio.chunks(320) {
+        sink.write(chunk);
+    }
+    drop(sink);
+
+    let mut written = Vec::new();
+    for _ in 0..100 {
+        if let Ok(mut reader) = hound::WavReader::open(&path)
+            && reader.len() as usize == audio.len()
+        {
+            assert_eq!(reader.spec().sample_rate, 16_000);
+            written = reader.samples::<i16>().map(Result::unwrap).collect();
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    let _ = std::fs::remove_dir_all(&dir);
+
+    assert_eq!(written, audio);
+}
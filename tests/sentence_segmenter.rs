@@ -0,0 +1,101 @@
+//! Clauses cut from a streamed LLM reply before they are spoken.
+
+use voicehanler_rs::domain::utils::segmenter::SentenceSegmenter;
+
+/// Every clause of `deltas` pushed in turn, the flushed rest included.
+fn clauses(deltas: &[&str]) -> Vec<String> {
+    let mut segmenter = SentenceSegmenter::new();
+    let mut clauses: Vec<String> = deltas
+        .iter()
+        .flat_map(|delta| segmenter.push(delta))
+        .collect();
+    clauses.extend(segmenter.flush());
+    clauses
+}
+
+#[test]
+fn splits_on_terminators_with_french_typography() {
+    assert_eq!(
+        clauses(&["Bonjour ! Comment allez-vous ? Très bien; merci : au revoir."]),
+        [
+            "Bonjour !",
+            "Comment allez-vous ?",
+            "Très bien;",
+            "merci :",
+            "au revoir."
+        ]
+    );
+    assert_eq!(
+        clauses(&["Il a dit \"non.\" Puis il est parti… Voilà"]),
+        ["Il a dit \"non.\"", "Puis il est parti…", "Voilà"]
+    );
+}
+
+#[test]
+fn keeps_abbreviations_and_initials_in_their_sentence() {
+    assert_eq!(
+        clauses(&["M. Dupont et Mme. Durand vous attendent av. Foch. Le Dr. J. Martin aussi."]),
+        [
+            "M. Dupont et Mme. Durand vous attendent av. Foch.",
+            "Le Dr. J. Martin aussi."
+        ]
+    );
+}
+
+#[test]
+fn keeps_decimals_whole() {
+    assert_eq!(
+        clauses(&["Le taux est de 3.5 pour cent. Il passera à 4.25 en mai."]),
+        ["Le taux est de 3.5 pour cent.", "Il passera à 4.25 en mai."]
+    );
+}
+
+#[test]
+fn waits_for_the_character_after_a_terminator() {
+    let mut segmenter = SentenceSegmenter::new();
+
+    // a dot at the end of a delta may be a decimal point
+    assert!(segmenter.push("Il coûte 3.").is_empty());
+    assert!(segmenter.push("5 euros.").is_empty());
+    assert_eq!(segmenter.push(" Merci"), ["Il coûte 3.5 euros."]);
+    assert_eq!(segmenter.flush().as_deref(), Some("Merci"));
+    assert_eq!(segmenter.flush(), None);
+}
+
+#[test]
+fn chunks_cut_mid_word_give_the_same_clauses() {
+    let reply = "Bonjour Mme. Petit ! Votre rendez-vous est confirmé. Il dure 1.5 heure";
+    let whole = clauses(&[reply]);
+
+    let deltas: Vec<String> = reply
+        .chars()
+        .collect::<Vec<char>>()
+        .chunks(3)
+        .map(|chunk| chunk.iter().collect())
+        .collect();
+    let deltas: Vec<&str> = deltas.iter().map(String::as_str).collect();
+
+    assert_eq!(clauses(&deltas), whole);
+    assert_eq!(
+        whole,
+        [
+            "Bonjour Mme. Petit !",
+            "Votre rendez-vous est confirmé.",
+            "Il dure 1.5 heure"
+        ]
+    );
+}
+
+#[test]
+fn splits_long_clauses_on_commas_only() {
+    assert_eq!(
+        clauses(&[
+            "Oui, bien sûr. Nous avons des créneaux le lundi matin et le mardi après-midi, ou bien le jeudi."
+        ]),
+        [
+            "Oui, bien sûr.",
+            "Nous avons des créneaux le lundi matin et le mardi après-midi,",
+            "ou bien le jeudi."
+        ]
+    );
+}
@@ -0,0 +1,85 @@
+use std::{path::Path, time::Duration};
+
+use voicehanler_rs::{
+    application::{
+        env::capture::CaptureEnv,
+        http::app_state::AppState,
+        registry::ProviderRegistry,
+        simulator::{self, scenario::Scenario},
+    },
+    domain::entities::{
+        agent_profile::VadSettings, agent_registry::AgentRegistry, audio_buffer::DEFAULT_RETENTION,
+        barge_in::BargeInMode, pipeline::pool_manager::PoolManager, tool_registry::ToolRegistry,
+    },
+};
+
+#[tokio::test(start_paused = true)]
+async fn plays_the_rendez_vous_scenario_with_mocks() {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("scenarios/rendez-vous.yaml");
+    let scenario = Scenario::load(&path).unwrap();
+    let state = AppState::new(
+        PoolManager::new(1),
+        ProviderRegistry::new(),
+        ToolRegistry::new(Duration::from_secs(1)),
+        AgentRegistry::new(
+            Vec::new(),
+            None,
+            VadSettings::default(),
+            BargeInMode::Immediate,
+        )
+        .unwrap(),
+        CaptureEnv {
+            capture_dir: None,
+            capture_audio: false,
+        },
+        DEFAULT_RETENTION,
+    );
+
+    let report = simulator::run(&state, &scenario).await.unwrap();
+
+    assert_eq!(report.scenario.as_deref(), Some("prise de rendez-vous"));
+    assert!(report.slow_turns(6000).is_empty(), "{:#?}", report);
+
+    let turns: Vec<(Option<&str>, Option<&str>, bool)> = report
+        .turns
+        .iter()
+        .map(|turn| {
+            (
+                turn.transcript.as_deref(),
+                turn.answer.as_deref(),
+                turn.interrupted,
+            )
+        })
+        .collect();
+    assert_eq!(
+        turns,
+        vec![
+            (
+                Some("Je voudrais prendre rendez-vous"),
+                Some(
+                    "Bien sûr. Pour quel jour souhaitez-vous venir ? Nous avons de la place toute la semaine, le matin comme l'après-midi."
+                ),
+                // the second turn cuts it after 1 s
+                true,
+            ),
+            (
+                Some("Demain matin"),
+                Some("Très bien, c'est noté pour demain matin."),
+                false,
+            ),
+            (
+                Some("Non attendez"),
+                Some("D'accord, je vous écoute."),
+                false,
+            ),
+        ]
+    );
+    assert!(
+        report
+            .turns
+            .iter()
+            .all(|turn| turn.vad_endpoint_ms.is_some()
+                && turn.stt_ms == Some(300)
+                && turn.llm_ms == Some(500))
+    );
+}
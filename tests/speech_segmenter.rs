@@ -0,0 +1,140 @@
+//! The turn bookkeeping shared by the VADs, driven by a scripted classifier.
+
+mod common;
+
+use voicehanler_rs::{
+    domain::{
+        entities::agent_profile::VadSettings,
+        ports::vad::VadEvent,
+        utils::units::{Millis, Samples},
+    },
+    infrastructure::vad::speech_segmenter::SpeechSegmenter,
+};
+
+const FRAME: u64 = 320;
+
+/// `frames` frames of speech (1) or silence (0), told apart by the classifier.
+fn frames(frames: usize, speech: bool) -> Vec<i16> {
+    vec![speech as i16; frames * FRAME as usize]
+}
+
+/// Every event with the frame it was raised at, the audio pushed `chunk`
+/// samples at a time.
+fn events(segmenter: &mut SpeechSegmenter, audio: &[i16], chunk: usize) -> Vec<(VadEvent, u64)> {
+    common::raise(audio, chunk, |audio_buffer| {
+        segmenter.process(audio_buffer, |frame| frame[0] != 0)
+    })
+    .into_iter()
+    .map(|raised| (raised.event, raised.cursor.0 / FRAME))
+    .collect()
+}
+
+#[test]
+fn cuts_a_turn_with_its_pre_roll_and_trailer() {
+    let mut segmenter = SpeechSegmenter::new(Samples(FRAME));
+    let audio = [frames(10, false), frames(20, true), frames(150, false)].concat();
+
+    // 3 frames of pre-roll before the first speech frame, a pause ending 2
+    // frames after the speech and a full stop past 2 s (100 frames) of silence
+    assert_eq!(
+        events(&mut segmenter, &audio, FRAME as usize),
+        vec![
+            (VadEvent::SpeechStarted, 11),
+            (
+                VadEvent::SpeechPaused(Samples(8 * FRAME), Samples(33 * FRAME)),
+                33
+            ),
+            (VadEvent::SpeechFullStop, 132),
+        ]
+    );
+}
+
+#[test]
+fn pauses_once_per_silence_and_resumes_on_speech() {
+    let mut segmenter = SpeechSegmenter::new(Samples(FRAME));
+    let audio = [
+        frames(20, true),
+        frames(30, false),
+        frames(10, true),
+        frames(120, false),
+    ]
+    .concat();
+
+    assert_eq!(
+        events(&mut segmenter, &audio, FRAME as usize),
+        vec![
+            (VadEvent::SpeechStarted, 1),
+            (
+                VadEvent::SpeechPaused(Samples::ZERO, Samples(23 * FRAME)),
+                23
+            ),
+            (VadEvent::SpeechResumed, 51),
+            // the turn keeps its start across the resumption
+            (
+                VadEvent::SpeechPaused(Samples::ZERO, Samples(63 * FRAME)),
+                63
+            ),
+            (VadEvent::SpeechFullStop, 162),
+        ]
+    );
+}
+
+#[test]
+fn gives_the_same_events_whatever_the_chunk_size() {
+    let audio = [frames(5, false), frames(25, true), frames(110, false)].concat();
+
+    let whole = events(
+        &mut SpeechSegmenter::new(Samples(FRAME)),
+        &audio,
+        audio.len(),
+    );
+    for chunk in [1, 100, 333, 960] {
+        assert_eq!(
+            events(&mut SpeechSegmenter::new(Samples(FRAME)), &audio, chunk),
+            whole,
+            "{} samples chunks",
+            chunk
+        );
+    }
+}
+
+#[test]
+fn applies_the_turn_settings_but_keeps_its_frame_size() {
+    let mut segmenter = SpeechSegmenter::new(Samples(FRAME));
+    segmenter.configure(&VadSettings {
+        full_stop_ms: Millis(500),
+        pre_roll_frames: 1,
+        pause_trailer_frames: 4,
+        // only the detectors read the frame length
+        frame_ms: Millis(32),
+        ..VadSettings::default()
+    });
+    let audio = [frames(10, false), frames(20, true), frames(40, false)].concat();
+
+    assert_eq!(
+        events(&mut segmenter, &audio, FRAME as usize),
+        vec![
+            (VadEvent::SpeechStarted, 11),
+            (
+                VadEvent::SpeechPaused(Samples(10 * FRAME), Samples(35 * FRAME)),
+                35
+            ),
+            (VadEvent::SpeechFullStop, 57),
+        ]
+    );
+
+    // pre-roll and trailer are counted in frames, twice as long now
+    segmenter.set_frame_size(Samples(2 * FRAME));
+    let audio = [frames(10, false), frames(20, true), frames(80, false)].concat();
+    assert_eq!(
+        events(&mut segmenter, &audio, FRAME as usize),
+        vec![
+            (VadEvent::SpeechStarted, 12),
+            (
+                VadEvent::SpeechPaused(Samples(10 * FRAME), Samples(40 * FRAME)),
+                40
+            ),
+            (VadEvent::SpeechFullStop, 58),
+        ]
+    );
+}
@@ -0,0 +1,99 @@
+//! Sample counts, durations and rates, and the wall-clock time of the caller
+//! audio they date.
+
+mod common;
+
+use chrono::{TimeDelta, TimeZone, Utc};
+use voicehanler_rs::{
+    domain::{
+        entities::timeline::Timeline,
+        utils::{
+            audio::Audio,
+            units::{Millis, SampleRate, Samples},
+        },
+    },
+    infrastructure::{llm::mock_llm::MockLlm, stt::mock_stt::MockStt, tts::mock_tts::MockTts},
+};
+
+use common::{Harness, silence, utterance};
+
+#[test]
+fn converts_between_samples_and_millis() {
+    assert_eq!(SampleRate::PIPELINE.samples(Millis(32)), Samples(512));
+    assert_eq!(SampleRate::TELEPHONY.samples(Millis(20)), Samples(160));
+    assert_eq!(SampleRate::PIPELINE.millis(Samples(8000)), Millis(500));
+    assert_eq!(SampleRate::TELEPHONY.duration(Samples(1)).as_micros(), 125);
+
+    let padded = Audio::add_padding(&[1, 2], SampleRate::TELEPHONY, Millis(250), Millis(125));
+    assert_eq!(padded.len(), 2000 + 2 + 1000);
+    assert_eq!(&padded[1999..2003], &[0, 1, 2, 0]);
+}
+
+#[test]
+fn dates_samples_from_the_audio_clock_despite_jitter() {
+    let origin = Utc.with_ymd_and_hms(2026, 1, 5, 9, 30, 0).unwrap();
+    let chunk = Samples(320);
+    let mut timeline = Timeline::new(SampleRate::PIPELINE);
+    assert_eq!(timeline.at(Samples::ZERO), None);
+
+    // 20 ms chunks arriving up to 300 ms late, then in a burst
+    for (index, late_ms) in [0, 120, 300, 40, 0, 10].into_iter().enumerate() {
+        let arrival = origin + TimeDelta::milliseconds(20 * (index as i64 + 1) + late_ms);
+        let gap = timeline.record(chunk * index as u64, chunk, arrival);
+        assert_eq!(gap, Samples::ZERO, "chunk {}", index);
+    }
+
+    assert_eq!(timeline.origin(), Some(origin));
+    assert_eq!(
+        timeline.at(Samples(1600)),
+        Some(origin + TimeDelta::milliseconds(100))
+    );
+    assert_eq!(
+        timeline.at(Samples(1)),
+        Some(origin + TimeDelta::microseconds(62) + TimeDelta::nanoseconds(500))
+    );
+}
+
+#[test]
+fn re_anchors_after_a_stall() {
+    let origin = Utc.with_ymd_and_hms(2026, 1, 5, 9, 30, 0).unwrap();
+    let chunk = Samples(320);
+    let mut timeline = Timeline::new(SampleRate::PIPELINE);
+
+    timeline.record(Samples::ZERO, chunk, origin + TimeDelta::milliseconds(20));
+    // the stream froze for three seconds after the first chunk
+    let gap = timeline.record(chunk, chunk, origin + TimeDelta::milliseconds(3040));
+    assert_eq!(gap, SampleRate::PIPELINE.samples(Millis(3000)));
+
+    // audio before the stall keeps its date, audio after it is dated from its arrival
+    assert_eq!(
+        timeline.at(Samples(160)),
+        Some(origin + TimeDelta::milliseconds(10))
+    );
+    assert_eq!(
+        timeline.at(chunk + Samples(160)),
+        Some(origin + TimeDelta::milliseconds(3030))
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn history_dates_the_turn_when_the_caller_spoke() {
+    let mut harness = Harness::new(
+        MockStt::new(vec![Ok("Bonjour".to_string())]),
+        MockLlm::new(vec![Ok(MockLlm::text("Bonjour, que puis-je faire ?"))]),
+        MockTts::new(),
+    );
+
+    let mut audio = silence(1000);
+    audio.extend(utterance(800));
+    harness.speak(&audio).await;
+    harness.settle().await;
+
+    let origin = harness.audio_buffer.timeline.origin().unwrap();
+    let user = &harness.history.events[0];
+    assert_eq!(user.content.as_deref(), Some("Bonjour"));
+
+    // the pre-roll starts the turn a few frames before the tone
+    let spoken_after = (user.created_at - origin).num_milliseconds();
+    assert!((850..=1000).contains(&spoken_after), "{} ms", spoken_after);
+}
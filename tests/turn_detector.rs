@@ -0,0 +1,180 @@
+//! Ending the caller's turn from what they said rather than a fixed silence.
+
+mod common;
+
+use std::{collections::HashMap, sync::Arc};
+
+use clap::Parser;
+use voicehanler_rs::{
+    application::env::turn_detector::TurnDetectorEnv,
+    domain::{
+        entities::agent_profile::VadSettings, ports::audio_source::SessionEvent,
+        ports::turn_detector::TurnDetector, utils::units::Millis,
+    },
+    infrastructure::{
+        llm::mock_llm::MockLlm,
+        stt::mock_stt::MockStt,
+        tts::mock_tts::MockTts,
+        turn_detector::{
+            semantic_turn_detector::SemanticTurnDetector, turn_classifier::TurnClassifier,
+        },
+    },
+};
+
+use common::{Harness, silence, tone};
+
+fn semantic() -> SemanticTurnDetector {
+    let mut detector = SemanticTurnDetector::new(Millis(300), Millis(6000));
+    detector.configure(&VadSettings::default());
+    detector
+}
+
+fn harness(transcript: &str) -> Harness {
+    Harness::new(
+        MockStt::new(vec![Ok(transcript.to_string())]),
+        MockLlm::new(vec![Ok(MockLlm::text("Très bien."))]),
+        MockTts::new(),
+    )
+}
+
+async fn full_stops(harness: &Harness) -> usize {
+    harness
+        .events()
+        .await
+        .iter()
+        .filter(|event| {
+            matches!(
+                event,
+                SessionEvent::Vad {
+                    kind: "speech_full_stop",
+                    ..
+                }
+            )
+        })
+        .count()
+}
+
+#[test]
+fn punctuation_and_trailing_words_move_the_end_of_turn() {
+    let detector = semantic();
+    let full_stop = VadSettings::default().full_stop_ms;
+
+    assert_eq!(detector.silence_ms(None), full_stop);
+    assert_eq!(detector.silence_ms(Some("Bonjour")), full_stop);
+    assert!(detector.silence_ms(Some("Je voudrais réserver une table.")) < full_stop);
+    assert!(detector.silence_ms(Some("Vous ouvrez lundi ?")) < full_stop);
+
+    for hanging in [
+        "Mon numéro c'est le…",
+        "Mon numéro c'est le...",
+        "Mon numéro c'est le",
+        "Je voudrais une table et.",
+        "I'd like a table for,",
+    ] {
+        assert!(
+            detector.silence_ms(Some(hanging)) > full_stop,
+            "{}",
+            hanging
+        );
+    }
+
+    assert_eq!(detector.max_silence_ms(), Millis(6000));
+}
+
+#[derive(Parser)]
+struct Deployment {
+    #[command(flatten)]
+    turn_detector: TurnDetectorEnv,
+}
+
+#[test]
+fn rejects_a_wait_the_full_stop_cannot_cover() {
+    let parse = |incomplete: &str| {
+        Deployment::parse_from(["voicehandler", "--turn-incomplete-ms", incomplete]).turn_detector
+    };
+
+    assert!(parse("10000").detector().is_ok());
+    assert!(parse("12000").detector().is_err());
+}
+
+#[test]
+fn a_classifier_replaces_the_rules() {
+    let classifier = TurnClassifier::new(
+        0.0,
+        HashMap::from([
+            ("end:?".to_string(), 4.0),
+            ("word:et".to_string(), -4.0),
+            ("bigram:c'est le".to_string(), -4.0),
+        ]),
+    );
+
+    assert!(classifier.probability("Vous ouvrez lundi ?") > 0.95);
+    assert!(classifier.probability("Oui et") < 0.05);
+    assert!(classifier.probability("Mon numéro c'est le") < 0.05);
+    assert_eq!(classifier.probability("Bonjour."), 0.5);
+
+    // the rules would take the final period for a finished sentence
+    let detector = semantic().with_classifier(Arc::new(classifier));
+    assert_eq!(
+        detector.silence_ms(Some("Bonjour.")),
+        VadSettings::default().full_stop_ms
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn answers_a_finished_sentence_before_the_full_stop() {
+    let audio = [tone(800), silence(1600)].concat();
+
+    let mut fixed = harness("Je voudrais réserver une table.");
+    fixed.speak(&audio).await;
+    assert_eq!(full_stops(&fixed).await, 0);
+
+    let mut harness = harness("Je voudrais réserver une table.").with_turn_detector(semantic());
+    harness.speak(&audio).await;
+    harness.settle().await;
+
+    assert_eq!(full_stops(&harness).await, 1);
+    assert_eq!(
+        harness.transcript(),
+        vec!["user: Je voudrais réserver une table.", "agent: Très bien."]
+    );
+    assert!(!harness.outbound_audio().await.is_empty());
+}
+
+#[tokio::test(start_paused = true)]
+async fn waits_for_a_caller_who_left_their_sentence_hanging() {
+    let audio = [tone(800), silence(4500)].concat();
+
+    let mut fixed = harness("Mon numéro c'est le…");
+    fixed.speak(&audio).await;
+    assert_eq!(full_stops(&fixed).await, 1);
+
+    let mut waiting = harness("Mon numéro c'est le…").with_turn_detector(semantic());
+    waiting.speak(&audio).await;
+
+    assert_eq!(full_stops(&waiting).await, 0);
+    assert!(waiting.outbound_audio().await.is_empty());
+
+    // the answer held since the pause is released once 6 s of silence are over
+    let mut harness = harness("Mon numéro c'est le…").with_turn_detector(semantic());
+    harness.speak(&[tone(800), silence(6500)].concat()).await;
+    harness.settle().await;
+
+    assert_eq!(full_stops(&harness).await, 1);
+    assert_eq!(
+        harness.transcript(),
+        vec!["user: Mon numéro c'est le…", "agent: Très bien."]
+    );
+    assert!(!harness.outbound_audio().await.is_empty());
+}
+
+#[tokio::test(start_paused = true)]
+async fn never_waits_longer_than_the_longest_full_stop() {
+    let mut detector = SemanticTurnDetector::new(Millis(300), Millis(20_000));
+    detector.configure(&VadSettings::default());
+
+    let mut harness = harness("Mon numéro c'est le…").with_turn_detector(detector);
+    harness.speak(&[tone(800), silence(10_500)].concat()).await;
+
+    assert_eq!(full_stops(&harness).await, 1);
+}
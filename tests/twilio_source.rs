@@ -0,0 +1,249 @@
+//! Twilio Media Streams: the events of the stream and the agent audio sent
+//! back on the line.
+
+mod common;
+
+use std::sync::Arc;
+
+use base64::{Engine, engine::general_purpose};
+use serde_json::{Value, json};
+use tokio::sync::mpsc::{Receiver, channel};
+use voicehanler_rs::{
+    domain::{
+        ports::audio_source::{AudioSource, InboundFrame, OutboundFrame},
+        utils::{
+            audio::{
+                codec::{Codec, l16::L16, mulaw::MuLaw},
+                resampler::Resampler,
+            },
+            units::{SampleRate, Samples},
+        },
+    },
+    infrastructure::{
+        audio_source::twilio_source_adapter::TwilioAdapter, llm::mock_llm::MockLlm,
+        stt::mock_stt::MockStt, tts::mock_tts::MockTts,
+    },
+};
+
+use common::{Harness, tone};
+
+fn harness() -> Harness {
+    Harness::new(MockStt::new(vec![]), MockLlm::new(vec![]), MockTts::new())
+}
+
+fn text(message: Value) -> InboundFrame {
+    InboundFrame::Text(message.to_string())
+}
+
+fn start() -> Value {
+    json!({
+        "event": "start",
+        "streamSid": "MZ42",
+        "start": {
+            "accountSid": "AC1",
+            "callSid": "CA1",
+            "mediaFormat": {"encoding": "audio/x-mulaw", "sampleRate": 8000, "channels": 1}
+        }
+    })
+}
+
+fn media(payload: &[u8]) -> InboundFrame {
+    text(json!({
+        "event": "media",
+        "streamSid": "MZ42",
+        "media": {
+            "track": "inbound",
+            "chunk": "1",
+            "timestamp": "0",
+            "payload": general_purpose::STANDARD.encode(payload)
+        }
+    }))
+}
+
+/// A Twilio adapter bound to a socket, and the other end of the socket.
+fn connected() -> (Arc<dyn AudioSource>, Receiver<OutboundFrame>) {
+    let (outbound, line) = channel(1024);
+    (TwilioAdapter::new().connect(outbound), line)
+}
+
+/// The JSON messages sent on the line so far.
+fn sent(line: &mut Receiver<OutboundFrame>) -> Vec<Value> {
+    let mut messages = Vec::new();
+    while let Ok(frame) = line.try_recv() {
+        match frame {
+            OutboundFrame::Text(text) => messages.push(serde_json::from_str(&text).unwrap()),
+            OutboundFrame::Binary(_) => panic!("Twilio only takes text frames"),
+        }
+    }
+    messages
+}
+
+#[tokio::test(start_paused = true)]
+async fn sends_mulaw_frames_of_20_ms_to_the_stream() {
+    let (source, mut line) = connected();
+    harness().stream(source.as_ref(), vec![text(start())]).await;
+
+    // 310 ms, the last frame is cut short
+    let clause = tone(310);
+    source.send_audio(&clause).await.unwrap();
+
+    let messages = sent(&mut line);
+    assert!(messages.iter().all(|message| message["event"] == "media"));
+    assert!(
+        messages
+            .iter()
+            .all(|message| message["streamSid"] == "MZ42")
+    );
+
+    let payloads: Vec<Vec<u8>> = messages
+        .iter()
+        .map(|message| {
+            let payload = message["media"]["payload"].as_str().unwrap();
+            general_purpose::STANDARD.decode(payload).unwrap()
+        })
+        .collect();
+    let sizes: Vec<usize> = payloads.iter().map(Vec::len).collect();
+    assert_eq!(sizes, [vec![160; 15], vec![80]].concat());
+
+    let line_audio = Resampler::new(SampleRate::PIPELINE, SampleRate::TELEPHONY)
+        .unwrap()
+        .resample(&clause);
+    assert_eq!(payloads.concat(), MuLaw.encode(&line_audio).unwrap());
+}
+
+#[tokio::test(start_paused = true)]
+async fn cannot_send_before_the_stream_started() {
+    let (source, mut line) = connected();
+
+    assert!(source.send_audio(&tone(20)).await.is_err());
+    assert!(TwilioAdapter::new().send_audio(&tone(20)).await.is_err());
+
+    // nothing to clear yet
+    source.clear_audio().await.unwrap();
+    assert!(sent(&mut line).is_empty());
+}
+
+#[tokio::test(start_paused = true)]
+async fn clears_the_audio_queued_on_the_stream() {
+    let (source, mut line) = connected();
+    harness().stream(source.as_ref(), vec![text(start())]).await;
+
+    source.clear_audio().await.unwrap();
+
+    assert_eq!(
+        sent(&mut line),
+        [json!({"event": "clear", "streamSid": "MZ42"})]
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn starts_the_call_with_its_parameters_and_format() {
+    let mut start = start();
+    start["start"]["customParameters"] = json!({"caller": "0601020304"});
+
+    let mut harness = harness();
+    let connected = json!({"event": "connected", "protocol": "Call", "version": "1.0.0"});
+    harness
+        .stream(&TwilioAdapter::new(), vec![text(connected), text(start)])
+        .await;
+
+    let metadata = &harness.metadata;
+    assert_eq!(metadata.call_sid.as_deref(), Some("CA1"));
+    assert_eq!(metadata.account_sid.as_deref(), Some("AC1"));
+    assert_eq!(metadata.stream_sid.as_deref(), Some("MZ42"));
+    assert_eq!(
+        metadata.custom_parameters.get("caller").map(String::as_str),
+        Some("0601020304")
+    );
+
+    let format = metadata.media_format.as_ref().unwrap();
+    assert_eq!(
+        (
+            format.encoding.as_str(),
+            format.sample_rate,
+            format.channels
+        ),
+        ("audio/x-mulaw", 8000, 1)
+    );
+    assert!(!harness.ended);
+}
+
+#[tokio::test(start_paused = true)]
+async fn decodes_the_format_the_stream_announced() {
+    let audio = tone(200);
+
+    // μ-law at 8 kHz until told otherwise
+    let mut mulaw = harness();
+    let line_audio = Resampler::new(SampleRate::PIPELINE, SampleRate::TELEPHONY)
+        .unwrap()
+        .resample(&audio);
+    mulaw
+        .stream(
+            &TwilioAdapter::new(),
+            vec![media(&MuLaw.encode(&line_audio).unwrap())],
+        )
+        .await;
+    // resampled up to 16 kHz, but for the filter delay held back
+    let written = mulaw.audio_buffer.user.written();
+    assert!(written <= Samples::of(&audio));
+    assert!(written >= Samples::of(&audio) - Samples(64));
+
+    // 16 kHz L16 needs neither decoding loss nor resampling
+    let mut start = start();
+    start["start"]["mediaFormat"] =
+        json!({"encoding": "audio/L16", "sampleRate": 16000, "channels": 1});
+
+    let mut l16 = harness();
+    l16.stream(
+        &TwilioAdapter::new(),
+        vec![text(start), media(&L16.encode(&audio).unwrap())],
+    )
+    .await;
+    assert_eq!(
+        l16.audio_buffer
+            .user_audio(Samples::ZERO..Samples::of(&audio)),
+        Some(audio)
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn dispatches_marks_digits_and_the_end_of_the_stream() {
+    let mark = json!({"event": "mark", "streamSid": "MZ42", "mark": {"name": "reply-1"}});
+    let dtmf = json!({
+        "event": "dtmf",
+        "streamSid": "MZ42",
+        "dtmf": {"track": "inbound_track", "digit": "5"}
+    });
+    let stop = json!({"event": "stop", "streamSid": "MZ42"});
+
+    let mut harness = harness();
+    harness
+        .stream(
+            &TwilioAdapter::new(),
+            vec![text(start()), text(mark), text(dtmf), text(stop)],
+        )
+        .await;
+
+    let marks: Vec<&String> = harness.audio_buffer.events.values().collect();
+    assert_eq!(marks, ["reply-1"]);
+    assert_eq!(harness.transcript(), ["user: [DTMF] 5"]);
+    assert!(harness.ended);
+}
+
+#[tokio::test(start_paused = true)]
+async fn ignores_unknown_and_malformed_frames() {
+    let frames = vec![
+        text(json!({"event": "bogus", "streamSid": "MZ42"})),
+        InboundFrame::Text("{not json".to_string()),
+        text(json!({"event": "start", "streamSid": "MZ42"})),
+        InboundFrame::Binary(vec![0xFF; 160]),
+    ];
+
+    let mut harness = harness();
+    harness.stream(&TwilioAdapter::new(), frames).await;
+
+    assert!(harness.metadata.call_sid.is_none());
+    assert!(harness.transcript().is_empty());
+    assert_eq!(harness.audio_buffer.user.written(), Samples::ZERO);
+    assert!(!harness.ended);
+}
@@ -0,0 +1,239 @@
+//! Endpointing settings: deployment defaults, agent profiles and session
+//! parameters, and their effect on the turns the energy VAD cuts.
+
+mod common;
+
+use std::{collections::HashMap, f32::consts::PI};
+
+use clap::Parser;
+use voicehanler_rs::{
+    application::env::vad::VadEnv,
+    domain::{
+        entities::{
+            agent_profile::{AdaptiveThreshold, AgentProfile, VadOverrides, VadSettings},
+            agent_registry::AgentRegistry,
+            barge_in::BargeInMode,
+        },
+        ports::vad::{Vad, VadEvent},
+        utils::units::{Millis, SampleRate, Samples},
+    },
+    infrastructure::vad::local_vad::LocalVadAdapter,
+};
+
+fn params(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+    pairs
+        .iter()
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect()
+}
+
+/// One second of a loud 200 Hz tone followed by silence.
+fn sentence() -> Vec<i16> {
+    let mut audio: Vec<i16> = (0..16_000)
+        .map(|n| (4000.0 * (2.0 * PI * 200.0 * n as f32 / 16_000.0).sin()) as i16)
+        .collect();
+    audio.resize(audio.len() + 16_000 * 10, 0);
+    audio
+}
+
+/// Every event with the cursor it was raised at, in samples.
+fn events(settings: &VadSettings) -> Vec<(VadEvent, Samples)> {
+    let mut vad = LocalVadAdapter::new();
+    vad.configure(settings);
+
+    common::events(&mut vad, &sentence())
+        .into_iter()
+        .map(|raised| (raised.event, raised.cursor))
+        .collect()
+}
+
+#[test]
+fn rejects_settings_that_cannot_end_a_turn() {
+    assert!(VadSettings::default().validate().is_ok());
+
+    let invalid = [
+        VadOverrides {
+            threshold: Some(0.0),
+            ..VadOverrides::default()
+        },
+        VadOverrides {
+            frame_ms: Some(Millis(5)),
+            ..VadOverrides::default()
+        },
+        VadOverrides {
+            min_speech_ms: Some(Millis(3000)),
+            ..VadOverrides::default()
+        },
+        VadOverrides {
+            pre_roll_frames: Some(0),
+            ..VadOverrides::default()
+        },
+        VadOverrides {
+            pause_trailer_frames: Some(0),
+            ..VadOverrides::default()
+        },
+        VadOverrides {
+            full_stop_ms: Some(Millis(300)),
+            pause_trailer_frames: Some(10),
+            ..VadOverrides::default()
+        },
+    ];
+
+    for overrides in invalid {
+        assert!(
+            VadSettings::default()
+                .merged(&overrides)
+                .validate()
+                .is_err(),
+            "{:?}",
+            overrides
+        );
+    }
+}
+
+#[derive(Parser)]
+struct Deployment {
+    #[command(flatten)]
+    vad: VadEnv,
+}
+
+#[test]
+fn deployments_turn_the_adaptive_threshold_on() {
+    let fixed = Deployment::parse_from(["voicehandler"])
+        .vad
+        .settings()
+        .unwrap();
+    assert!(fixed.adaptive.is_none());
+
+    let adaptive = Deployment::parse_from([
+        "voicehandler",
+        "--vad-adaptive",
+        "true",
+        "--vad-adaptive-onset-db",
+        "12",
+    ])
+    .vad
+    .settings()
+    .unwrap();
+    let adaptive = adaptive.adaptive.unwrap();
+    assert_eq!(adaptive.onset_db, 12.0);
+    assert_eq!(adaptive.offset_db, AdaptiveThreshold::default().offset_db);
+    assert_eq!(adaptive.window_ms, AdaptiveThreshold::default().window_ms);
+
+    // an offset above the onset would never end speech
+    assert!(
+        Deployment::parse_from([
+            "voicehandler",
+            "--vad-adaptive",
+            "true",
+            "--vad-adaptive-offset-db",
+            "20",
+        ])
+        .vad
+        .settings()
+        .is_err()
+    );
+}
+
+#[test]
+fn parses_session_parameters() {
+    let overrides = VadOverrides::from_params(&params(&[
+        ("agent", "faq"),
+        ("vad_full_stop_ms", "600"),
+        ("vad_pause_trailer_frames", "1"),
+    ]))
+    .unwrap();
+
+    assert_eq!(overrides.full_stop_ms, Some(Millis(600)));
+    assert_eq!(overrides.pause_trailer_frames, Some(1));
+    assert_eq!(overrides.frame_ms, None);
+
+    assert!(VadOverrides::from_params(&params(&[("vad_frame_ms", "fast")])).is_err());
+}
+
+#[test]
+fn sessions_tune_their_agent_and_fall_back_when_invalid() {
+    let dictation = AgentProfile {
+        name: "dictation".to_string(),
+        vad: VadOverrides {
+            full_stop_ms: Some(Millis(4000)),
+            ..VadOverrides::default()
+        },
+        ..AgentProfile::default()
+    };
+    let defaults = VadSettings {
+        min_speech_ms: Millis(300),
+        ..VadSettings::default()
+    };
+    let agents =
+        AgentRegistry::new(vec![dictation], None, defaults, BargeInMode::default()).unwrap();
+    let agent = agents.default_profile();
+
+    let settings = agents.vad_settings(&agent, &VadOverrides::default());
+    assert_eq!(settings.full_stop_ms, Millis(4000));
+    assert_eq!(settings.min_speech_ms, Millis(300));
+
+    let session = VadOverrides {
+        full_stop_ms: Some(Millis(5000)),
+        ..VadOverrides::default()
+    };
+    assert_eq!(
+        agents.vad_settings(&agent, &session).full_stop_ms,
+        Millis(5000)
+    );
+
+    let broken = VadOverrides {
+        frame_ms: Some(Millis(1000)),
+        ..VadOverrides::default()
+    };
+    assert_eq!(agents.vad_settings(&agent, &broken).frame_ms, Millis(32));
+
+    let invalid_profile = AgentProfile {
+        vad: broken,
+        ..AgentProfile::default()
+    };
+    assert!(
+        AgentRegistry::new(
+            vec![invalid_profile],
+            None,
+            defaults,
+            BargeInMode::default()
+        )
+        .is_err()
+    );
+}
+
+#[test]
+fn shorter_full_stop_and_trailer_end_the_turn_sooner() {
+    let full_stop = |events: &[(VadEvent, Samples)]| {
+        events
+            .iter()
+            .find(|(event, _)| matches!(event, VadEvent::SpeechFullStop))
+            .map(|(_, cursor)| *cursor)
+            .unwrap()
+    };
+    let first_pause = |events: &[(VadEvent, Samples)]| {
+        events
+            .iter()
+            .find_map(|(event, cursor)| match event {
+                VadEvent::SpeechPaused(_, end) => Some((*cursor, *end)),
+                _ => None,
+            })
+            .unwrap()
+    };
+
+    let slow = events(&VadSettings::default());
+    let fast = events(&VadSettings {
+        full_stop_ms: Millis(600),
+        pause_trailer_frames: 1,
+        ..VadSettings::default()
+    });
+
+    assert!(full_stop(&fast) < full_stop(&slow), "{:?} {:?}", fast, slow);
+
+    let frame_size = SampleRate::PIPELINE.samples(VadSettings::default().frame_ms);
+    let (slow_at, slow_end) = first_pause(&slow);
+    let (fast_at, fast_end) = first_pause(&fast);
+    assert_eq!(slow_at - fast_at, frame_size);
+    assert_eq!(slow_end - fast_end, frame_size);
+}